@@ -60,7 +60,12 @@ where
 		len: usize,
 	) -> TransactionValidity {
 		if let Some((ref id, ref extra)) = self.signed {
-			Extra::validate(extra, id, &self.function, info, len)
+			if let Some(payer) = extra.fee_sponsor() {
+				// A delegated transaction with fees paid by the doughnut issuer
+				Extra::validate_sponsored(extra, id, &payer, &self.function, info, len)
+			} else {
+				Extra::validate(extra, id, &self.function, info, len)
+			}
 		} else {
 			let valid = Extra::validate_unsigned(&self.function, info, len)?;
 			Ok(valid.combine_with(U::validate_unsigned(&self.function)?))
@@ -73,7 +78,10 @@ where
 		len: usize,
 	) -> crate::ApplyResult {
 		let (pre, res) = if let Some((id, extra)) = self.signed {
-			let pre = Extra::pre_dispatch(&extra, &id, &self.function, info, len)?;
+			let pre = match extra.fee_sponsor() {
+				Some(payer) => Extra::pre_dispatch_sponsored(&extra, &id, &payer, &self.function, info, len)?,
+				None => Extra::pre_dispatch(&extra, &id, &self.function, info, len)?,
+			};
 			if let Some(doughnut) = extra.doughnut() {
 				// A delegated transaction
 				(pre, self.function.dispatch(Origin::from((Some(doughnut.issuer()), Some(doughnut)))))
@@ -86,16 +94,23 @@ where
 			let pre = Extra::pre_dispatch_unsigned(&self.function, info, len)?;
 			(pre, self.function.dispatch(Origin::from((None, None))))
 		};
-		Extra::post_dispatch(pre, info, len);
-		Ok(res.map_err(Into::into))
+		let res: crate::ApplyOutcome = res.map_err(Into::into);
+		Extra::post_dispatch(pre, info, len, &res);
+		Ok(res)
 	}
 }
 
 impl<AccountId, Call, Extra> GetDispatchInfo for CheckedExtrinsic<AccountId, Call, Extra>
 where
 	Call: GetDispatchInfo,
+	Extra: MaybeDoughnut,
 {
 	fn get_dispatch_info(&self) -> DispatchInfo {
-		self.function.get_dispatch_info()
+		let mut info = self.function.get_dispatch_info();
+		// Verifying a doughnut adds to the weight of the call it delegates
+		if let Some((_, ref extra)) = self.signed {
+			info.weight = info.weight.saturating_add(extra.doughnut_weight());
+		}
+		info
 	}
 }
@@ -337,7 +337,10 @@ impl<AccountId, Origin, Call, Extra, Doughnut> Applyable for TestXt<AccountId, C
 		// NOTE: This is lifted directly from the implemenation for `CheckedExtrinsic::apply()`, it handles
 		// switching origin for delegated calls
 		let (pre, res) = if let Some((id, extra)) = self.0 {
-			let pre = Extra::pre_dispatch(&extra, &id, &self.1, info, len)?;
+			let pre = match extra.fee_sponsor() {
+				Some(payer) => Extra::pre_dispatch_sponsored(&extra, &id, &payer, &self.1, info, len)?,
+				None => Extra::pre_dispatch(&extra, &id, &self.1, info, len)?,
+			};
 			if let Some(doughnut) = extra.doughnut() {
 				// A delegated transaction
 				(pre, self.1.dispatch(Origin::from((Some(doughnut.issuer()), Some(doughnut)))))
@@ -351,8 +354,9 @@ impl<AccountId, Origin, Call, Extra, Doughnut> Applyable for TestXt<AccountId, C
 			(pre, self.1.dispatch(Origin::from((None, None))))
 		};
 
-		Extra::post_dispatch(pre, info, len);
-		Ok(res.map_err(Into::into))
+		let res: crate::ApplyOutcome = res.map_err(Into::into);
+		Extra::post_dispatch(pre, info, len, &res);
+		Ok(res)
 	}
 }
 
@@ -371,7 +375,7 @@ pub mod doughnut {
 	//! Doughnut aware types for extrinsic tests
 	//!
 	use super::*;
-	use crate::traits::DoughnutApi;
+	use crate::traits::{DoughnutApi, DoughnutFees, DoughnutWeight};
 	use primitives::crypto::UncheckedFrom;
 
 	/// A test account ID. Stores a `u64` as a byte array
@@ -450,4 +454,12 @@ pub mod doughnut {
 		fn signature_version(&self) -> u8 { 0 }
 		fn get_domain(&self, _domain: &str) -> Option<&[u8]> { None }
 	}
+
+	impl DoughnutFees for TestDoughnut {
+		fn issuer_pays_fees(&self) -> bool { false }
+	}
+
+	impl DoughnutWeight for TestDoughnut {
+		fn verification_weight(&self) -> crate::weights::Weight { 0 }
+	}
 }
\ No newline at end of file
@@ -838,6 +838,36 @@ pub trait SignedExtension: Codec + MaybeDebug + Sync + Send + Clone + Eq + Parti
 			.map_err(Into::into)
 	}
 
+	/// Validate a signed transaction whose fees are sponsored by `payer` rather than paid by the
+	/// signer `who`, e.g. a delegated transaction where the doughnut issuer pays on behalf of the holder.
+	///
+	/// Extensions which charge fees should charge `payer`. By default this delegates to `validate`.
+	fn validate_sponsored(
+		&self,
+		who: &Self::AccountId,
+		_payer: &Self::AccountId,
+		call: &Self::Call,
+		info: DispatchInfo,
+		len: usize,
+	) -> TransactionValidity {
+		self.validate(who, call, info, len)
+	}
+
+	/// Do any pre-flight stuff for a signed transaction whose fees are sponsored by `payer`.
+	///
+	/// Note this function by default delegates to `pre_dispatch`. If you ever override this
+	/// function, you need to make sure to always perform the same validation as in `validate_sponsored`.
+	fn pre_dispatch_sponsored(
+		&self,
+		who: &Self::AccountId,
+		_payer: &Self::AccountId,
+		call: &Self::Call,
+		info: DispatchInfo,
+		len: usize,
+	) -> Result<Self::Pre, crate::ApplyError> {
+		self.pre_dispatch(who, call, info, len)
+	}
+
 	/// Validate an unsigned transaction for the transaction queue.
 	///
 	/// Normally the default implementation is fine since `ValidateUnsigned`
@@ -876,7 +906,9 @@ pub trait SignedExtension: Codec + MaybeDebug + Sync + Send + Clone + Eq + Parti
 	}
 
 	/// Do any post-flight stuff for a transaction.
-	fn post_dispatch(_pre: Self::Pre, _info: DispatchInfo, _len: usize) { }
+	///
+	/// `result` is the outcome of dispatching the transaction's call.
+	fn post_dispatch(_pre: Self::Pre, _info: DispatchInfo, _len: usize, _result: &crate::ApplyOutcome) { }
 }
 
 /// An error that is returned by a dispatchable function of a module.
@@ -920,6 +952,30 @@ impl<AccountId, Call> SignedExtension for Tuple {
 		Ok(for_tuples!( ( #( Tuple.pre_dispatch(who, call, info, len)? ),* ) ))
 	}
 
+	fn validate_sponsored(
+		&self,
+		who: &Self::AccountId,
+		payer: &Self::AccountId,
+		call: &Self::Call,
+		info: DispatchInfo,
+		len: usize,
+	) -> TransactionValidity {
+		let valid = ValidTransaction::default();
+		for_tuples!( #( let valid = valid.combine_with(Tuple.validate_sponsored(who, payer, call, info, len)?); )* );
+		Ok(valid)
+	}
+
+	fn pre_dispatch_sponsored(
+		&self,
+		who: &Self::AccountId,
+		payer: &Self::AccountId,
+		call: &Self::Call,
+		info: DispatchInfo,
+		len: usize,
+	) -> Result<Self::Pre, crate::ApplyError> {
+		Ok(for_tuples!( ( #( Tuple.pre_dispatch_sponsored(who, payer, call, info, len)? ),* ) ))
+	}
+
 	fn validate_unsigned(
 		call: &Self::Call,
 		info: DispatchInfo,
@@ -942,8 +998,9 @@ impl<AccountId, Call> SignedExtension for Tuple {
 		pre: Self::Pre,
 		info: DispatchInfo,
 		len: usize,
+		result: &crate::ApplyOutcome,
 	) {
-		for_tuples!( #( Tuple::post_dispatch(pre.Tuple, info, len); )* )
+		for_tuples!( #( Tuple::post_dispatch(pre.Tuple, info, len, result); )* )
 	}
 }
 
@@ -956,6 +1013,33 @@ pub trait MaybeDoughnut {
 	type Doughnut: Send + Sync + DoughnutApi;
 	/// Return the doughnut from the `SignedExtension` payload, if any
 	fn doughnut(self) -> Option<Self::Doughnut>;
+	/// Return the account sponsoring the transaction fees, if any.
+	/// This is the doughnut issuer when the doughnut obliges its issuer to pay fees.
+	fn fee_sponsor(&self) -> Option<<Self::Doughnut as DoughnutApi>::PublicKey>;
+	/// Return the weight of verifying the doughnut, if any, which adds to the weight of the transaction
+	fn doughnut_weight(&self) -> crate::weights::Weight;
+}
+
+/// A doughnut which may oblige its issuer to pay the fees of the transactions it delegates, i.e.
+/// the issuer sponsors the holder's transactions.
+pub trait DoughnutFees {
+	/// Return whether the doughnut issuer pays transaction fees on behalf of the holder
+	fn issuer_pays_fees(&self) -> bool;
+}
+
+impl DoughnutFees for () {
+	fn issuer_pays_fees(&self) -> bool { false }
+}
+
+/// A doughnut whose verification has a weight beyond that of the call it delegates,
+/// e.g. a delegation chain verifies the signature of every link.
+pub trait DoughnutWeight {
+	/// Return the weight of verifying the doughnut
+	fn verification_weight(&self) -> crate::weights::Weight;
+}
+
+impl DoughnutWeight for () {
+	fn verification_weight(&self) -> crate::weights::Weight { 0 }
 }
 
 // Blanket impl for `Option<T: SignedExtension>`
@@ -974,6 +1058,41 @@ where
 		}
 		Ok(ValidTransaction::default())
 	}
+	fn pre_dispatch(&self, who: &Self::AccountId, call: &Self::Call, info: DispatchInfo, len: usize) -> Result<Self::Pre, crate::ApplyError> {
+		if let Some(inner) = self {
+			return inner.pre_dispatch(who, call, info, len)
+		}
+		Ok(Self::Pre::default())
+	}
+	fn validate_sponsored(
+		&self,
+		who: &Self::AccountId,
+		payer: &Self::AccountId,
+		call: &Self::Call,
+		info: DispatchInfo,
+		len: usize,
+	) -> Result<ValidTransaction, TransactionValidityError> {
+		if let Some(inner) = self {
+			return inner.validate_sponsored(who, payer, call, info, len)
+		}
+		Ok(ValidTransaction::default())
+	}
+	fn pre_dispatch_sponsored(
+		&self,
+		who: &Self::AccountId,
+		payer: &Self::AccountId,
+		call: &Self::Call,
+		info: DispatchInfo,
+		len: usize,
+	) -> Result<Self::Pre, crate::ApplyError> {
+		if let Some(inner) = self {
+			return inner.pre_dispatch_sponsored(who, payer, call, info, len)
+		}
+		Ok(Self::Pre::default())
+	}
+	fn post_dispatch(pre: Self::Pre, info: DispatchInfo, len: usize, result: &crate::ApplyOutcome) {
+		T::post_dispatch(pre, info, len, result)
+	}
 }
 
 /// Only for bare bone testing when you don't care about signed extensions at all.
@@ -1311,13 +1430,21 @@ macro_rules! tuple_impl_indexed {
 	([$($direct:ident)+] ; [$($index:tt,)+]) => {
 		impl<
 			AccountId,
-			Doughnut: SignedExtension<AccountId=AccountId> + DoughnutApi,
+			Doughnut: SignedExtension<AccountId=AccountId> + DoughnutApi + DoughnutFees + DoughnutWeight,
 			$($direct: SignedExtension<AccountId=AccountId>),+
 		> MaybeDoughnut for (Option<Doughnut>, $($direct),+,) {
 			type Doughnut = Doughnut;
 			fn doughnut(self) -> Option<Self::Doughnut> {
 				self.0
 			}
+			fn fee_sponsor(&self) -> Option<<Self::Doughnut as DoughnutApi>::PublicKey> {
+				self.0.as_ref()
+					.filter(|doughnut| doughnut.issuer_pays_fees())
+					.map(|doughnut| doughnut.issuer())
+			}
+			fn doughnut_weight(&self) -> crate::weights::Weight {
+				self.0.as_ref().map(|doughnut| doughnut.verification_weight()).unwrap_or(0)
+			}
 		}
 	};
 	([$($direct:ident)+] [] ; [$($index:tt,)+] []) => {
@@ -195,6 +195,10 @@ impl DoughnutRuntime for Runtime {
 	type Call = Call;
 	type Doughnut = <Self as system::Trait>::Doughnut;
 	type TimestampProvider = timestamp::Module<Runtime>;
+	type RevocationRegistry = ();
+	type UsageTracker = ();
+	type CallSpend = ();
+	type MaxChainLength = ();
 }
 
 parameter_types! {
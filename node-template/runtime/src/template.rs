@@ -131,6 +131,10 @@ mod tests {
 		type Call = <Self as system::Trait>::Call;
 		type Doughnut = <Self as system::Trait>::Doughnut;
 		type TimestampProvider = timestamp::Module<Test>;
+		type RevocationRegistry = ();
+		type UsageTracker = ();
+		type CallSpend = ();
+		type MaxChainLength = ();
 	}
 	type TemplateModule = Module<Test>;
 
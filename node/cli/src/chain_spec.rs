@@ -21,11 +21,12 @@ use primitives::{Pair, Public, crypto::UncheckedInto};
 use serde::{Serialize, Deserialize};
 use node_runtime::{
 	AuthorityDiscoveryConfig, BabeConfig, BalancesConfig, ContractsConfig, CouncilConfig, DemocracyConfig,
-	ElectionsConfig, GrandpaConfig, ImOnlineConfig, IndicesConfig, SessionConfig, SessionKeys, StakerStatus,
-	StakingConfig, SudoConfig, SystemConfig, TechnicalCommitteeConfig, WASM_BINARY,
+	ElectionsConfig, GenericAssetConfig, GrandpaConfig, ImOnlineConfig, IndicesConfig, PriceOracleConfig,
+	SessionConfig, SessionKeys, StakerStatus, StakingConfig, SudoConfig, SystemConfig, TechnicalCommitteeConfig,
+	WASM_BINARY,
 };
 use node_runtime::Block;
-use node_runtime::constants::{time::*, currency::*};
+use node_runtime::constants::{time::*, currency::*, asset::*};
 use substrate_service;
 use hex_literal::hex;
 use substrate_telemetry::TelemetryEndpoints;
@@ -33,8 +34,9 @@ use grandpa_primitives::{AuthorityId as GrandpaId};
 use babe_primitives::{AuthorityId as BabeId};
 use im_online::sr25519::{AuthorityId as ImOnlineId};
 use sr_primitives::Perbill;
+use generic_asset::AssetInfo;
 
-pub use node_primitives::{AccountId, Balance};
+pub use node_primitives::{AccountId, AssetId, Balance};
 pub use node_runtime::GenesisConfig;
 
 const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
@@ -63,6 +65,23 @@ fn session_keys(grandpa: GrandpaId, babe: BabeId, im_online: ImOnlineId) -> Sess
 	SessionKeys { grandpa, babe, im_online, }
 }
 
+fn asset_meta() -> Vec<(AssetId, AssetInfo<Balance>)> {
+	vec![
+		(STAKING_ASSET_ID, AssetInfo {
+			symbol: b"STAKE".to_vec(),
+			decimal_places: 14,
+			name: b"Staking Asset".to_vec(),
+			existential_deposit: None,
+		}),
+		(SPENDING_ASSET_ID, AssetInfo {
+			symbol: b"SPEND".to_vec(),
+			decimal_places: 14,
+			name: b"Spending Asset".to_vec(),
+			existential_deposit: None,
+		}),
+	]
+}
+
 fn staging_testnet_config_genesis() -> GenesisConfig {
 	// stash, controller, session-key
 	// generated with secret:
@@ -142,6 +161,18 @@ fn staging_testnet_config_genesis() -> GenesisConfig {
 				.chain(initial_authorities.iter().map(|x| x.0.clone()))
 				.collect::<Vec<_>>(),
 		}),
+		generic_asset: Some(GenericAssetConfig {
+			assets: vec![STAKING_ASSET_ID, SPENDING_ASSET_ID],
+			initial_balance: ENDOWMENT,
+			endowed_accounts: endowed_accounts.iter().cloned()
+				.chain(initial_authorities.iter().map(|x| x.0.clone()))
+				.collect(),
+			next_asset_id: NEXT_ASSET_ID,
+			staking_asset_id: STAKING_ASSET_ID,
+			spending_asset_id: SPENDING_ASSET_ID,
+			asset_meta: asset_meta(),
+			vesting: vec![],
+		}),
 		session: Some(SessionConfig {
 			keys: initial_authorities.iter().map(|x| {
 				(x.0.clone(), session_keys(x.2.clone(), x.3.clone(), x.4.clone()))
@@ -193,6 +224,9 @@ fn staging_testnet_config_genesis() -> GenesisConfig {
 			authorities: vec![],
 		}),
 		membership_Instance1: Some(Default::default()),
+		price_oracle: Some(PriceOracleConfig {
+			oracles: vec![],
+		}),
 	}
 }
 
@@ -271,6 +305,16 @@ pub fn testnet_genesis(
 			balances: endowed_accounts.iter().map(|k| (k.clone(), ENDOWMENT)).collect(),
 			vesting: vec![],
 		}),
+		generic_asset: Some(GenericAssetConfig {
+			assets: vec![STAKING_ASSET_ID, SPENDING_ASSET_ID],
+			initial_balance: ENDOWMENT,
+			endowed_accounts: endowed_accounts.clone(),
+			next_asset_id: NEXT_ASSET_ID,
+			staking_asset_id: STAKING_ASSET_ID,
+			spending_asset_id: SPENDING_ASSET_ID,
+			asset_meta: asset_meta(),
+			vesting: vec![],
+		}),
 		session: Some(SessionConfig {
 			keys: initial_authorities.iter().map(|x| {
 				(x.0.clone(), session_keys(x.2.clone(), x.3.clone(), x.4.clone()))
@@ -327,6 +371,9 @@ pub fn testnet_genesis(
 			authorities: vec![],
 		}),
 		membership_Instance1: Some(Default::default()),
+		price_oracle: Some(PriceOracleConfig {
+			oracles: vec![],
+		}),
 	}
 }
 
@@ -24,8 +24,8 @@ use rand::rngs::StdRng;
 use codec::{Encode, Decode};
 use keyring::sr25519::Keyring;
 use node_runtime::{
-	Call, CheckedExtrinsic, UncheckedExtrinsic, SignedExtra, BalancesCall, ExistentialDeposit,
-	MinimumPeriod,
+	Call, CheckedExtrinsic, UncheckedExtrinsic, SignedExtra, BalancesCall, GenericAssetCall, ExistentialDeposit,
+	MinimumPeriod, constants::asset::SPENDING_ASSET_ID,
 };
 use primitives::{sr25519, crypto::Pair};
 use sr_primitives::{generic::Era, traits::{Block as BlockT, Header as HeaderT, SignedExtension}};
@@ -57,7 +57,7 @@ impl<Number> FactoryState<Number> {
 			system::CheckEra::from(Era::mortal(256, phase)),
 			system::CheckNonce::from(index),
 			system::CheckWeight::new(),
-			balances::TakeFees::from(0),
+			prml_fee_exchange::TakeFees::new(0, None),
 			Default::default(),
 		)
 	}
@@ -152,6 +152,27 @@ impl RuntimeAdapter for FactoryState<Number> {
 		}, key, ((), version, genesis_hash.clone(), prior_block_hash.clone(), (), (), (), ()))
 	}
 
+	fn batch_transfer_extrinsic(
+		&self,
+		sender: &Self::AccountId,
+		key: &Self::Secret,
+		destinations: &[(Self::AccountId, Self::Balance)],
+		version: u32,
+		genesis_hash: &<Self::Block as BlockT>::Hash,
+		prior_block_hash: &<Self::Block as BlockT>::Hash,
+	) -> <Self::Block as BlockT>::Extrinsic {
+		let index = self.extract_index(&sender, prior_block_hash);
+		let phase = self.extract_phase(*prior_block_hash);
+		let transfers = destinations
+			.iter()
+			.map(|(destination, amount)| (SPENDING_ASSET_ID, destination.clone(), *amount))
+			.collect();
+		sign::<Self>(CheckedExtrinsic {
+			signed: Some((sender.clone(), Self::build_extra(index, phase))),
+			function: Call::GenericAsset(GenericAssetCall::batch_transfer(transfers))
+		}, key, ((), version, genesis_hash.clone(), prior_block_hash.clone(), (), (), (), ()))
+	}
+
 	fn inherent_extrinsics(&self) -> InherentData {
 		let timestamp = (self.block_no as u64 + 1) * MinimumPeriod::get();
 
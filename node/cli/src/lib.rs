@@ -91,7 +91,12 @@ pub struct FactoryCmd {
 	///               B -> E, C -> F, D -> G, ...{n}
 	///               ... x `rounds`
 	///
-	/// These three modes control manufacturing.
+	/// MasterToNBatch: Manufacture a single generic asset batch transfer from
+	///                 the master account to `num` randomly created accounts,
+	///                 one transfer each. `num` may be at most the runtime's
+	///                 maximum number of transfers in a batch.
+	///
+	/// These four modes control manufacturing.
 	#[structopt(long="mode", default_value = "MasterToN")]
 	pub mode: transaction_factory::Mode,
 
@@ -528,7 +528,7 @@ mod tests {
 				let check_era = system::CheckEra::from(Era::Immortal);
 				let check_nonce = system::CheckNonce::from(index);
 				let check_weight = system::CheckWeight::new();
-				let take_fees = balances::TakeFees::from(0);
+				let take_fees = prml_fee_exchange::TakeFees::new(0, None);
 				let extra = (
 					None,
 					check_version,
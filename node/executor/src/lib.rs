@@ -36,11 +36,11 @@ native_executor_instance!(
 #[cfg(test)]
 mod tests {
 	use super::Executor;
-	use {balances, contracts, indices, system, timestamp};
+	use {balances, contracts, generic_asset, indices, session, staking, system, timestamp};
 	use runtime_io;
 	use codec::{Encode, Decode, Joiner};
 	use runtime_support::{
-		Hashable, StorageValue, StorageMap, traits::Currency,
+		Hashable, StorageValue, StorageMap, traits::{Currency, OnUnbalanced},
 	};
 	use state_machine::TestExternalities as CoreTestExternalities;
 	use primitives::{
@@ -56,10 +56,10 @@ mod tests {
 	use system::{EventRecord, Phase};
 	use node_runtime::{
 		Header, Block, UncheckedExtrinsic, CheckedExtrinsic, Call, Runtime, Balances, BuildStorage,
-		System, Event, TransferFee, TransactionBaseFee, TransactionByteFee,
-		constants::currency::*, impls::WeightToFee,
+		System, Event, TransferFee, TransactionBaseFee, TransactionByteFee, GenericAsset, Staking, Timestamp, Treasury,
+		constants::{currency::*, asset::*}, impls::WeightToFee,
 	};
-	use node_primitives::{Balance, Hash, BlockNumber};
+	use node_primitives::{AccountId, Balance, Hash, BlockNumber};
 	use node_testing::keyring::*;
 	use wabt;
 
@@ -889,6 +889,172 @@ mod tests {
 		client.import(BlockOrigin::Own, block).unwrap();
 	}
 
+	#[test]
+	fn generic_asset_transfers_work() {
+		let mut t = new_test_ext(COMPACT_CODE, false);
+
+		let block = construct_block(
+			&mut new_test_ext(COMPACT_CODE, false),
+			1,
+			GENESIS_HASH.into(),
+			vec![
+				CheckedExtrinsic {
+					signed: None,
+					function: Call::Timestamp(timestamp::Call::set(42 * 1000)),
+				},
+				CheckedExtrinsic {
+					signed: Some((alice(), signed_extra(0, 0))),
+					function: Call::GenericAsset(generic_asset::Call::transfer(SPENDING_ASSET_ID, bob(), 10 * DOLLARS)),
+				},
+				CheckedExtrinsic {
+					signed: Some((alice(), signed_extra(1, 0))),
+					function: Call::GenericAsset(generic_asset::Call::transfer(STAKING_ASSET_ID, bob(), 5 * DOLLARS)),
+				},
+			]
+		);
+
+		executor().call::<_, NeverNativeValue, fn() -> _>(
+			&mut t,
+			"Core_execute_block",
+			&block.0,
+			true,
+			None,
+		).0.unwrap();
+
+		runtime_io::with_externalities(&mut t, || {
+			assert_eq!(GenericAsset::free_balance(&SPENDING_ASSET_ID, &alice()), 101 * DOLLARS);
+			assert_eq!(GenericAsset::free_balance(&SPENDING_ASSET_ID, &bob()), 121 * DOLLARS);
+			assert_eq!(GenericAsset::free_balance(&STAKING_ASSET_ID, &alice()), 106 * DOLLARS);
+			assert_eq!(GenericAsset::free_balance(&STAKING_ASSET_ID, &bob()), 116 * DOLLARS);
+			assert_eq!(GenericAsset::try_state(), Ok(()));
+			assert!(System::events().iter().any(|record| record.event == Event::generic_asset(
+				generic_asset::RawEvent::Transferred(SPENDING_ASSET_ID, alice(), bob(), 10 * DOLLARS)
+			)));
+			assert!(System::events().iter().any(|record| record.event == Event::generic_asset(
+				generic_asset::RawEvent::Transferred(STAKING_ASSET_ID, alice(), bob(), 5 * DOLLARS)
+			)));
+		});
+	}
+
+	#[test]
+	fn doughnut_approvals_count_against_spend_limits() {
+		use keyring::AccountKeyring;
+		use prml_doughnut::{MethodDomain, ModuleDomain, PlugDomain, PlugDoughnut, PlugDoughnutChain, UsageLimits};
+		use sr_primitives::{DoughnutV0, traits::DoughnutApi};
+
+		// Alice lets Bob approve up to 100 dollars of her spending asset on her behalf.
+		// Bob approves himself and then draws the allowance with `transfer_from` on his own authority.
+		let domain = PlugDomain::new()
+			.module(ModuleDomain::new(b"srml-generic-asset").method(MethodDomain::new(b"approve")))
+			.limits(UsageLimits::new().max_spend(u64::from(SPENDING_ASSET_ID), 100 * DOLLARS));
+		let mut doughnut = DoughnutV0 {
+			issuer: AccountKeyring::Alice.into(),
+			holder: AccountKeyring::Bob.into(),
+			expiry: u32::max_value(),
+			not_before: 0,
+			payload_version: 0,
+			signature: [0u8; 64].into(),
+			signature_version: 0,
+			domains: vec![("plug".to_string(), domain.encode())],
+		};
+		doughnut.signature = AccountKeyring::Alice.sign(&doughnut.payload()).into();
+		let doughnut = PlugDoughnutChain::<_, Runtime>::new(PlugDoughnut::new(doughnut));
+
+		let delegated_approve = |nonce, amount| {
+			let mut extra = signed_extra(nonce, 0);
+			extra.0 = Some(doughnut.clone());
+			CheckedExtrinsic {
+				signed: Some((bob(), extra)),
+				function: Call::GenericAsset(generic_asset::Call::approve(SPENDING_ASSET_ID, bob(), amount)),
+			}
+		};
+		let apply = |t: &mut TestExternalities<Blake2Hasher>, xt| {
+			let r = executor().call::<_, NeverNativeValue, fn() -> _>(
+				t,
+				"BlockBuilder_apply_extrinsic",
+				&vec![].and(&sign(xt)),
+				true,
+				None,
+			).0.unwrap();
+			ApplyResult::decode(&mut &r.as_encoded()[..]).unwrap()
+		};
+
+		let mut t = new_test_ext(COMPACT_CODE, false);
+		executor().call::<_, NeverNativeValue, fn() -> _>(
+			&mut t,
+			"Core_initialize_block",
+			&vec![].and(&from_block_number(1u32)),
+			true,
+			None,
+		).0.unwrap();
+
+		assert_eq!(apply(&mut t, delegated_approve(0, 101 * DOLLARS)), Err(InvalidTransaction::Custom(174).into()));
+		assert_eq!(apply(&mut t, delegated_approve(0, 100 * DOLLARS)), Ok(Ok(())));
+		assert_eq!(
+			apply(&mut t, CheckedExtrinsic {
+				signed: Some((bob(), signed_extra(1, 0))),
+				function: Call::GenericAsset(
+					generic_asset::Call::transfer_from(SPENDING_ASSET_ID, alice(), bob(), 100 * DOLLARS)
+				),
+			}),
+			Ok(Ok(())),
+		);
+		// The approval used up the doughnut's spending limit
+		assert_eq!(apply(&mut t, delegated_approve(2, 1)), Err(InvalidTransaction::Custom(174).into()));
+
+		runtime_io::with_externalities(&mut t, || {
+			assert_eq!(GenericAsset::free_balance(&SPENDING_ASSET_ID, &alice()), 11 * DOLLARS);
+			assert_eq!(GenericAsset::free_balance(&SPENDING_ASSET_ID, &bob()), 211 * DOLLARS);
+		});
+	}
+
+	#[test]
+	fn staking_rewards_are_paid_in_the_spending_asset() {
+		let mut t = new_test_ext(COMPACT_CODE, false);
+
+		runtime_io::with_externalities(&mut t, || {
+			let staked = GenericAsset::free_balance(&STAKING_ASSET_ID, &dave());
+			let spending = GenericAsset::free_balance(&SPENDING_ASSET_ID, &dave());
+			let spending_issuance = GenericAsset::total_issuance(&SPENDING_ASSET_ID);
+
+			// an hour into the era, dave's validator earns some points and the era ends
+			Timestamp::set_timestamp(60 * 60 * 1000);
+			Staking::reward_by_ids(vec![(dave(), 20)]);
+			staking::ForceEra::put(staking::Forcing::ForceNew);
+			<Staking as session::OnSessionEnding<AccountId>>::on_session_ending(0, 1);
+
+			let reward = GenericAsset::free_balance(&SPENDING_ASSET_ID, &dave()) - spending;
+			assert!(reward > 0);
+			// the treasury is funded in the spending asset to offset the dilution
+			let treasury_funding = GenericAsset::free_balance(&SPENDING_ASSET_ID, &Treasury::account_id());
+			assert!(treasury_funding > 0);
+			assert_eq!(GenericAsset::total_issuance(&SPENDING_ASSET_ID), spending_issuance + reward + treasury_funding);
+			// the stake is not changed by rewards in another asset
+			assert_eq!(GenericAsset::free_balance(&STAKING_ASSET_ID, &dave()), staked);
+			assert_eq!(Staking::ledger(&alice()).unwrap().active, 111 * DOLLARS);
+		});
+	}
+
+	#[test]
+	fn staking_slashes_are_sent_to_the_treasury() {
+		let mut t = new_test_ext(COMPACT_CODE, false);
+
+		runtime_io::with_externalities(&mut t, || {
+			let staked = GenericAsset::free_balance(&STAKING_ASSET_ID, &alice());
+			let staking_issuance = GenericAsset::total_issuance(&STAKING_ASSET_ID);
+
+			let (imbalance, _) = <generic_asset::StakingAssetCurrency<Runtime> as Currency<AccountId>>::slash(
+				&alice(),
+				10 * DOLLARS,
+			);
+			<Runtime as staking::Trait>::Slash::on_unbalanced(imbalance);
+
+			let slashed = staked - GenericAsset::free_balance(&STAKING_ASSET_ID, &alice());
+			assert!(slashed > 0);
+			assert_eq!(GenericAsset::free_balance(&STAKING_ASSET_ID, &Treasury::account_id()), slashed);
+			assert_eq!(GenericAsset::total_issuance(&STAKING_ASSET_ID), staking_issuance);
+		});
+	}
 
 	#[test]
 	fn weight_multiplier_increases_and_decreases_on_big_weight() {
@@ -46,6 +46,9 @@ pub type AccountIndex = u32;
 /// Balance of an account.
 pub type Balance = u128;
 
+/// Identifier of a generic asset.
+pub type AssetId = u32;
+
 /// The runtime supported proof of delegation format
 pub type Doughnut = DoughnutV0;
 
@@ -94,6 +97,124 @@ pub enum ContractExecResult {
 	Error,
 }
 
+/// The trace of a contract call or instantiation made during a dry run.
+#[derive(Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct ContractCallTrace {
+	/// The account called or instantiated.
+	pub dest: AccountId,
+	/// The gas used by the call, including the gas used by its nested calls.
+	pub gas_used: u64,
+	/// Whether the call ran to completion with a successful status code.
+	pub success: bool,
+	/// The storage keys read by the contract.
+	pub storage_reads: Vec<[u8; 32]>,
+	/// The storage keys written or removed by the contract.
+	pub storage_writes: Vec<[u8; 32]>,
+	/// The calls and instantiations made by the contract, in order.
+	pub nested: Vec<ContractCallTrace>,
+}
+
+/// A result of a dry run of a contract call.
+#[derive(Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct ContractDryRunResult {
+	/// The result of the call.
+	pub result: ContractExecResult,
+	/// The gas used by the call.
+	pub gas_used: u64,
+	/// The trace of the call, or `None` if the call failed before it was made.
+	pub trace: Option<ContractCallTrace>,
+	/// The encoded runtime events that would be deposited.
+	pub events: Vec<Vec<u8>>,
+	/// The transfers that would be made, as `(from, to, value)`.
+	pub transfers: Vec<(AccountId, AccountId, Balance)>,
+	/// The generic asset transfers that would be made, as `(asset_id, from, to, value)`.
+	pub asset_transfers: Vec<(AssetId, AccountId, AccountId, Balance)>,
+	/// The encoded runtime calls that would be dispatched, with the contract dispatching each.
+	pub dispatches: Vec<(AccountId, Vec<u8>)>,
+}
+
+/// Details of a doughnut, as decoded by the runtime.
+#[derive(Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct DoughnutInfo {
+	/// The account delegating its authority. For a delegation chain, the root issuer.
+	pub issuer: AccountId,
+	/// The account authority is delegated to. For a delegation chain, the final holder.
+	pub holder: AccountId,
+	/// The timestamp from which the doughnut may be used.
+	pub not_before: u32,
+	/// The timestamp from which the doughnut has expired.
+	pub expiry: u32,
+	/// The number of delegation links. A doughnut which has not been re-delegated has one link.
+	pub links: u32,
+}
+
+/// The verdict of checking whether a doughnut authorizes its holder to dispatch a call.
+#[derive(Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub enum DoughnutVerdict {
+	/// The doughnut authorizes the call.
+	Authorized,
+	/// The doughnut could not be decoded.
+	MalformedDoughnut,
+	/// The call could not be decoded.
+	MalformedCall,
+	/// The doughnut, or a link of its delegation chain, has an invalid signature.
+	InvalidSignature,
+	/// The doughnut may not be used by the holder at the current timestamp.
+	InvalidHolderOrTime,
+	/// The doughnut has been revoked by its issuer.
+	Revoked,
+	/// The doughnut has been used the maximum number of times.
+	MaxUsesExceeded,
+	/// The call would exceed the doughnut's spending limit.
+	SpendLimitExceeded,
+	/// A link of the delegation chain is not held by the issuer of the next.
+	BrokenChain,
+	/// The doughnut has usage limits which the runtime does not enforce.
+	UnenforceableLimits,
+	/// The doughnut has spending limits and the runtime does not account the spend of the call.
+	UnaccountedSpend,
+	/// The doughnut does not permit the call.
+	///
+	/// Contains the reason given by the dispatch verifier.
+	Unauthorized(Vec<u8>),
+}
+
+/// Metadata of a generic asset, describing how it is presented to users.
+#[derive(Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct AssetInfo {
+	/// The ticker symbol of the asset.
+	pub symbol: Vec<u8>,
+	/// The number of decimal places used to display an amount of the asset.
+	pub decimal_places: u8,
+	/// The display name of the asset.
+	pub name: Vec<u8>,
+	/// The minimum balance an account should hold of the asset, if any.
+	pub existential_deposit: Option<Balance>,
+}
+
+/// The total issuance of a generic asset alongside the sums of the balances of its holders.
+#[derive(Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct IssuanceAudit {
+	/// The recorded total issuance of the asset.
+	pub total_issuance: Balance,
+	/// The sum of the free balances of the asset.
+	pub free: Balance,
+	/// The sum of the reserved balances of the asset.
+	pub reserved: Balance,
+}
+
 client::decl_runtime_apis! {
 	/// The API to query account account nonce (aka index).
 	pub trait AccountNonceApi {
@@ -113,5 +234,47 @@ client::decl_runtime_apis! {
 			gas_limit: u64,
 			input_data: Vec<u8>,
 		) -> ContractExecResult;
+
+		/// Perform a dry run of a call from a specified account to a given contract.
+		///
+		/// Nothing is committed. Instead, the trace of the call and the events, transfers and
+		/// runtime calls it would result in are returned.
+		fn dry_run_call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			input_data: Vec<u8>,
+		) -> ContractDryRunResult;
+	}
+
+	/// The API to inspect and validate doughnuts without submitting an extrinsic.
+	pub trait DoughnutInspectionApi {
+		/// Decode a doughnut and return its details, or `None` if it is malformed.
+		fn inspect_doughnut(doughnut: Vec<u8>) -> Option<DoughnutInfo>;
+
+		/// Check whether a doughnut authorizes `holder` to dispatch the encoded `call` at the current block.
+		///
+		/// The signature, time window, revocation and usage limits of the doughnut are checked as
+		/// they would be for an extrinsic, followed by its permission to dispatch `call`.
+		fn validate_doughnut(holder: AccountId, doughnut: Vec<u8>, call: Vec<u8>) -> DoughnutVerdict;
+	}
+
+	/// The API to query the metadata of generic assets.
+	pub trait GenericAssetApi {
+		/// Get the metadata of an asset, or `None` if none has been registered.
+		fn asset_info(asset_id: AssetId) -> Option<AssetInfo>;
+
+		/// Get the ids and metadata of all assets with registered metadata.
+		fn registered_assets() -> Vec<(AssetId, AssetInfo)>;
+
+		/// Get the amount of `owner`'s balance of an asset that `spender` may transfer.
+		fn allowance(asset_id: AssetId, owner: AccountId, spender: AccountId) -> Balance;
+
+		/// Get the total issuance of an asset alongside the sums of the balances of its holders.
+		fn audit_issuance(asset_id: AssetId) -> IssuanceAudit;
+
+		/// Get the audits of all assets whose total issuance does not match their balances.
+		fn issuance_discrepancies() -> Vec<(AssetId, IssuanceAudit)>;
 	}
 }
@@ -23,7 +23,8 @@ use client::blockchain::HeaderBackend;
 use jsonrpc_core::{Error, ErrorCode, Result};
 use jsonrpc_derive::rpc;
 use node_primitives::{
-	AccountId, Balance, Block, BlockId, ContractExecResult, ContractsApi as ContractsRuntimeApi,
+	AccountId, Balance, Block, BlockId, ContractDryRunResult, ContractExecResult,
+	ContractsApi as ContractsRuntimeApi,
 };
 use sr_primitives::traits::{
 	self,
@@ -58,6 +59,21 @@ pub trait ContractsApi<BlockHash> {
 		call_request: CallRequest,
 		at: Option<BlockHash>,
 	) -> Result<ContractExecResult>;
+
+	/// Performs a dry run of a call to a contract, tracing its execution.
+	///
+	/// Like `contracts_call`, this is performed locally without submitting any transactions. On top
+	/// of the result of the call, it returns the gas used by the call and each of its nested calls,
+	/// the storage keys they read and wrote, and the events, transfers and runtime calls the call
+	/// would result in.
+	///
+	/// This method is useful for estimating the gas limit of a call and previewing its effects.
+	#[rpc(name = "contracts_dryRun")]
+	fn dry_run(
+		&self,
+		call_request: CallRequest,
+		at: Option<BlockHash>,
+	) -> Result<ContractDryRunResult>;
 }
 
 /// An implementation of contract specific RPC methods.
@@ -70,6 +86,13 @@ impl<C> Contracts<C> {
 	pub fn new(client: Arc<C>) -> Self {
 		Contracts { client }
 	}
+
+	fn block_id(&self, at: Option<<Block as BlockT>::Hash>) -> BlockId where C: HeaderBackend<Block> {
+		BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		))
+	}
 }
 
 impl<C> ContractsApi<<Block as BlockT>::Hash> for Contracts<C>
@@ -85,10 +108,7 @@ where
 		at: Option<<Block as BlockT>::Hash>,
 	) -> Result<ContractExecResult> {
 		let api = self.client.runtime_api();
-		let at = BlockId::hash(at.unwrap_or_else(||
-			// If the block hash is not supplied assume the best block.
-			self.client.info().best_hash
-		));
+		let at = self.block_id(at);
 
 		let CallRequest {
 			origin,
@@ -97,20 +117,52 @@ where
 			gas_limit,
 			input_data
 		} = call_request;
-		let gas_limit = gas_limit.to_number().map_err(|e| Error {
-			code: ErrorCode::InvalidParams,
-			message: e,
-			data: None,
-		})?;
+		let gas_limit = to_gas_limit(gas_limit)?;
 
 		let exec_result = api
 			.call(&at, origin, dest, value, gas_limit, input_data)
-			.map_err(|e| Error {
-				code: ErrorCode::ServerError(crate::constants::RUNTIME_ERROR),
-				message: "Runtime trapped while executing a contract.".into(),
-				data: Some(format!("{:?}", e).into()),
-			})?;
+			.map_err(runtime_error)?;
 
 		Ok(exec_result)
 	}
+
+	fn dry_run(
+		&self,
+		call_request: CallRequest,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<ContractDryRunResult> {
+		let api = self.client.runtime_api();
+		let at = self.block_id(at);
+
+		let CallRequest {
+			origin,
+			dest,
+			value,
+			gas_limit,
+			input_data
+		} = call_request;
+		let gas_limit = to_gas_limit(gas_limit)?;
+
+		let dry_run_result = api
+			.dry_run_call(&at, origin, dest, value, gas_limit, input_data)
+			.map_err(runtime_error)?;
+
+		Ok(dry_run_result)
+	}
+}
+
+fn to_gas_limit(gas_limit: number::NumberOrHex<u64>) -> Result<u64> {
+	gas_limit.to_number().map_err(|e| Error {
+		code: ErrorCode::InvalidParams,
+		message: e,
+		data: None,
+	})
+}
+
+fn runtime_error(e: impl std::fmt::Debug) -> Error {
+	Error {
+		code: ErrorCode::ServerError(crate::constants::RUNTIME_ERROR),
+		message: "Runtime trapped while executing a contract.".into(),
+		data: Some(format!("{:?}", e).into()),
+	}
 }
@@ -0,0 +1,210 @@
+// Copyright 2019 Plug New Zealand Limited
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Node-specific RPC methods for inspecting and validating doughnuts.
+
+use std::sync::Arc;
+
+use serde::{Serialize, Deserialize};
+use client::blockchain::HeaderBackend;
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use node_primitives::{
+	AccountId, Block, BlockId, DoughnutInspectionApi as DoughnutRuntimeApi, DoughnutInfo, DoughnutVerdict,
+};
+use sr_primitives::traits::{
+	self,
+	Block as BlockT,
+};
+use substrate_primitives::Bytes;
+
+pub use self::gen_client::Client as DoughnutClient;
+
+/// A struct that encodes RPC parameters required to validate a doughnut.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all="camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct ValidateRequest {
+	holder: AccountId,
+	doughnut: Bytes,
+	call: Bytes,
+}
+
+/// Doughnut RPC methods.
+#[rpc]
+pub trait DoughnutApi<BlockHash> {
+	/// Decodes a doughnut and returns its details, or `None` if it is malformed.
+	#[rpc(name = "doughnut_inspect")]
+	fn inspect(
+		&self,
+		doughnut: Bytes,
+		at: Option<BlockHash>,
+	) -> Result<Option<DoughnutInfo>>;
+
+	/// Checks whether a doughnut authorizes its holder to dispatch an encoded call.
+	///
+	/// The signature, time window, revocation, usage limits and dispatch permission of the doughnut
+	/// are checked locally without submitting any transactions.
+	#[rpc(name = "doughnut_validate")]
+	fn validate(
+		&self,
+		validate_request: ValidateRequest,
+		at: Option<BlockHash>,
+	) -> Result<DoughnutVerdict>;
+}
+
+/// An implementation of doughnut specific RPC methods.
+pub struct Doughnut<C> {
+	client: Arc<C>,
+}
+
+impl<C> Doughnut<C> {
+	/// Create new `Doughnut` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Doughnut { client }
+	}
+
+	fn block_id(&self, at: Option<<Block as BlockT>::Hash>) -> BlockId where C: HeaderBackend<Block> {
+		BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		))
+	}
+}
+
+impl<C> DoughnutApi<<Block as BlockT>::Hash> for Doughnut<C>
+where
+	C: Send + Sync + 'static,
+	C: traits::ProvideRuntimeApi,
+	C: HeaderBackend<Block>,
+	C::Api: DoughnutRuntimeApi<Block>,
+{
+	fn inspect(
+		&self,
+		doughnut: Bytes,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<DoughnutInfo>> {
+		let api = self.client.runtime_api();
+		let at = self.block_id(at);
+
+		api.inspect_doughnut(&at, doughnut.0).map_err(|e| Error {
+			code: ErrorCode::ServerError(crate::constants::RUNTIME_ERROR),
+			message: "Runtime trapped while inspecting a doughnut.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})
+	}
+
+	fn validate(
+		&self,
+		validate_request: ValidateRequest,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<DoughnutVerdict> {
+		let api = self.client.runtime_api();
+		let at = self.block_id(at);
+
+		let ValidateRequest {
+			holder,
+			doughnut,
+			call,
+		} = validate_request;
+
+		api.validate_doughnut(&at, holder, doughnut.0, call.0).map_err(|e| Error {
+			code: ErrorCode::ServerError(crate::constants::RUNTIME_ERROR),
+			message: "Runtime trapped while validating a doughnut.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use codec::Encode;
+	use keyring::AccountKeyring;
+	use node_runtime::{BalancesCall, Call, Runtime};
+	use node_testing::{
+		client::{TestClientBuilder, TestClientBuilderExt},
+		keyring::{alice, bob, charlie},
+	};
+	use prml_doughnut::{PlugDomain, PlugDoughnut, PlugDoughnutChain, UsageLimits};
+	use sr_primitives::{DoughnutV0, traits::DoughnutApi as _};
+	use substrate_primitives::Pair;
+
+	fn make_doughnut() -> PlugDoughnutChain<DoughnutV0, Runtime> {
+		make_doughnut_with_domain(PlugDomain::new())
+	}
+
+	fn make_doughnut_with_domain(domain: PlugDomain) -> PlugDoughnutChain<DoughnutV0, Runtime> {
+		let issuer = AccountKeyring::Alice.pair();
+		let mut doughnut = DoughnutV0 {
+			issuer: AccountKeyring::Alice.into(),
+			holder: AccountKeyring::Bob.into(),
+			expiry: u32::max_value(),
+			not_before: 0,
+			payload_version: 0,
+			signature: [0u8; 64].into(),
+			signature_version: 0,
+			domains: vec![("plug".to_string(), domain.encode())],
+		};
+		doughnut.signature = issuer.sign(&doughnut.payload()).into();
+		PlugDoughnutChain::new(PlugDoughnut::new(doughnut))
+	}
+
+	#[test]
+	fn should_inspect_doughnut_through_runtime_api() {
+		let client = Arc::new(TestClientBuilder::new().build());
+		let doughnut = Doughnut::new(client);
+
+		assert_eq!(
+			doughnut.inspect(make_doughnut().encode().into(), None).unwrap(),
+			Some(DoughnutInfo {
+				issuer: alice(),
+				holder: bob(),
+				not_before: 0,
+				expiry: u32::max_value(),
+				links: 1,
+			}),
+		);
+		assert_eq!(doughnut.inspect(vec![1, 2, 3].into(), None).unwrap(), None);
+	}
+
+	#[test]
+	fn should_validate_doughnut_through_runtime_api() {
+		let client = Arc::new(TestClientBuilder::new().build());
+		let doughnut = Doughnut::new(client);
+		let call = Call::Balances(BalancesCall::transfer(charlie().into(), 1));
+
+		let validate = |doughnut_bytes: Vec<u8>, call_bytes: Vec<u8>| doughnut.validate(
+			ValidateRequest {
+				holder: bob(),
+				doughnut: doughnut_bytes.into(),
+				call: call_bytes.into(),
+			},
+			None,
+		).unwrap();
+
+		assert_eq!(validate(vec![1, 2, 3], call.encode()), DoughnutVerdict::MalformedDoughnut);
+		assert_eq!(validate(make_doughnut().encode(), vec![255]), DoughnutVerdict::MalformedCall);
+		// The plug domain grants no module permissions
+		assert!(match validate(make_doughnut().encode(), call.encode()) {
+			DoughnutVerdict::Unauthorized(_) => true,
+			_ => false,
+		});
+		// The runtime does not account the spend of `force_transfer`
+		let limited = make_doughnut_with_domain(PlugDomain::new().limits(UsageLimits::new().max_spend(0, 1)));
+		let force_transfer = Call::Balances(BalancesCall::force_transfer(alice().into(), charlie().into(), 1));
+		assert_eq!(validate(limited.encode(), force_transfer.encode()), DoughnutVerdict::UnaccountedSpend);
+	}
+}
@@ -0,0 +1,174 @@
+// Copyright 2019 Plug New Zealand Limited
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Node-specific RPC methods for querying generic asset metadata, allowances and issuance audits.
+
+use std::sync::Arc;
+
+use client::blockchain::HeaderBackend;
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use node_primitives::{
+	AccountId, AssetId, AssetInfo, Balance, Block, IssuanceAudit, BlockId, GenericAssetApi as GenericAssetRuntimeApi,
+};
+use sr_primitives::traits::{
+	self,
+	Block as BlockT,
+};
+
+pub use self::gen_client::Client as GenericAssetClient;
+
+/// Generic asset RPC methods.
+#[rpc]
+pub trait GenericAssetApi<BlockHash> {
+	/// Returns the metadata of an asset, or `None` if none has been registered.
+	#[rpc(name = "genericAsset_assetInfo")]
+	fn asset_info(
+		&self,
+		asset_id: AssetId,
+		at: Option<BlockHash>,
+	) -> Result<Option<AssetInfo>>;
+
+	/// Returns the ids and metadata of all assets with registered metadata.
+	#[rpc(name = "genericAsset_registeredAssets")]
+	fn registered_assets(
+		&self,
+		at: Option<BlockHash>,
+	) -> Result<Vec<(AssetId, AssetInfo)>>;
+
+	/// Returns the amount of `owner`'s balance of an asset that `spender` may transfer.
+	#[rpc(name = "genericAsset_allowance")]
+	fn allowance(
+		&self,
+		asset_id: AssetId,
+		owner: AccountId,
+		spender: AccountId,
+		at: Option<BlockHash>,
+	) -> Result<Balance>;
+
+	/// Returns the total issuance of an asset alongside the sums of the balances of its holders.
+	#[rpc(name = "genericAsset_auditIssuance")]
+	fn audit_issuance(
+		&self,
+		asset_id: AssetId,
+		at: Option<BlockHash>,
+	) -> Result<IssuanceAudit>;
+
+	/// Returns the audits of all assets whose total issuance does not match their balances.
+	#[rpc(name = "genericAsset_issuanceDiscrepancies")]
+	fn issuance_discrepancies(
+		&self,
+		at: Option<BlockHash>,
+	) -> Result<Vec<(AssetId, IssuanceAudit)>>;
+}
+
+/// An implementation of generic asset specific RPC methods.
+pub struct GenericAsset<C> {
+	client: Arc<C>,
+}
+
+impl<C> GenericAsset<C> {
+	/// Create new `GenericAsset` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		GenericAsset { client }
+	}
+
+	fn block_id(&self, at: Option<<Block as BlockT>::Hash>) -> BlockId where C: HeaderBackend<Block> {
+		BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash
+		))
+	}
+}
+
+impl<C> GenericAssetApi<<Block as BlockT>::Hash> for GenericAsset<C>
+where
+	C: Send + Sync + 'static,
+	C: traits::ProvideRuntimeApi,
+	C: HeaderBackend<Block>,
+	C::Api: GenericAssetRuntimeApi<Block>,
+{
+	fn asset_info(
+		&self,
+		asset_id: AssetId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<AssetInfo>> {
+		let api = self.client.runtime_api();
+		let at = self.block_id(at);
+
+		api.asset_info(&at, asset_id).map_err(|e| Error {
+			code: ErrorCode::ServerError(crate::constants::RUNTIME_ERROR),
+			message: "Runtime trapped while querying asset info.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})
+	}
+
+	fn registered_assets(
+		&self,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<(AssetId, AssetInfo)>> {
+		let api = self.client.runtime_api();
+		let at = self.block_id(at);
+
+		api.registered_assets(&at).map_err(|e| Error {
+			code: ErrorCode::ServerError(crate::constants::RUNTIME_ERROR),
+			message: "Runtime trapped while querying registered assets.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})
+	}
+	fn allowance(
+		&self,
+		asset_id: AssetId,
+		owner: AccountId,
+		spender: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Balance> {
+		let api = self.client.runtime_api();
+		let at = self.block_id(at);
+
+		api.allowance(&at, asset_id, owner, spender).map_err(|e| Error {
+			code: ErrorCode::ServerError(crate::constants::RUNTIME_ERROR),
+			message: "Runtime trapped while querying allowance.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})
+	}
+	fn audit_issuance(
+		&self,
+		asset_id: AssetId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<IssuanceAudit> {
+		let api = self.client.runtime_api();
+		let at = self.block_id(at);
+
+		api.audit_issuance(&at, asset_id).map_err(|e| Error {
+			code: ErrorCode::ServerError(crate::constants::RUNTIME_ERROR),
+			message: "Runtime trapped while auditing issuance.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})
+	}
+
+	fn issuance_discrepancies(
+		&self,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<(AssetId, IssuanceAudit)>> {
+		let api = self.client.runtime_api();
+		let at = self.block_id(at);
+
+		api.issuance_discrepancies(&at).map_err(|e| Error {
+			code: ErrorCode::ServerError(crate::constants::RUNTIME_ERROR),
+			message: "Runtime trapped while querying issuance discrepancies.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})
+	}
+}
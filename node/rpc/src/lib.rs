@@ -31,12 +31,14 @@
 
 use std::sync::Arc;
 
-use node_primitives::{Block, AccountNonceApi, ContractsApi};
+use node_primitives::{Block, AccountNonceApi, ContractsApi, DoughnutInspectionApi, GenericAssetApi};
 use sr_primitives::traits::ProvideRuntimeApi;
 use transaction_pool::txpool::{ChainApi, Pool};
 
 pub mod accounts;
 pub mod contracts;
+pub mod doughnut;
+pub mod generic_asset;
 
 mod constants {
 	/// A status code indicating an error happened while trying to call into the runtime.
@@ -50,13 +52,15 @@ pub fn create<C, P, M>(client: Arc<C>, pool: Arc<Pool<P>>) -> jsonrpc_core::IoHa
 	C: ProvideRuntimeApi,
 	C: client::blockchain::HeaderBackend<Block>,
 	C: Send + Sync + 'static,
-	C::Api: AccountNonceApi<Block> + ContractsApi<Block>,
+	C::Api: AccountNonceApi<Block> + ContractsApi<Block> + DoughnutInspectionApi<Block> + GenericAssetApi<Block>,
 	P: ChainApi + Sync + Send + 'static,
 	M: jsonrpc_core::Metadata + Default,
 {
 	use self::{
 		accounts::{Accounts, AccountsApi},
 		contracts::{Contracts, ContractsApi},
+		doughnut::{Doughnut, DoughnutApi},
+		generic_asset::{GenericAsset, GenericAssetApi},
 	};
 
 	let mut io = jsonrpc_core::IoHandler::default();
@@ -64,7 +68,13 @@ pub fn create<C, P, M>(client: Arc<C>, pool: Arc<Pool<P>>) -> jsonrpc_core::IoHa
 		AccountsApi::to_delegate(Accounts::new(client.clone(), pool))
 	);
 	io.extend_with(
-		ContractsApi::to_delegate(Contracts::new(client))
+		ContractsApi::to_delegate(Contracts::new(client.clone()))
+	);
+	io.extend_with(
+		DoughnutApi::to_delegate(Doughnut::new(client.clone()))
+	);
+	io.extend_with(
+		GenericAssetApi::to_delegate(GenericAsset::new(client))
 	);
 	io
 }
@@ -25,6 +25,18 @@ pub mod currency {
 	pub const DOLLARS: Balance = 100 * CENTS;
 }
 
+/// Generic asset identifiers.
+pub mod asset {
+	use node_primitives::AssetId;
+
+	/// The asset staked by validators and nominators.
+	pub const STAKING_ASSET_ID: AssetId = 16000;
+	/// The asset used for payments, staking rewards are paid in it.
+	pub const SPENDING_ASSET_ID: AssetId = 16001;
+	/// The first identifier available to user created assets.
+	pub const NEXT_ASSET_ID: AssetId = 17000;
+}
+
 /// Time.
 pub mod time {
 	use node_primitives::{Moment, BlockNumber};
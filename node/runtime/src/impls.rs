@@ -16,12 +16,20 @@
 
 //! Some configurable implementations as associated type for the substrate runtime.
 
-use node_primitives::Balance;
+use node_primitives::{AccountId, Balance};
+use rstd::prelude::*;
 use sr_primitives::weights::{Weight, WeightMultiplier};
-use sr_primitives::traits::{Convert, Saturating};
-use sr_primitives::Fixed64;
-use support::traits::{OnUnbalanced, Currency};
-use crate::{Balances, Authorship, MaximumBlockWeight, NegativeImbalance};
+use sr_primitives::traits::{CheckedSub, Convert, Saturating, Zero};
+use sr_primitives::{Fixed64, Perbill};
+use support::additional_traits::DelegatedCallSpend;
+use support::traits::{OnDilution, OnUnbalanced, Currency};
+use contracts::Call as ContractsCall;
+use generic_asset::DustImbalance;
+use staking::Call as StakingCall;
+use crate::{
+	Balances, BalancesCall, GenericAsset, GenericAssetCall, Authorship, Call, MaximumBlockWeight, NegativeImbalance,
+	Runtime, Treasury,
+};
 use crate::constants::fee::TARGET_BLOCK_FULLNESS;
 
 pub struct Author;
@@ -31,12 +39,55 @@ impl OnUnbalanced<NegativeImbalance> for Author {
 	}
 }
 
+/// Splits fees paid in exchange assets as `DealWithFees` does, 80% to the treasury and 20% to
+/// the block author, in the asset they were paid with.
+pub struct DealWithAssetFees;
+impl OnUnbalanced<DustImbalance<Runtime>> for DealWithAssetFees {
+	fn on_unbalanced(fees: DustImbalance<Runtime>) {
+		let to_author = fees.peek() / 5;
+		let (to_author, to_treasury) = fees.split(to_author);
+		GenericAsset::resolve_imbalance(&Authorship::author(), to_author);
+		GenericAsset::resolve_imbalance(&Treasury::account_id(), to_treasury);
+	}
+}
+
+type StakingAssetCurrency = generic_asset::StakingAssetCurrency<Runtime>;
+type SpendingAssetCurrency = generic_asset::SpendingAssetCurrency<Runtime>;
+
+/// Sends the staking asset slashed from stakers to the treasury account.
+pub struct SlashToTreasury;
+impl OnUnbalanced<<StakingAssetCurrency as Currency<AccountId>>::NegativeImbalance> for SlashToTreasury {
+	fn on_unbalanced(amount: <StakingAssetCurrency as Currency<AccountId>>::NegativeImbalance) {
+		StakingAssetCurrency::resolve_creating(&Treasury::account_id(), amount);
+	}
+}
+
+/// Mints spending asset for the treasury account when staking rewards are minted, keeping the
+/// ratio of the rewarded portion to the total issuance the same, as `treasury` does for its own
+/// currency.
+pub struct RewardDilutionToTreasury;
+impl OnDilution<Balance> for RewardDilutionToTreasury {
+	fn on_dilution(minted: Balance, portion: Balance) {
+		if !minted.is_zero() && !portion.is_zero() {
+			let total_issuance = SpendingAssetCurrency::total_issuance();
+			if let Some(funding) = total_issuance.checked_sub(&portion) {
+				let increase_ratio = Perbill::from_rational_approximation(minted, portion);
+				let funding = increase_ratio * funding;
+				SpendingAssetCurrency::resolve_creating(&Treasury::account_id(), SpendingAssetCurrency::issue(funding));
+			}
+		}
+	}
+}
+
 /// Struct that handles the conversion of Balance -> `u64`. This is used for staking's election
 /// calculation.
 pub struct CurrencyToVoteHandler;
 
 impl CurrencyToVoteHandler {
-	fn factor() -> Balance { (Balances::total_issuance() / u64::max_value() as Balance).max(1) }
+	fn factor() -> Balance {
+		let issuance = generic_asset::StakingAssetCurrency::<Runtime>::total_issuance();
+		(issuance / u64::max_value() as Balance).max(1)
+	}
 }
 
 impl Convert<Balance, u64> for CurrencyToVoteHandler {
@@ -68,6 +119,39 @@ impl Convert<Weight, Balance> for WeightToFee {
 	}
 }
 
+/// The asset id under which spends of the native `Balances` currency are accounted for doughnut usage limits.
+/// Spends of generic assets are accounted under their own asset id.
+pub const NATIVE_ASSET_ID: u64 = 0;
+
+/// Determines the amounts a call spends on behalf of a doughnut issuer, so that doughnut
+/// spending limits may be enforced.
+///
+/// Only the calls listed here are accounted, any other call may not be dispatched with a doughnut which has
+/// spending limits. Approvals count as a spend of the approved amount, as the spender may then draw it with
+/// `transfer_from` outside of the doughnut's limits. Bonded funds count as spent, while transaction fees and
+/// contract gas do not.
+pub struct DelegatedCallSpendHandler;
+impl DelegatedCallSpend<Call> for DelegatedCallSpendHandler {
+	fn spend(call: &Call) -> Option<Vec<(u64, u128)>> {
+		let staking_asset_id = || u64::from(GenericAsset::staking_asset_id());
+		let spend = match call {
+			Call::Balances(BalancesCall::transfer(_, value)) => vec![(NATIVE_ASSET_ID, *value)],
+			Call::Contracts(ContractsCall::call(_, value, _, _)) => vec![(NATIVE_ASSET_ID, *value)],
+			Call::Contracts(ContractsCall::instantiate(endowment, _, _, _)) => vec![(NATIVE_ASSET_ID, *endowment)],
+			Call::GenericAsset(GenericAssetCall::transfer(asset_id, _, amount)) => vec![(u64::from(*asset_id), *amount)],
+			Call::GenericAsset(GenericAssetCall::approve(asset_id, _, amount)) => vec![(u64::from(*asset_id), *amount)],
+			Call::GenericAsset(GenericAssetCall::batch_transfer(transfers)) => transfers
+				.iter()
+				.map(|(asset_id, _, amount)| (u64::from(*asset_id), *amount))
+				.collect(),
+			Call::Staking(StakingCall::bond(_, value, _)) => vec![(staking_asset_id(), *value)],
+			Call::Staking(StakingCall::bond_extra(max_additional)) => vec![(staking_asset_id(), *max_additional)],
+			_ => return None,
+		};
+		Some(spend)
+	}
+}
+
 /// A struct that updates the weight multiplier based on the saturation level of the previous block.
 /// This should typically be called once per-block.
 ///
@@ -299,4 +383,49 @@ mod tests {
 				assert_eq!(fm, max_fm);
 			});
 	}
+
+	#[test]
+	fn delegated_call_spend_should_count_transfers_and_approvals() {
+		let who = AccountId::default();
+		assert_eq!(
+			DelegatedCallSpendHandler::spend(&Call::Balances(BalancesCall::transfer(who.clone().into(), 10))),
+			Some(vec![(NATIVE_ASSET_ID, 10)]),
+		);
+		assert_eq!(
+			DelegatedCallSpendHandler::spend(&Call::GenericAsset(GenericAssetCall::transfer(16_000, who.clone(), 100))),
+			Some(vec![(16_000, 100)]),
+		);
+		assert_eq!(
+			DelegatedCallSpendHandler::spend(&Call::GenericAsset(GenericAssetCall::batch_transfer(vec![
+				(16_000, who.clone(), 100),
+				(16_001, who.clone(), 50),
+			]))),
+			Some(vec![(16_000, 100), (16_001, 50)]),
+		);
+		assert_eq!(
+			DelegatedCallSpendHandler::spend(&Call::GenericAsset(GenericAssetCall::approve(16_000, who.clone(), 100))),
+			Some(vec![(16_000, 100)]),
+		);
+		assert_eq!(
+			DelegatedCallSpendHandler::spend(&Call::Contracts(ContractsCall::call(who.clone().into(), 5, 10_000, vec![]))),
+			Some(vec![(NATIVE_ASSET_ID, 5)]),
+		);
+	}
+
+	#[test]
+	fn delegated_call_spend_should_not_account_other_calls() {
+		let who = AccountId::default();
+		// The spender of an allowance draws it from its owner, not from the doughnut issuer
+		assert_eq!(
+			DelegatedCallSpendHandler::spend(
+				&Call::GenericAsset(GenericAssetCall::transfer_from(16_001, who.clone(), who.clone(), 50))
+			),
+			None,
+		);
+		assert_eq!(
+			DelegatedCallSpendHandler::spend(&Call::Balances(BalancesCall::force_transfer(who.clone().into(), who.into(), 1))),
+			None,
+		);
+		assert_eq!(DelegatedCallSpendHandler::spend(&Call::System(system::Call::remark(vec![]))), None);
+	}
 }
@@ -26,8 +26,9 @@ use support::{
 };
 use primitives::u32_trait::{_1, _2, _3, _4};
 use node_primitives::{
-	AccountId, AccountIndex, Balance, BlockNumber, Hash, Index,
-	Moment, Signature, ContractExecResult, Doughnut,
+	AccountId, AccountIndex, AssetId, AssetInfo, Balance, IssuanceAudit, BlockNumber, Hash, Index,
+	Moment, Signature, ContractCallTrace, ContractDryRunResult, ContractExecResult, Doughnut,
+	DoughnutInfo, DoughnutVerdict,
 };
 use babe_primitives::{AuthorityId as BabeId, AuthoritySignature as BabeSignature};
 use grandpa::fg_primitives;
@@ -39,10 +40,10 @@ use sr_primitives::{
 	Permill, Perbill, ApplyResult, impl_opaque_keys, generic, create_runtime_str, key_types
 };
 use sr_primitives::curve::PiecewiseLinear;
-use sr_primitives::transaction_validity::TransactionValidity;
+use sr_primitives::transaction_validity::{InvalidTransaction, TransactionValidity, TransactionValidityError};
 use sr_primitives::weights::Weight;
 use sr_primitives::traits::{
-	self, BlakeTwo256, Block as BlockT, NumberFor, StaticLookup, SaturatedConversion,
+	self, BlakeTwo256, Block as BlockT, ConvertInto, NumberFor, StaticLookup, SaturatedConversion,
 };
 use version::RuntimeVersion;
 use elections::VoteIndex;
@@ -59,13 +60,17 @@ use system::offchain::TransactionSubmitter;
 pub use sr_primitives::BuildStorage;
 pub use timestamp::Call as TimestampCall;
 pub use balances::Call as BalancesCall;
+pub use generic_asset::Call as GenericAssetCall;
 pub use contracts::Gas;
 pub use support::StorageValue;
 pub use staking::StakerStatus;
 
 /// Implementations of some helper traits passed into runtime modules as associated types.
 pub mod impls;
-use impls::{CurrencyToVoteHandler, WeightMultiplierUpdateHandler, Author, WeightToFee};
+use impls::{
+	CurrencyToVoteHandler, WeightMultiplierUpdateHandler, Author, WeightToFee, DelegatedCallSpendHandler,
+	DealWithAssetFees, RewardDilutionToTreasury, SlashToTreasury,
+};
 
 /// Constant values used within the runtime.
 pub mod constants;
@@ -84,7 +89,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// and set impl_version to equal spec_version. If only runtime
 	// implementation changes and behavior does not, then leave spec_version as
 	// is and increment impl_version.
-	spec_version: 170,
+	spec_version: 171,
 	impl_version: 171,
 	apis: RUNTIME_API_VERSIONS,
 };
@@ -128,7 +133,7 @@ impl system::Trait for Runtime {
 	type WeightMultiplierUpdate = WeightMultiplierUpdateHandler;
 	type Event = Event;
 	type BlockHashCount = BlockHashCount;
-	type Doughnut = prml_doughnut::PlugDoughnut<Doughnut, Runtime>;
+	type Doughnut = prml_doughnut::PlugDoughnutChain<Doughnut, Runtime>;
 	type DelegatedDispatchVerifier = prml_doughnut::PlugDoughnutDispatcher<Runtime>;
 	type MaximumBlockWeight = MaximumBlockWeight;
 	type MaximumBlockLength = MaximumBlockLength;
@@ -136,11 +141,36 @@ impl system::Trait for Runtime {
 	type Version = Version;
 }
 
+parameter_types! {
+	pub const MaxDoughnutChainLength: u32 = 4;
+}
+
 impl prml_doughnut::DoughnutRuntime for Runtime {
 	type AccountId = <Self as system::Trait>::AccountId;
 	type Call = Call;
 	type Doughnut = <Self as system::Trait>::Doughnut;
 	type TimestampProvider = timestamp::Module<Runtime>;
+	type RevocationRegistry = DoughnutRevocation;
+	type UsageTracker = DoughnutUsage;
+	type CallSpend = DelegatedCallSpendHandler;
+	type MaxChainLength = MaxDoughnutChainLength;
+}
+
+impl prml_doughnut_revocation::Trait for Runtime {
+	type Event = Event;
+}
+
+impl prml_doughnut_usage::Trait for Runtime {}
+
+parameter_types! {
+	pub const MaxAttestationDataLength: u32 = 1024;
+	// "kyc" as hex, the topic of the claims required to send restricted assets.
+	pub const KycAttestationTopic: u64 = 0x6b7963;
+}
+
+impl prml_attestation::Trait for Runtime {
+	type Event = Event;
+	type MaxDataLength = MaxAttestationDataLength;
 }
 
 parameter_types! {
@@ -170,7 +200,7 @@ parameter_types! {
 
 impl balances::Trait for Runtime {
 	type Balance = Balance;
-	type OnFreeBalanceZero = ((Staking, Contracts), Session);
+	type OnFreeBalanceZero = Contracts;
 	type OnNewAccount = Indices;
 	type Event = Event;
 	type TransactionPayment = DealWithFees;
@@ -184,6 +214,47 @@ impl balances::Trait for Runtime {
 	type WeightToFee = WeightToFee;
 }
 
+parameter_types! {
+	pub const MaxSymbolLength: u32 = 16;
+	pub const MaxNameLength: u32 = 64;
+}
+
+impl generic_asset::Trait for Runtime {
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type Event = Event;
+	type DustRemoval = ();
+	// Validators bond the staking asset, so their session keys are purged along with their ledger
+	// once their balance of it is reaped.
+	type OnFreeBalanceZero = generic_asset::ForAsset<generic_asset::StakingAssetIdProvider<Self>, (Staking, Session)>;
+	type OnNewAccount = ();
+	type RestrictedTransferOrigin = prml_attestation::EnsureAttested<Self, KycAttestationTopic>;
+	type MaxSymbolLength = MaxSymbolLength;
+	type MaxNameLength = MaxNameLength;
+}
+
+parameter_types! {
+	pub const PriceTimeout: BlockNumber = 1 * HOURS;
+}
+
+impl prml_price_oracle::Trait for Runtime {
+	type AssetId = AssetId;
+	type Balance = Balance;
+	type PriceTimeout = PriceTimeout;
+	type Event = Event;
+}
+
+// Fees are charged in the Balances currency unless a transaction chooses a generic asset to pay
+// them with. Fees paid in the spending asset are not priced by the oracle, and are split between
+// the treasury and block author in the same proportions as `DealWithFees`.
+impl prml_fee_exchange::Trait for Runtime {
+	type AssetPricing = PriceOracle;
+	type SpendingBaseFee = TransactionBaseFee;
+	type SpendingByteFee = TransactionByteFee;
+	type SpendingWeightToFee = WeightToFee;
+	type ExchangePayment = DealWithAssetFees;
+}
+
 parameter_types! {
 	pub const MinimumPeriod: Moment = SLOT_DURATION / 2;
 }
@@ -261,14 +332,14 @@ parameter_types! {
 }
 
 impl staking::Trait for Runtime {
-	type Currency = Balances;
-	type RewardCurrency = Balances;
+	type Currency = generic_asset::StakingAssetCurrency<Self>;
+	type RewardCurrency = generic_asset::SpendingAssetCurrency<Self>;
 	type CurrencyToReward = Balance;
 	type Time = Timestamp;
 	type CurrencyToVote = CurrencyToVoteHandler;
-	type OnRewardMinted = Treasury;
+	type OnRewardMinted = RewardDilutionToTreasury;
 	type Event = Event;
-	type Slash = Treasury; // send the slashed funds to the treasury.
+	type Slash = SlashToTreasury; // send the slashed funds to the treasury.
 	type Reward = (); // rewards are minted from the void
 	type SessionsPerEra = SessionsPerEra;
 	type BondingDuration = BondingDuration;
@@ -388,6 +459,10 @@ impl treasury::Trait for Runtime {
 	type Burn = Burn;
 }
 
+// Contracts buy gas with the spending asset, so the fees charged as gas (the transfer, creation,
+// contract and transaction fees) are amounts of the spending asset, while the rent, deposits and
+// rewards are amounts of the Balances currency. Both are denominated in `DOLLARS`, `CENTS` and
+// `MILLICENTS` of their own asset, which share the same number of decimal places.
 parameter_types! {
 	pub const ContractTransferFee: Balance = 1 * CENTS;
 	pub const ContractCreationFee: Balance = 1 * CENTS;
@@ -402,6 +477,9 @@ parameter_types! {
 
 impl contracts::Trait for Runtime {
 	type Currency = Balances;
+	type GasCurrency = generic_asset::SpendingAssetCurrency<Self>;
+	type MultiCurrency = GenericAsset;
+	type DoughnutAccount = ConvertInto;
 	type Time = Timestamp;
 	type Call = Call;
 	type Event = Event;
@@ -485,7 +563,7 @@ impl system::offchain::CreateTransaction<Runtime, UncheckedExtrinsic> for Runtim
 			system::CheckEra::<Runtime>::from(generic::Era::mortal(period, current_block)),
 			system::CheckNonce::<Runtime>::from(index),
 			system::CheckWeight::<Runtime>::new(),
-			balances::TakeFees::<Runtime>::from(tip),
+			prml_fee_exchange::TakeFees::<Runtime>::new(tip, None),
 			Default::default(),
 		);
 		let raw_payload = SignedPayload::new(call, extra).ok()?;
@@ -508,6 +586,7 @@ construct_runtime!(
 		Authorship: authorship::{Module, Call, Storage, Inherent},
 		Indices: indices,
 		Balances: balances::{default, Error},
+		GenericAsset: generic_asset::{Module, Call, Storage, Config<T>, Event<T>},
 		Staking: staking::{default, OfflineWorker},
 		Session: session::{Module, Call, Storage, Event, Config<T>},
 		Democracy: democracy::{Module, Call, Storage, Config, Event<T>},
@@ -523,6 +602,10 @@ construct_runtime!(
 		ImOnline: im_online::{Module, Call, Storage, Event<T>, ValidateUnsigned, Config<T>},
 		AuthorityDiscovery: authority_discovery::{Module, Call, Config<T>},
 		Offences: offences::{Module, Call, Storage, Event},
+		DoughnutRevocation: prml_doughnut_revocation::{Module, Call, Storage, Event<T>},
+		DoughnutUsage: prml_doughnut_usage::{Module, Storage},
+		Attestation: prml_attestation::{Module, Call, Storage, Event<T>},
+		PriceOracle: prml_price_oracle::{Module, Call, Storage, Config<T>, Event<T>},
 	}
 );
 
@@ -545,7 +628,7 @@ pub type SignedExtra = (
 	system::CheckEra<Runtime>,
 	system::CheckNonce<Runtime>,
 	system::CheckWeight<Runtime>,
-	balances::TakeFees<Runtime>,
+	prml_fee_exchange::TakeFees<Runtime>,
 	contracts::CheckBlockGasLimit<Runtime>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
@@ -686,16 +769,123 @@ impl_runtime_apis! {
 				gas_limit,
 				input_data,
 			);
-			match exec_result {
-				Ok(v) => ContractExecResult::Success {
-					status: v.status,
-					data: v.data,
-				},
-				Err(_) => ContractExecResult::Error,
+			to_contract_exec_result(exec_result)
+		}
+
+		fn dry_run_call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			input_data: Vec<u8>,
+		) -> ContractDryRunResult {
+			let dry_run = Contracts::dry_run_call(
+				origin,
+				dest.into(),
+				value,
+				gas_limit,
+				input_data,
+			);
+			ContractDryRunResult {
+				result: to_contract_exec_result(dry_run.result),
+				gas_used: dry_run.gas_used,
+				trace: dry_run.trace.map(to_contract_call_trace),
+				events: dry_run.events.into_iter()
+					.map(|(event, _topics)| Event::from(event).encode())
+					.collect(),
+				transfers: dry_run.transfers,
+				asset_transfers: dry_run.asset_transfers,
+				dispatches: dry_run.dispatches.into_iter()
+					.map(|(who, call)| (who, call.encode()))
+					.collect(),
 			}
 		}
 	}
 
+	impl node_primitives::DoughnutInspectionApi<Block> for Runtime {
+		fn inspect_doughnut(doughnut: Vec<u8>) -> Option<DoughnutInfo> {
+			use prml_doughnut::DelegationChain;
+			use sr_primitives::traits::DoughnutApi;
+
+			let doughnut = <Runtime as system::Trait>::Doughnut::decode(&mut &doughnut[..]).ok()?;
+			Some(DoughnutInfo {
+				issuer: doughnut.issuer(),
+				holder: doughnut.holder(),
+				not_before: doughnut.not_before(),
+				expiry: doughnut.expiry(),
+				links: doughnut.links().len() as u32,
+			})
+		}
+
+		fn validate_doughnut(holder: AccountId, doughnut: Vec<u8>, call: Vec<u8>) -> DoughnutVerdict {
+			use sr_primitives::traits::SignedExtension;
+			use support::additional_traits::VerifyDelegatedDispatch;
+
+			let doughnut = match <Runtime as system::Trait>::Doughnut::decode(&mut &doughnut[..]) {
+				Ok(doughnut) => doughnut,
+				Err(_) => return DoughnutVerdict::MalformedDoughnut,
+			};
+			let call = match Call::decode(&mut &call[..]) {
+				Ok(call) => call,
+				Err(_) => return DoughnutVerdict::MalformedCall,
+			};
+			// Map the doughnut's custom validity errors, see `prml_doughnut`
+			if let Err(err) = doughnut.validate(&holder, &call, Default::default(), 0) {
+				return match err {
+					TransactionValidityError::Invalid(InvalidTransaction::Custom(code)) => match code {
+						170 => DoughnutVerdict::InvalidSignature,
+						172 => DoughnutVerdict::Revoked,
+						173 => DoughnutVerdict::MaxUsesExceeded,
+						174 => DoughnutVerdict::SpendLimitExceeded,
+						175 => DoughnutVerdict::BrokenChain,
+						176 => DoughnutVerdict::UnenforceableLimits,
+						177 => DoughnutVerdict::UnaccountedSpend,
+						_ => DoughnutVerdict::InvalidHolderOrTime,
+					},
+					_ => DoughnutVerdict::InvalidHolderOrTime,
+				}
+			}
+			match call.verify_delegated_dispatch(&doughnut) {
+				Ok(()) => DoughnutVerdict::Authorized,
+				Err(reason) => DoughnutVerdict::Unauthorized(reason.as_bytes().to_vec()),
+			}
+		}
+	}
+
+	impl node_primitives::GenericAssetApi<Block> for Runtime {
+		fn asset_info(asset_id: AssetId) -> Option<AssetInfo> {
+			use support::StorageLinkedMap;
+
+			if generic_asset::AssetMeta::<Runtime>::exists(asset_id) {
+				Some(to_asset_info(GenericAsset::asset_meta(asset_id)))
+			} else {
+				None
+			}
+		}
+
+		fn registered_assets() -> Vec<(AssetId, AssetInfo)> {
+			GenericAsset::registered_assets()
+				.into_iter()
+				.map(|(asset_id, info)| (asset_id, to_asset_info(info)))
+				.collect()
+		}
+
+		fn allowance(asset_id: AssetId, owner: AccountId, spender: AccountId) -> Balance {
+			GenericAsset::allowance(&asset_id, &owner, &spender)
+		}
+
+		fn audit_issuance(asset_id: AssetId) -> IssuanceAudit {
+			to_issuance_audit(GenericAsset::audit_issuance(&asset_id))
+		}
+
+		fn issuance_discrepancies() -> Vec<(AssetId, IssuanceAudit)> {
+			GenericAsset::issuance_discrepancies()
+				.into_iter()
+				.map(|(asset_id, audit)| (asset_id, to_issuance_audit(audit)))
+				.collect()
+		}
+	}
+
 	impl substrate_session::SessionKeys<Block> for Runtime {
 		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
 			let seed = seed.as_ref().map(|s| rstd::str::from_utf8(&s).expect("Seed is an utf8 string"));
@@ -704,6 +894,48 @@ impl_runtime_apis! {
 	}
 }
 
+/// Converts the result of a contract call into the runtime API representation.
+fn to_contract_exec_result(exec_result: contracts::ExecResult) -> ContractExecResult {
+	match exec_result {
+		Ok(v) => ContractExecResult::Success {
+			status: v.status,
+			data: v.data,
+		},
+		Err(_) => ContractExecResult::Error,
+	}
+}
+
+/// Converts the trace of a contract call into the runtime API representation.
+fn to_contract_call_trace(trace: contracts::CallTrace<AccountId>) -> ContractCallTrace {
+	ContractCallTrace {
+		dest: trace.dest,
+		gas_used: trace.gas_used,
+		success: trace.success,
+		storage_reads: trace.storage_reads,
+		storage_writes: trace.storage_writes,
+		nested: trace.nested.into_iter().map(to_contract_call_trace).collect(),
+	}
+}
+
+/// Converts the generic asset module's metadata into the runtime API representation.
+fn to_asset_info(info: generic_asset::AssetInfo<Balance>) -> AssetInfo {
+	AssetInfo {
+		symbol: info.symbol,
+		decimal_places: info.decimal_places,
+		name: info.name,
+		existential_deposit: info.existential_deposit,
+	}
+}
+
+/// Converts the generic asset module's issuance audit into the runtime API representation.
+fn to_issuance_audit(audit: generic_asset::IssuanceAudit<Balance>) -> IssuanceAudit {
+	IssuanceAudit {
+		total_issuance: audit.total_issuance,
+		free: audit.free,
+		reserved: audit.reserved,
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -20,9 +20,9 @@ use crate::keyring::*;
 use keyring::{Ed25519Keyring, Sr25519Keyring};
 use node_runtime::{
 	GenesisConfig, BalancesConfig, SessionConfig, StakingConfig, SystemConfig,
-	GrandpaConfig, IndicesConfig, ContractsConfig, WASM_BINARY,
+	GrandpaConfig, IndicesConfig, ContractsConfig, GenericAssetConfig, WASM_BINARY,
 };
-use node_runtime::constants::currency::*;
+use node_runtime::constants::{currency::*, asset::*};
 use primitives::ChangesTrieConfiguration;
 use sr_primitives::Perbill;
 
@@ -51,6 +51,16 @@ pub fn config(support_changes_trie: bool, code: Option<&[u8]>) -> GenesisConfig
 			],
 			vesting: vec![],
 		}),
+		generic_asset: Some(GenericAssetConfig {
+			assets: vec![STAKING_ASSET_ID, SPENDING_ASSET_ID],
+			initial_balance: 111 * DOLLARS,
+			endowed_accounts: vec![alice(), bob(), charlie(), dave(), eve(), ferdie()],
+			next_asset_id: NEXT_ASSET_ID,
+			staking_asset_id: STAKING_ASSET_ID,
+			spending_asset_id: SPENDING_ASSET_ID,
+			asset_meta: vec![],
+			vesting: vec![],
+		}),
 		session: Some(SessionConfig {
 			keys: vec![
 				(alice(), to_session_keys(
@@ -96,5 +106,6 @@ pub fn config(support_changes_trie: bool, code: Option<&[u8]>) -> GenesisConfig
 		membership_Instance1: Some(Default::default()),
 		elections: Some(Default::default()),
 		sudo: Some(Default::default()),
+		price_oracle: Some(Default::default()),
 	}
 }
@@ -24,6 +24,8 @@
 //! Holder: the entity that the claim is about
 //! Topic: the topic which the claim is about ie isOver18
 //! Value: any value pertaining to the claim
+//! Data: bytes pertaining to the claim which do not fit in a value, up to `MaxDataLength` long
+//! Expiry: the block or moment from which a claim is treated as absent
 //!
 //! ## Usage
 //!
@@ -32,20 +34,46 @@
 //! The user of the module must convert whatever value that they would like to store into a value that can be stored as a U256.
 //!
 //! It is recommended that Topic be a string value converted to hex and stored on the blockchain as a U256.
+//!
+//! Claims may be given an expiry with `set_claim_with_data`. Expired claims are kept in storage until removed, but the
+//! `value`, `data`, `topics` and `issuers` getters treat them as absent.
+//!
+//! Governance may set a list of trusted issuers per topic with `set_trusted_issuers`. `EnsureAttested` is an
+//! `EnsureOrigin` which only accepts signed (or delegated) origins holding an unexpired claim on its topic from one of
+//! those issuers, so other modules can gate their calls on an attestation, e.g. a KYC claim.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use primitives::uint::U256;
-use support::rstd::prelude::*;
-use support::{decl_event, decl_module, decl_storage, dispatch::Result};
-use system::ensure_signed;
+use sr_primitives::traits::EnsureOrigin;
+use support::rstd::{marker::PhantomData, prelude::*, result};
+use codec::{Decode, Encode};
+use support::{
+	decl_event, decl_module, decl_storage, dispatch::Result, ensure,
+	traits::Get,
+};
+use system::{ensure_root, ensure_signed, RawOrigin};
 
-pub trait Trait: system::Trait {
+pub trait Trait: system::Trait + timestamp::Trait {
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+	/// The maximum length of the data stored alongside a claim.
+	type MaxDataLength: Get<u32>;
 }
 
-type AttestationTopic = U256;
-type AttestationValue = U256;
+pub type AttestationTopic = U256;
+pub type AttestationValue = U256;
+
+pub type ClaimExpiryOf<T> = ClaimExpiry<<T as system::Trait>::BlockNumber, <T as timestamp::Trait>::Moment>;
+
+/// When a claim expires, after which it is treated as absent.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum ClaimExpiry<BlockNumber, Moment> {
+	/// The claim expires at the given block.
+	Block(BlockNumber),
+	/// The claim expires once the timestamp reaches the given moment.
+	Timestamp(Moment),
+}
 
 decl_module! {
 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
@@ -55,7 +83,22 @@ decl_module! {
 		pub fn set_claim(origin, holder: T::AccountId, topic: AttestationTopic, value: AttestationValue) -> Result {
 			let issuer = ensure_signed(origin)?;
 
-			Self::create_claim(holder, issuer, topic, value)?;
+			Self::create_claim(holder, issuer, topic, value, Vec::new(), None)?;
+			Ok(())
+		}
+
+		/// Create a new claim with some data stored alongside its value, which may expire
+		pub fn set_claim_with_data(
+			origin,
+			holder: T::AccountId,
+			topic: AttestationTopic,
+			value: AttestationValue,
+			data: Vec<u8>,
+			expiry: Option<ClaimExpiryOf<T>>
+		) -> Result {
+			let issuer = ensure_signed(origin)?;
+
+			Self::create_claim(holder, issuer, topic, value, data, expiry)?;
 			Ok(())
 		}
 
@@ -63,7 +106,7 @@ decl_module! {
 		pub fn set_self_claim(origin, topic: AttestationTopic, value: AttestationValue) -> Result {
 			let holder_and_issuer = ensure_signed(origin)?;
 
-			Self::create_claim(holder_and_issuer.clone(), holder_and_issuer, topic, value)?;
+			Self::create_claim(holder_and_issuer.clone(), holder_and_issuer, topic, value, Vec::new(), None)?;
 			Ok(())
 		}
 
@@ -73,11 +116,27 @@ decl_module! {
 			<Issuers<T>>::mutate(&holder,|issuers| issuers.retain(|vec_issuer| *vec_issuer != issuer));
 			<Topics<T>>::mutate((holder.clone(), issuer.clone()),|topics| topics.retain(|vec_topic| *vec_topic != topic));
 			<Values<T>>::remove((holder.clone(), issuer.clone(), topic));
+			<Data<T>>::remove((holder.clone(), issuer.clone(), topic));
+			<Expiries<T>>::remove((holder.clone(), issuer.clone(), topic));
 
 			Self::deposit_event(RawEvent::ClaimRemoved(holder, issuer, topic));
 
 			Ok(())
 		}
+
+		/// Set the issuers whose claims on `topic` are trusted by `EnsureAttested`, only callable by root
+		pub fn set_trusted_issuers(origin, topic: AttestationTopic, issuers: Vec<T::AccountId>) -> Result {
+			ensure_root(origin)?;
+
+			if issuers.is_empty() {
+				<TrustedIssuers<T>>::remove(topic);
+			} else {
+				<TrustedIssuers<T>>::insert(topic, issuers.clone());
+			}
+			Self::deposit_event(RawEvent::TrustedIssuersSet(topic, issuers));
+
+			Ok(())
+		}
 	}
 }
 
@@ -85,6 +144,7 @@ decl_event!(
 	pub enum Event<T> where <T as system::Trait>::AccountId {
 		ClaimSet(AccountId, AccountId, AttestationTopic, AttestationValue),
 		ClaimRemoved(AccountId, AccountId, AttestationTopic),
+		TrustedIssuersSet(AttestationTopic, Vec<AccountId>),
 	}
 );
 
@@ -107,16 +167,84 @@ decl_storage! {
 		Topics: map (T::AccountId, T::AccountId) => Vec<AttestationTopic>;
 		/// A map of (HolderId, IssuerId, AttestationTopic) => AttestationValue
 		Values: map (T::AccountId, T::AccountId, AttestationTopic) => AttestationValue;
+		/// A map of (HolderId, IssuerId, AttestationTopic) => data stored alongside the value
+		Data: map (T::AccountId, T::AccountId, AttestationTopic) => Vec<u8>;
+		/// A map of (HolderId, IssuerId, AttestationTopic) => when the claim expires, if ever
+		Expiries get(expiry): map (T::AccountId, T::AccountId, AttestationTopic) => Option<ClaimExpiryOf<T>>;
+		/// A map of AttestationTopic => the issuers trusted by `EnsureAttested` for that topic
+		TrustedIssuers get(trusted_issuers): map AttestationTopic => Vec<T::AccountId>;
 	}
 }
 
 impl<T: Trait> Module<T> {
+	/// Get the value of a claim, or `None` if there is no such claim or it has expired
+	pub fn value(holder: &T::AccountId, issuer: &T::AccountId, topic: AttestationTopic) -> Option<AttestationValue> {
+		let key = (holder.clone(), issuer.clone(), topic);
+		if <Values<T>>::exists(&key) && !Self::has_expired(&key) {
+			Some(<Values<T>>::get(&key))
+		} else {
+			None
+		}
+	}
+
+	/// Get the data stored alongside a claim, or `None` if there is no such claim or it has expired
+	pub fn data(holder: &T::AccountId, issuer: &T::AccountId, topic: AttestationTopic) -> Option<Vec<u8>> {
+		let key = (holder.clone(), issuer.clone(), topic);
+		if <Values<T>>::exists(&key) && !Self::has_expired(&key) {
+			Some(<Data<T>>::get(&key))
+		} else {
+			None
+		}
+	}
+
+	/// Get the topics of the unexpired claims an issuer has made about a holder
+	pub fn topics(holder: &T::AccountId, issuer: &T::AccountId) -> Vec<AttestationTopic> {
+		<Topics<T>>::get((holder.clone(), issuer.clone()))
+			.into_iter()
+			.filter(|topic| !Self::has_expired(&(holder.clone(), issuer.clone(), *topic)))
+			.collect()
+	}
+
+	/// Get the issuers which have made unexpired claims about a holder
+	pub fn issuers(holder: &T::AccountId) -> Vec<T::AccountId> {
+		<Issuers<T>>::get(holder)
+			.into_iter()
+			.filter(|issuer| !Self::topics(holder, issuer).is_empty())
+			.collect()
+	}
+
+	/// Whether a trusted issuer of `topic` has made an unexpired claim about `holder`
+	pub fn has_trusted_claim(holder: &T::AccountId, topic: AttestationTopic) -> bool {
+		Self::trusted_issuers(topic)
+			.iter()
+			.any(|issuer| Self::value(holder, issuer, topic).is_some())
+	}
+
+	/// Whether the given expiry has passed
+	pub fn is_expired(expiry: &ClaimExpiryOf<T>) -> bool {
+		match expiry {
+			ClaimExpiry::Block(block) => <system::Module<T>>::block_number() >= *block,
+			ClaimExpiry::Timestamp(moment) => <timestamp::Module<T>>::get() >= *moment,
+		}
+	}
+
+	fn has_expired(key: &(T::AccountId, T::AccountId, AttestationTopic)) -> bool {
+		Self::expiry(key).map_or(false, |expiry| Self::is_expired(&expiry))
+	}
+
 	fn create_claim(
 		holder: T::AccountId,
 		issuer: T::AccountId,
 		topic: AttestationTopic,
 		value: AttestationValue,
+		data: Vec<u8>,
+		expiry: Option<ClaimExpiryOf<T>>,
 	) -> Result {
+		ensure!(data.len() <= T::MaxDataLength::get() as usize, "claim data is too long");
+		if let Some(ref expiry) = expiry {
+			ensure!(!Self::is_expired(expiry), "claim expiry has already passed");
+		}
+
 		<Issuers<T>>::mutate(&holder, |issuers| {
 			if !issuers.contains(&issuer) {
 				issuers.push(issuer.clone())
@@ -130,7 +258,199 @@ impl<T: Trait> Module<T> {
 		});
 
 		<Values<T>>::insert((holder.clone(), issuer.clone(), topic), value);
+		if data.is_empty() {
+			<Data<T>>::remove((holder.clone(), issuer.clone(), topic));
+		} else {
+			<Data<T>>::insert((holder.clone(), issuer.clone(), topic), data);
+		}
+		match expiry {
+			Some(expiry) => <Expiries<T>>::insert((holder.clone(), issuer.clone(), topic), expiry),
+			None => <Expiries<T>>::remove((holder.clone(), issuer.clone(), topic)),
+		}
 		Self::deposit_event(RawEvent::ClaimSet(holder, issuer, topic, value));
 		Ok(())
 	}
 }
+
+/// Ensure the origin is signed, or delegated, by an account holding an unexpired claim on `Topic` from one of the
+/// topic's trusted issuers. Returns the account on success.
+pub struct EnsureAttested<T, Topic>(PhantomData<(T, Topic)>);
+impl<T: Trait, Topic: Get<AttestationTopic>> EnsureOrigin<T::Origin> for EnsureAttested<T, Topic> {
+	type Success = T::AccountId;
+	fn try_origin(o: T::Origin) -> result::Result<Self::Success, T::Origin> {
+		o.into().and_then(|o| match o {
+			RawOrigin::Signed(ref who) | RawOrigin::Delegated(ref who, _)
+				if <Module<T>>::has_trusted_claim(who, Topic::get()) => Ok(who.clone()),
+			r => Err(T::Origin::from(r)),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use primitives::{Blake2Hasher, H256};
+	use sr_primitives::{
+		Perbill,
+		testing::Header,
+		traits::{BlakeTwo256, IdentityLookup},
+	};
+	use support::{assert_noop, assert_ok, impl_outer_origin, parameter_types};
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: u32 = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+		pub const MinimumPeriod: u64 = 1;
+		pub const MaxDataLength: u32 = 4;
+		pub const KycTopic: u64 = 10;
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Call = ();
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<u64>;
+		type Header = Header;
+		type Event = ();
+		type WeightMultiplierUpdate = ();
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type BlockHashCount = BlockHashCount;
+		type Version = ();
+		type Doughnut = ();
+		type DelegatedDispatchVerifier = ();
+	}
+	impl timestamp::Trait for Test {
+		type Moment = u64;
+		type OnTimestampSet = ();
+		type MinimumPeriod = MinimumPeriod;
+	}
+	impl Trait for Test {
+		type Event = ();
+		type MaxDataLength = MaxDataLength;
+	}
+	type Attestation = Module<Test>;
+	type EnsureKyc = EnsureAttested<Test, KycTopic>;
+	type System = system::Module<Test>;
+	type Timestamp = timestamp::Module<Test>;
+
+	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	}
+
+	#[test]
+	fn claims_without_expiry_should_never_expire() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			assert_ok!(Attestation::set_claim(Origin::signed(1), 2, 10.into(), 100.into()));
+			System::set_block_number(1_000_000);
+
+			assert_eq!(Attestation::value(&2, &1, 10.into()), Some(100.into()));
+			assert_eq!(Attestation::data(&2, &1, 10.into()), Some(vec![]));
+			assert_eq!(Attestation::topics(&2, &1), vec![10.into()]);
+			assert_eq!(Attestation::issuers(&2), vec![1]);
+			assert_eq!(Attestation::value(&2, &1, 11.into()), None);
+		});
+	}
+
+	#[test]
+	fn expired_claims_should_be_absent() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			assert_ok!(Attestation::set_claim_with_data(
+				Origin::signed(1), 2, 10.into(), 100.into(), vec![1, 2, 3], Some(ClaimExpiry::Block(5))
+			));
+			assert_ok!(Attestation::set_claim_with_data(
+				Origin::signed(1), 2, 11.into(), 101.into(), vec![], Some(ClaimExpiry::Timestamp(500))
+			));
+			assert_eq!(Attestation::data(&2, &1, 10.into()), Some(vec![1, 2, 3]));
+			assert_eq!(Attestation::topics(&2, &1), vec![10.into(), 11.into()]);
+
+			System::set_block_number(5);
+			assert_eq!(Attestation::value(&2, &1, 10.into()), None);
+			assert_eq!(Attestation::data(&2, &1, 10.into()), None);
+			assert_eq!(Attestation::topics(&2, &1), vec![11.into()]);
+
+			Timestamp::set_timestamp(500);
+			assert_eq!(Attestation::value(&2, &1, 11.into()), None);
+			assert!(Attestation::issuers(&2).is_empty());
+
+			// renewing a claim without expiry clears its old expiry
+			assert_ok!(Attestation::set_claim(Origin::signed(1), 2, 10.into(), 102.into()));
+			assert_eq!(Attestation::value(&2, &1, 10.into()), Some(102.into()));
+			assert_eq!(Attestation::data(&2, &1, 10.into()), Some(vec![]));
+		});
+	}
+
+	#[test]
+	fn set_claim_with_data_should_check_length_and_expiry() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			System::set_block_number(5);
+			assert_noop!(
+				Attestation::set_claim_with_data(Origin::signed(1), 2, 10.into(), 100.into(), vec![0; 5], None),
+				"claim data is too long"
+			);
+			assert_noop!(
+				Attestation::set_claim_with_data(
+					Origin::signed(1), 2, 10.into(), 100.into(), vec![], Some(ClaimExpiry::Block(5))
+				),
+				"claim expiry has already passed"
+			);
+
+			assert_ok!(Attestation::set_claim_with_data(
+				Origin::signed(1), 2, 10.into(), 100.into(), vec![0; 4], Some(ClaimExpiry::Block(6))
+			));
+			assert_ok!(Attestation::remove_claim(Origin::signed(1), 2, 10.into()));
+			assert_eq!(Attestation::data(&2, &1, 10.into()), None);
+			assert_eq!(Attestation::expiry((2u64, 1u64, U256::from(10))), None);
+		});
+	}
+
+	#[test]
+	fn set_trusted_issuers_should_require_root() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			assert_noop!(
+				Attestation::set_trusted_issuers(Origin::signed(1), 10.into(), vec![1]),
+				"RequireRootOrigin"
+			);
+			assert_ok!(Attestation::set_trusted_issuers(Origin::ROOT, 10.into(), vec![1, 3]));
+			assert_eq!(Attestation::trusted_issuers(U256::from(10)), vec![1, 3]);
+
+			assert_ok!(Attestation::set_trusted_issuers(Origin::ROOT, 10.into(), vec![]));
+			assert!(Attestation::trusted_issuers(U256::from(10)).is_empty());
+		});
+	}
+
+	#[test]
+	fn ensure_attested_should_require_a_trusted_unexpired_claim() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			assert_ok!(Attestation::set_trusted_issuers(Origin::ROOT, 10.into(), vec![1]));
+			assert!(EnsureKyc::try_origin(Origin::signed(2)).is_err());
+
+			// claims from untrusted issuers, or on other topics, are not enough
+			assert_ok!(Attestation::set_claim(Origin::signed(3), 2, 10.into(), 1.into()));
+			assert_ok!(Attestation::set_claim(Origin::signed(1), 2, 11.into(), 1.into()));
+			assert!(EnsureKyc::try_origin(Origin::signed(2)).is_err());
+
+			assert_ok!(Attestation::set_claim_with_data(
+				Origin::signed(1), 2, 10.into(), 1.into(), vec![], Some(ClaimExpiry::Block(5))
+			));
+			assert_eq!(EnsureKyc::try_origin(Origin::signed(2)).ok(), Some(2));
+			assert!(EnsureKyc::try_origin(Origin::ROOT).is_err());
+			assert!(EnsureKyc::try_origin(Origin::NONE).is_err());
+
+			System::set_block_number(5);
+			assert!(EnsureKyc::try_origin(Origin::signed(2)).is_err());
+		});
+	}
+}
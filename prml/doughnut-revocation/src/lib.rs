@@ -0,0 +1,205 @@
+// Copyright 2019 Plug New Zealand Limited
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Doughnut Revocation Module
+//!
+//! The Doughnut Revocation module provides an on-chain registry of doughnuts revoked by their issuers.
+//!
+//! ## Terminology
+//!
+//! Issuer: the entity which signed the doughnut, delegating its authority
+//! Holder: the entity the doughnut delegates authority to
+//! Doughnut hash: the runtime `Hashing` of the SCALE encoded doughnut
+//!
+//! ## Usage
+//!
+//! An issuer may revoke a single doughnut by its hash with `revoke_doughnut`, or revoke every doughnut it has
+//! issued to a holder with `revoke_holder`. A holder revocation may be lifted with `unrevoke_holder`.
+//! Revocations are scoped to the issuer, so an account can never revoke doughnuts issued by another.
+//!
+//! The module implements `DoughnutRevocationRegistry` so that a runtime's doughnut validation can reject
+//! revoked proofs.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Encode;
+use sr_primitives::traits::{DoughnutApi, Hash as HashT};
+use support::{
+	decl_event, decl_module, decl_storage, dispatch::Result, StorageDoubleMap,
+	additional_traits::DoughnutRevocationRegistry,
+};
+use system::ensure_signed;
+
+pub trait Trait: system::Trait {
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// Revoke a doughnut issued by the caller, identified by its hash
+		pub fn revoke_doughnut(origin, doughnut_hash: T::Hash) -> Result {
+			let issuer = ensure_signed(origin)?;
+
+			<RevokedDoughnuts<T>>::insert(&issuer, &doughnut_hash, true);
+			Self::deposit_event(RawEvent::DoughnutRevoked(issuer, doughnut_hash));
+			Ok(())
+		}
+
+		/// Revoke all doughnuts issued by the caller to `holder`
+		pub fn revoke_holder(origin, holder: T::AccountId) -> Result {
+			let issuer = ensure_signed(origin)?;
+
+			<RevokedHolders<T>>::insert(&issuer, &holder, true);
+			Self::deposit_event(RawEvent::HolderRevoked(issuer, holder));
+			Ok(())
+		}
+
+		/// Lift a revocation of all doughnuts issued by the caller to `holder`
+		pub fn unrevoke_holder(origin, holder: T::AccountId) -> Result {
+			let issuer = ensure_signed(origin)?;
+
+			<RevokedHolders<T>>::remove(&issuer, &holder);
+			Self::deposit_event(RawEvent::HolderUnrevoked(issuer, holder));
+			Ok(())
+		}
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where <T as system::Trait>::AccountId, <T as system::Trait>::Hash {
+		/// A doughnut was revoked (issuer, doughnut hash)
+		DoughnutRevoked(AccountId, Hash),
+		/// All doughnuts to a holder were revoked (issuer, holder)
+		HolderRevoked(AccountId, AccountId),
+		/// A holder revocation was lifted (issuer, holder)
+		HolderUnrevoked(AccountId, AccountId),
+	}
+);
+
+decl_storage! {
+	trait Store for Module<T: Trait> as DoughnutRevocation {
+		/// Doughnuts revoked by hash. (IssuerId, DoughnutHash) => revoked
+		pub RevokedDoughnuts get(is_doughnut_revoked): double_map T::AccountId, blake2_256(T::Hash) => bool;
+		/// Holders whose doughnuts are revoked. (IssuerId, HolderId) => revoked
+		pub RevokedHolders get(is_holder_revoked): double_map T::AccountId, blake2_256(T::AccountId) => bool;
+	}
+}
+
+impl<T: Trait> DoughnutRevocationRegistry<T::AccountId> for Module<T> {
+	fn is_revoked<Doughnut: DoughnutApi<PublicKey=T::AccountId> + Encode>(doughnut: &Doughnut) -> bool {
+		let issuer = doughnut.issuer();
+		Self::is_holder_revoked(&issuer, &doughnut.holder())
+			|| Self::is_doughnut_revoked(&issuer, &T::Hashing::hash_of(doughnut))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use primitives::{Blake2Hasher, H256};
+	use sr_primitives::{
+		Perbill,
+		testing::{Header, doughnut::{TestAccountId, TestDoughnut}},
+		traits::{BlakeTwo256, IdentityLookup},
+	};
+	use support::{assert_ok, impl_outer_origin, parameter_types};
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: u32 = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Call = ();
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = TestAccountId;
+		type Lookup = IdentityLookup<TestAccountId>;
+		type Header = Header;
+		type Event = ();
+		type WeightMultiplierUpdate = ();
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type BlockHashCount = BlockHashCount;
+		type Version = ();
+		type Doughnut = TestDoughnut;
+		type DelegatedDispatchVerifier = ();
+	}
+	impl Trait for Test {
+		type Event = ();
+	}
+	type DoughnutRevocation = Module<Test>;
+
+	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	}
+
+	fn doughnut(issuer: u64, holder: u64) -> TestDoughnut {
+		TestDoughnut { issuer: issuer.into(), holder: holder.into() }
+	}
+
+	#[test]
+	fn doughnuts_are_not_revoked_by_default() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			assert!(!<DoughnutRevocation as DoughnutRevocationRegistry<_>>::is_revoked(&doughnut(1, 2)));
+		});
+	}
+
+	#[test]
+	fn revoke_doughnut_works() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			let revoked = doughnut(1, 2);
+			let other = doughnut(1, 3);
+			assert_ok!(DoughnutRevocation::revoke_doughnut(Origin::signed(1.into()), BlakeTwo256::hash_of(&revoked)));
+			assert!(<DoughnutRevocation as DoughnutRevocationRegistry<_>>::is_revoked(&revoked));
+			assert!(!<DoughnutRevocation as DoughnutRevocationRegistry<_>>::is_revoked(&other));
+		});
+	}
+
+	#[test]
+	fn revoke_doughnut_is_scoped_to_issuer() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			let doughnut = doughnut(1, 2);
+			// Only the issuer may revoke its doughnuts
+			assert_ok!(DoughnutRevocation::revoke_doughnut(Origin::signed(3.into()), BlakeTwo256::hash_of(&doughnut)));
+			assert!(!<DoughnutRevocation as DoughnutRevocationRegistry<_>>::is_revoked(&doughnut));
+		});
+	}
+
+	#[test]
+	fn revoke_holder_works() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			assert_ok!(DoughnutRevocation::revoke_holder(Origin::signed(1.into()), 2.into()));
+			assert!(<DoughnutRevocation as DoughnutRevocationRegistry<_>>::is_revoked(&doughnut(1, 2)));
+			assert!(!<DoughnutRevocation as DoughnutRevocationRegistry<_>>::is_revoked(&doughnut(1, 3)));
+			assert!(!<DoughnutRevocation as DoughnutRevocationRegistry<_>>::is_revoked(&doughnut(3, 2)));
+
+			assert_ok!(DoughnutRevocation::unrevoke_holder(Origin::signed(1.into()), 2.into()));
+			assert!(!<DoughnutRevocation as DoughnutRevocationRegistry<_>>::is_revoked(&doughnut(1, 2)));
+		});
+	}
+}
@@ -0,0 +1,138 @@
+// Copyright 2019 Plug New Zealand Limited
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Doughnut Usage Module
+//!
+//! The Doughnut Usage module keeps count of how many extrinsics a doughnut has been used for and how much of
+//! each asset has been spent with it, so that issuer imposed usage limits can be enforced across extrinsics.
+//!
+//! Doughnuts are identified by the runtime `Hashing` of their SCALE encoding.
+//!
+//! The module has no dispatchable calls. It implements `DoughnutUsageTracker` for use by a runtime's
+//! doughnut validation.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Encode;
+use sr_primitives::traits::Hash as HashT;
+use support::{
+	decl_module, decl_storage, StorageDoubleMap, StorageMap,
+	additional_traits::DoughnutUsageTracker,
+};
+
+pub trait Trait: system::Trait {}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {}
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as DoughnutUsage {
+		/// The number of extrinsics a doughnut has been used for. DoughnutHash => uses
+		pub Uses get(doughnut_uses): map T::Hash => u32;
+		/// The total amount of an asset spent using a doughnut. (DoughnutHash, AssetId) => amount
+		pub Spent get(doughnut_spent): double_map T::Hash, blake2_256(u64) => u128;
+	}
+}
+
+impl<T: Trait> DoughnutUsageTracker for Module<T> {
+	fn enforces_limits() -> bool {
+		true
+	}
+
+	fn uses<Doughnut: Encode>(doughnut: &Doughnut) -> u32 {
+		Self::doughnut_uses(T::Hashing::hash_of(doughnut))
+	}
+
+	fn spent<Doughnut: Encode>(doughnut: &Doughnut, asset_id: u64) -> u128 {
+		Self::doughnut_spent(T::Hashing::hash_of(doughnut), asset_id)
+	}
+
+	fn record_use<Doughnut: Encode>(doughnut: &Doughnut, spend: &[(u64, u128)]) {
+		let doughnut_hash = T::Hashing::hash_of(doughnut);
+		<Uses<T>>::mutate(&doughnut_hash, |uses| *uses = uses.saturating_add(1));
+		for (asset_id, amount) in spend {
+			<Spent<T>>::mutate(&doughnut_hash, asset_id, |spent| *spent = spent.saturating_add(*amount));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use primitives::{Blake2Hasher, H256};
+	use sr_primitives::{
+		Perbill,
+		testing::{Header, doughnut::{TestAccountId, TestDoughnut}},
+		traits::{BlakeTwo256, IdentityLookup},
+	};
+	use support::{impl_outer_origin, parameter_types};
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: u32 = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Call = ();
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = TestAccountId;
+		type Lookup = IdentityLookup<TestAccountId>;
+		type Header = Header;
+		type Event = ();
+		type WeightMultiplierUpdate = ();
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type BlockHashCount = BlockHashCount;
+		type Version = ();
+		type Doughnut = TestDoughnut;
+		type DelegatedDispatchVerifier = ();
+	}
+	impl Trait for Test {}
+	type DoughnutUsage = Module<Test>;
+
+	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	}
+
+	#[test]
+	fn record_use_tracks_uses_and_spend() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			let doughnut = TestDoughnut { issuer: 1.into(), holder: 2.into() };
+			let other = TestDoughnut { issuer: 1.into(), holder: 3.into() };
+			assert_eq!(<DoughnutUsage as DoughnutUsageTracker>::uses(&doughnut), 0);
+
+			<DoughnutUsage as DoughnutUsageTracker>::record_use(&doughnut, &[(0, 10), (1, 5)]);
+			<DoughnutUsage as DoughnutUsageTracker>::record_use(&doughnut, &[(0, 7)]);
+
+			assert_eq!(<DoughnutUsage as DoughnutUsageTracker>::uses(&doughnut), 2);
+			assert_eq!(<DoughnutUsage as DoughnutUsageTracker>::spent(&doughnut, 0), 17);
+			assert_eq!(<DoughnutUsage as DoughnutUsageTracker>::spent(&doughnut, 1), 5);
+			assert_eq!(<DoughnutUsage as DoughnutUsageTracker>::spent(&doughnut, 2), 0);
+			assert_eq!(<DoughnutUsage as DoughnutUsageTracker>::uses(&other), 0);
+		});
+	}
+}
@@ -0,0 +1,467 @@
+// Copyright 2019 Plug New Zealand Limited
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Chained (re-delegated) doughnuts.
+//!
+//! A chain is an ordered list of doughnuts from the root issuer to the final holder, where the holder of each
+//! link is the issuer of the next. The chain acts with the authority of the root issuer and may only be used by
+//! the final holder.
+//!
+//! Permissions can only narrow along a chain: a call is dispatched only if every link permits it, and every
+//! link must be within its time window, unrevoked and within its usage limits.
+
+use crate::{DelegationChain, DoughnutRuntime, PlugDoughnut};
+use codec::{Compact, Decode, Encode, EncodeLike, Input, Output};
+use primitives::crypto::UncheckedFrom;
+use rstd::{self, prelude::*};
+use sr_primitives::{ApplyError, ApplyOutcome};
+use sr_primitives::traits::{
+	DoughnutApi, DoughnutFees, DoughnutVerify, DoughnutWeight, Member, SignedExtension, VerifyError,
+};
+use sr_primitives::transaction_validity::{InvalidTransaction, TransactionValidityError, ValidTransaction};
+use sr_primitives::weights::{DispatchInfo, Weight};
+use support::{
+	Parameter,
+	additional_traits::{DelegatedCallSpend, DoughnutUsageTracker},
+	traits::Get,
+};
+
+/// The leading byte of an encoded chain.
+///
+/// The first two bytes of an encoded doughnut hold its 11 bit payload version and 5 bit signature version,
+/// so neither byte of a version 0 doughnut has every bit set. A lone doughnut therefore still decodes,
+/// as a chain of one link.
+pub const CHAIN_VERSION: u8 = 0xff;
+
+/// A chain of doughnuts delegating authority from a root issuer, through zero or more intermediate holders,
+/// to a final holder. It can be passed to the runtime as a `SignedExtension` in an extrinsic.
+/// A chain always has at least one link and at most `Runtime::MaxChainLength` links.
+#[derive(Clone, Eq, PartialEq)]
+pub struct PlugDoughnutChain<Doughnut: DoughnutApi, Runtime: DoughnutRuntime>(Vec<PlugDoughnut<Doughnut, Runtime>>);
+
+impl<Doughnut, Runtime> Encode for PlugDoughnutChain<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi + Encode,
+	Runtime: DoughnutRuntime,
+{
+	fn encode_to<T: Output>(&self, dest: &mut T) {
+		CHAIN_VERSION.encode_to(dest);
+		self.0.encode_to(dest);
+	}
+}
+
+impl<Doughnut, Runtime> EncodeLike for PlugDoughnutChain<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi + Encode,
+	Runtime: DoughnutRuntime,
+{}
+
+impl<Doughnut, Runtime> Decode for PlugDoughnutChain<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi + Decode,
+	Runtime: DoughnutRuntime,
+{
+	fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+		let version = input.read_byte()?;
+		if version != CHAIN_VERSION {
+			let link = PlugDoughnut::decode(&mut PrefixedInput { prefix: Some(version), input })?;
+			return Ok(Self(vec![link]));
+		}
+		let len = <Compact<u32>>::decode(input)?.0;
+		if len == 0 {
+			return Err("Doughnut chain has no links".into());
+		}
+		if len > Runtime::MaxChainLength::get() {
+			return Err("Doughnut chain has too many links".into());
+		}
+		let links = (0..len)
+			.map(|_| PlugDoughnut::decode(input))
+			.collect::<Result<Vec<_>, _>>()?;
+		Ok(Self(links))
+	}
+}
+
+/// Input which yields a byte already read from `input` before the rest of `input`
+struct PrefixedInput<'a, I: Input> {
+	prefix: Option<u8>,
+	input: &'a mut I,
+}
+
+impl<'a, I: Input> Input for PrefixedInput<'a, I> {
+	fn remaining_len(&mut self) -> Result<Option<usize>, codec::Error> {
+		let prefix_len = self.prefix.map_or(0, |_| 1);
+		Ok(self.input.remaining_len()?.map(|len| len + prefix_len))
+	}
+
+	fn read(&mut self, into: &mut [u8]) -> Result<(), codec::Error> {
+		match (self.prefix.take(), into.split_first_mut()) {
+			(Some(prefix), Some((first, rest))) => {
+				*first = prefix;
+				self.input.read(rest)
+			}
+			(prefix, _) => {
+				self.prefix = prefix;
+				self.input.read(into)
+			}
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl<Doughnut, Runtime> rstd::fmt::Debug for PlugDoughnutChain<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi + Encode,
+	Runtime: DoughnutRuntime + Send + Sync,
+{
+	fn fmt(&self, f: &mut rstd::fmt::Formatter) -> rstd::fmt::Result {
+		self.0.fmt(f)
+	}
+}
+
+impl<Doughnut, Runtime> PlugDoughnutChain<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi,
+	Runtime: DoughnutRuntime,
+{
+	/// Create a new chain from the `root` doughnut, signed by the root issuer
+	pub fn new(root: PlugDoughnut<Doughnut, Runtime>) -> Self {
+		Self(vec![root])
+	}
+
+	/// Extend the chain with a doughnut re-delegating from the current final holder
+	pub fn delegate(mut self, link: PlugDoughnut<Doughnut, Runtime>) -> Self {
+		self.0.push(link);
+		self
+	}
+
+	/// Return the doughnut held by the final holder
+	fn leaf(&self) -> &PlugDoughnut<Doughnut, Runtime> {
+		self.0.last().expect("a doughnut chain always has at least one link; qed")
+	}
+}
+
+impl<Doughnut, Runtime> DelegationChain for PlugDoughnutChain<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi<Signature=[u8; 64]>,
+	<Doughnut as DoughnutApi>::PublicKey: Into<[u8; 32]>,
+	Runtime: DoughnutRuntime,
+	Runtime::AccountId: AsRef<[u8]> + UncheckedFrom<[u8; 32]>,
+{
+	type Link = PlugDoughnut<Doughnut, Runtime>;
+	fn links(&self) -> &[Self::Link] {
+		&self.0
+	}
+}
+
+// The chain acts as a single doughnut issued by the root issuer to the final holder,
+// valid only while every link is valid.
+impl<Doughnut, Runtime> DoughnutApi for PlugDoughnutChain<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi<Signature=[u8; 64]>,
+	<Doughnut as DoughnutApi>::PublicKey: Into<[u8; 32]>,
+	Runtime: DoughnutRuntime,
+	Runtime::AccountId: AsRef<[u8]> + UncheckedFrom<[u8; 32]>,
+{
+	type PublicKey = Runtime::AccountId;
+	type Signature = <Doughnut as DoughnutApi>::Signature;
+	type Timestamp = <Doughnut as DoughnutApi>::Timestamp;
+
+	fn holder(&self) -> Self::PublicKey {
+		self.leaf().holder()
+	}
+	fn issuer(&self) -> Self::PublicKey {
+		self.0[0].issuer()
+	}
+	fn not_before(&self) -> Self::Timestamp {
+		self.0[1..].iter().fold(self.0[0].not_before(), |latest, link| {
+			let not_before = link.not_before();
+			if not_before > latest { not_before } else { latest }
+		})
+	}
+	fn expiry(&self) -> Self::Timestamp {
+		self.0[1..].iter().fold(self.0[0].expiry(), |earliest, link| {
+			let expiry = link.expiry();
+			if expiry < earliest { expiry } else { earliest }
+		})
+	}
+	fn signature(&self) -> Self::Signature {
+		self.leaf().signature()
+	}
+	fn signature_version(&self) -> u8 {
+		self.leaf().signature_version()
+	}
+	fn payload(&self) -> Vec<u8> {
+		self.leaf().payload()
+	}
+	fn get_domain(&self, domain: &str) -> Option<&[u8]> {
+		self.leaf().get_domain(domain)
+	}
+}
+
+impl<Doughnut, Runtime> DoughnutFees for PlugDoughnutChain<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi,
+	Runtime: DoughnutRuntime,
+{
+	/// The root issuer pays fees only if every link of the chain says so
+	fn issuer_pays_fees(&self) -> bool {
+		self.0.iter().all(|link| link.issuer_pays_fees())
+	}
+}
+
+impl<Doughnut, Runtime> DoughnutWeight for PlugDoughnutChain<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi,
+	Runtime: DoughnutRuntime,
+{
+	/// The signature of every link is verified
+	fn verification_weight(&self) -> Weight {
+		self.0.iter().fold(0, |weight, link| weight.saturating_add(link.verification_weight()))
+	}
+}
+
+impl<Doughnut, Runtime> DoughnutVerify for PlugDoughnutChain<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi<Signature=[u8; 64]>,
+	<Doughnut as DoughnutApi>::PublicKey: Into<[u8; 32]>,
+	Runtime: DoughnutRuntime,
+{
+	/// Verify the signature of every link
+	fn verify(&self) -> Result<(), VerifyError> {
+		self.0.iter().try_for_each(|link| link.verify())
+	}
+}
+
+impl<Doughnut, Runtime> SignedExtension for PlugDoughnutChain<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi<Signature=[u8; 64]> + Member + Parameter,
+	<Doughnut as DoughnutApi>::PublicKey: Into<[u8; 32]>,
+	Runtime: DoughnutRuntime + Eq + Clone + Send + Sync,
+	Runtime::AccountId: AsRef<[u8]> + UncheckedFrom<[u8; 32]>,
+{
+	type AccountId = Runtime::AccountId;
+	type AdditionalSigned = ();
+	type Call = Runtime::Call;
+	/// The links with usage limits and the amounts spent by the call, whose use must be recorded
+	type Pre = (Vec<PlugDoughnut<Doughnut, Runtime>>, Vec<(u64, u128)>);
+	fn additional_signed(&self) -> rstd::result::Result<(), TransactionValidityError> { Ok(()) }
+	fn validate(&self, who: &Self::AccountId, call: &Self::Call, _info: DispatchInfo, _len: usize) -> Result<ValidTransaction, TransactionValidityError>
+	{
+		self.validate_use(who, call).map(|_| ValidTransaction::default())
+	}
+	fn pre_dispatch(&self, who: &Self::AccountId, call: &Self::Call, _info: DispatchInfo, _len: usize) -> Result<Self::Pre, ApplyError>
+	{
+		let (spend, limited_links) = self.validate_use(who, call)?;
+		Ok((limited_links.into_iter().cloned().collect(), spend))
+	}
+	fn post_dispatch(pre: Self::Pre, _info: DispatchInfo, _len: usize, result: &ApplyOutcome) {
+		// A failed dispatch spends nothing, so it does not count against any link's limits
+		if result.is_ok() {
+			let (limited_links, spend) = pre;
+			for link in limited_links {
+				Runtime::UsageTracker::record_use(&link, &spend);
+			}
+		}
+	}
+}
+
+impl<Doughnut, Runtime> PlugDoughnutChain<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi<Signature=[u8; 64]> + Member + Parameter,
+	<Doughnut as DoughnutApi>::PublicKey: Into<[u8; 32]>,
+	Runtime: DoughnutRuntime,
+	Runtime::AccountId: AsRef<[u8]> + UncheckedFrom<[u8; 32]>,
+{
+	/// Check the chain may be used by `who` to dispatch `call`.
+	/// Returns the amounts spent by `call` and the links with usage limits, whose use must be recorded.
+	fn validate_use(
+		&self,
+		who: &Runtime::AccountId,
+		call: &Runtime::Call,
+	) -> Result<(Vec<(u64, u128)>, Vec<&PlugDoughnut<Doughnut, Runtime>>), TransactionValidityError> {
+		if self.0.windows(2).any(|pair| pair[0].holder() != pair[1].issuer()) {
+			// 175 == doughnut chain is broken, a link's holder is not the next link's issuer
+			return Err(InvalidTransaction::Custom(175).into())
+		}
+		let spend = Runtime::CallSpend::spend(call);
+		let mut limited_links = Vec::new();
+		for (index, link) in self.0.iter().enumerate() {
+			// Intermediate links are used by the next issuer, the final link by `who`
+			match self.0.get(index + 1) {
+				Some(next) => link.validate_link(&next.issuer())?,
+				None => link.validate_link(who)?,
+			}
+			if link.check_usage_limits(spend.as_ref().map(Vec::as_slice))? {
+				limited_links.push(link);
+			}
+		}
+		Ok((spend.unwrap_or_default(), limited_links))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{DOUGHNUT_LINK_WEIGHT, MethodDomain, ModuleDomain, PlugDomain, PlugDoughnutDispatcher, mock::{ChainRuntime, MaxChainLength}};
+	use primitives::{crypto::Pair, sr25519};
+	use sr_primitives::DoughnutV0;
+	use support::additional_traits::DelegatedDispatchVerifier;
+
+	/// Make a signed doughnut from `issuer` to `holder`, permitting `methods` of the balances module
+	fn make_link(
+		issuer: &sr25519::Pair,
+		holder: &sr25519::Pair,
+		expiry: u32,
+		methods: &[&[u8]],
+	) -> PlugDoughnut<DoughnutV0, ChainRuntime> {
+		let domain = methods.iter().fold(ModuleDomain::new(b"srml-balances"), |module, method| {
+			module.method(MethodDomain::new(method))
+		});
+		let mut doughnut = DoughnutV0 {
+			issuer: issuer.public().into(),
+			holder: holder.public().into(),
+			expiry,
+			not_before: 0,
+			payload_version: 0,
+			signature: [0u8; 64].into(),
+			signature_version: 0,
+			domains: vec![("plug".to_string(), PlugDomain::new().module(domain).encode())],
+		};
+		doughnut.signature = issuer.sign(&doughnut.payload()).into();
+		PlugDoughnut::new(doughnut)
+	}
+
+	fn pair(seed: &str) -> sr25519::Pair {
+		sr25519::Pair::from_string(seed, None).unwrap()
+	}
+
+	#[test]
+	fn chain_acts_for_root_issuer_and_final_holder() {
+		let (alice, bob, charlie) = (pair("//Alice"), pair("//Bob"), pair("//Charlie"));
+		let chain = PlugDoughnutChain::new(make_link(&alice, &bob, 3000, &[b"*"]))
+			.delegate(make_link(&bob, &charlie, 2000, &[b"transfer"]));
+
+		assert_eq!(chain.issuer(), alice.public());
+		assert_eq!(chain.holder(), charlie.public());
+		assert_eq!(chain.expiry(), 2000);
+		assert!(chain.verify().is_ok());
+		assert!(SignedExtension::validate(&chain, &charlie.public(), &(), Default::default(), 0).is_ok());
+		// Only the final holder may use the chain
+		assert_eq!(
+			SignedExtension::validate(&chain, &bob.public(), &(), Default::default(), 0),
+			Err(InvalidTransaction::Custom(171).into()),
+		);
+	}
+
+	#[test]
+	fn chain_fails_when_a_link_holder_is_not_the_next_issuer() {
+		let (alice, bob, charlie, dave) = (pair("//Alice"), pair("//Bob"), pair("//Charlie"), pair("//Dave"));
+		let chain = PlugDoughnutChain::new(make_link(&alice, &bob, 3000, &[b"*"]))
+			.delegate(make_link(&charlie, &dave, 3000, &[b"transfer"]));
+
+		assert_eq!(
+			SignedExtension::validate(&chain, &dave.public(), &(), Default::default(), 0),
+			Err(InvalidTransaction::Custom(175).into()),
+		);
+	}
+
+	#[test]
+	fn chain_fails_when_a_link_signature_is_invalid() {
+		let (alice, bob, charlie) = (pair("//Alice"), pair("//Bob"), pair("//Charlie"));
+		let mut root = make_link(&alice, &bob, 3000, &[b"*"]);
+		// Signed by the holder rather than the issuer
+		(root.0).signature = bob.sign(&root.payload()).into();
+		let chain = PlugDoughnutChain::new(root).delegate(make_link(&bob, &charlie, 3000, &[b"transfer"]));
+
+		assert_eq!(chain.verify(), Err(VerifyError::Invalid));
+		assert_eq!(
+			SignedExtension::validate(&chain, &charlie.public(), &(), Default::default(), 0),
+			Err(InvalidTransaction::Custom(170).into()),
+		);
+	}
+
+	#[test]
+	fn chain_fails_when_a_link_is_premature() {
+		let (alice, bob, charlie) = (pair("//Alice"), pair("//Bob"), pair("//Charlie"));
+		// The root link is not yet valid, so the re-delegation is invalid also
+		let mut root = make_link(&alice, &bob, 3000, &[b"*"]);
+		(root.0).not_before = 1000;
+		(root.0).signature = alice.sign(&root.payload()).into();
+		let chain = PlugDoughnutChain::new(root).delegate(make_link(&bob, &charlie, 3000, &[b"transfer"]));
+
+		assert_eq!(chain.not_before(), 1000);
+
+		assert_eq!(
+			SignedExtension::validate(&chain, &charlie.public(), &(), Default::default(), 0),
+			Err(InvalidTransaction::Custom(171).into()),
+		);
+	}
+
+	#[test]
+	fn chain_dispatch_requires_every_link_to_permit_the_call() {
+		let (alice, bob, charlie) = (pair("//Alice"), pair("//Bob"), pair("//Charlie"));
+		// Bob attempts to widen the permissions Alice granted
+		let chain = PlugDoughnutChain::new(make_link(&alice, &bob, 3000, &[b"transfer"]))
+			.delegate(make_link(&bob, &charlie, 3000, &[b"*"]));
+
+		assert!(PlugDoughnutDispatcher::<ChainRuntime>::verify_dispatch(&chain, "srml-balances", "transfer", &[]).is_ok());
+		assert_eq!(
+			PlugDoughnutDispatcher::<ChainRuntime>::verify_dispatch(&chain, "srml-balances", "set_balance", &[]),
+			Err("Doughnut does not grant permission for method"),
+		);
+	}
+
+	#[test]
+	fn chain_decode_fails_with_no_links() {
+		let empty: Vec<PlugDoughnut<DoughnutV0, ChainRuntime>> = Vec::new();
+		assert!(PlugDoughnutChain::<DoughnutV0, ChainRuntime>::decode(&mut &(CHAIN_VERSION, empty).encode()[..]).is_err());
+	}
+
+	#[test]
+	fn chain_decode_fails_with_too_many_links() {
+		let (alice, bob) = (pair("//Alice"), pair("//Bob"));
+		let links = vec![make_link(&alice, &bob, 3000, &[b"*"]); MaxChainLength::get() as usize + 1];
+		assert_eq!(
+			PlugDoughnutChain::<DoughnutV0, ChainRuntime>::decode(&mut &(CHAIN_VERSION, links).encode()[..]),
+			Err("Doughnut chain has too many links".into()),
+		);
+	}
+
+	#[test]
+	fn chain_decodes_a_lone_doughnut() {
+		let (alice, bob) = (pair("//Alice"), pair("//Bob"));
+		let link = make_link(&alice, &bob, 3000, &[b"*"]);
+		assert_eq!(
+			PlugDoughnutChain::decode(&mut &link.encode()[..]),
+			Ok(PlugDoughnutChain::<DoughnutV0, ChainRuntime>::new(link)),
+		);
+	}
+
+	#[test]
+	fn chain_verification_weight_grows_with_its_links() {
+		let (alice, bob, charlie) = (pair("//Alice"), pair("//Bob"), pair("//Charlie"));
+		let chain = PlugDoughnutChain::new(make_link(&alice, &bob, 3000, &[b"*"]));
+		assert_eq!(chain.verification_weight(), DOUGHNUT_LINK_WEIGHT);
+		let chain = chain.delegate(make_link(&bob, &charlie, 3000, &[b"transfer"]));
+		assert_eq!(chain.verification_weight(), 2 * DOUGHNUT_LINK_WEIGHT);
+	}
+
+	#[test]
+	fn chain_encode_decode_roundtrips() {
+		let (alice, bob, charlie) = (pair("//Alice"), pair("//Bob"), pair("//Charlie"));
+		let chain = PlugDoughnutChain::new(make_link(&alice, &bob, 3000, &[b"*"]))
+			.delegate(make_link(&bob, &charlie, 3000, &[b"transfer"]));
+		assert_eq!(PlugDoughnutChain::decode(&mut &chain.encode()[..]), Ok(chain));
+	}
+}
@@ -0,0 +1,446 @@
+// Copyright 2019 Plug New Zealand Limited
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The "plug" doughnut permission domain.
+//!
+//! The domain payload is a version byte followed by the SCALE encoding of a `PlugDomain`:
+//!
+//! ```text
+//! PlugDomain   { modules: Vec<ModuleDomain>, limits: UsageLimits, issuer_pays_fees: bool }
+//! UsageLimits  { max_uses: Option<u32>, max_spend: Vec<(u64, u128)> }
+//! ModuleDomain { name: Vec<u8>, methods: Vec<MethodDomain> }
+//! MethodDomain { name: Vec<u8>, constraints: Vec<ArgConstraint> }
+//! ArgConstraint { name: Vec<u8>, rule: ArgRule }
+//! ```
+//!
+//! Module names are runtime module crate names e.g. `srml-balances`, method names are the
+//! dispatchable function names e.g. `transfer`. A name of `*` matches any module or method.
+//! An exact name match always takes precedence over a wildcard entry.
+//!
+//! Domains of an earlier version decode with the fields it lacks set to their defaults. Version 0 has
+//! only `modules`, version 1 adds `limits` and version 2 adds `issuer_pays_fees`.
+//!
+//! `UsageLimits` bound the number of extrinsics a doughnut may be used for and the total amount
+//! of each asset id which may be spent with it. Assets without a `max_spend` entry are not limited.
+//!
+//! When `issuer_pays_fees` is set, the transaction fees of extrinsics using the doughnut are charged
+//! to the issuer rather than the holder, i.e. the issuer sponsors the holder's transactions.
+
+use codec::{Decode, Encode, Input, Output};
+use rstd::prelude::*;
+use support::additional_traits::DispatchArg;
+
+/// The doughnut permission domain key for plug runtimes
+pub const PLUG_DOMAIN: &str = "plug";
+
+/// The version of the plug domain encoding
+pub const PLUG_DOMAIN_VERSION: u8 = 2;
+
+/// The declared method argument types whose values are SCALE encoded fixed width unsigned integers,
+/// and so may be constrained by `ArgRule::Max`
+pub const NUMERIC_ARG_TYPES: &[&str] = &[
+	"u8", "u16", "u32", "u64", "u128",
+	"T::Balance", "BalanceOf<T>", "T::BlockNumber", "T::Moment", "Gas",
+];
+
+/// The module or method name which matches any module or method
+pub const WILDCARD: &[u8] = b"*";
+
+/// The permissions granted by a doughnut in the "plug" domain
+#[derive(Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct PlugDomain {
+	/// The modules the holder may dispatch calls to
+	pub modules: Vec<ModuleDomain>,
+	/// Limits on the use of the doughnut
+	pub limits: UsageLimits,
+	/// Whether the issuer pays transaction fees on behalf of the holder
+	pub issuer_pays_fees: bool,
+}
+
+/// Limits on the use of a doughnut, tracked by the runtime across extrinsics
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct UsageLimits {
+	/// The maximum number of extrinsics the doughnut may be used for, if any
+	pub max_uses: Option<u32>,
+	/// The maximum total amount which may be spent per asset id
+	pub max_spend: Vec<(u64, u128)>,
+}
+
+/// The permissions granted on a single runtime module
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct ModuleDomain {
+	/// The module crate name or `*`
+	pub name: Vec<u8>,
+	/// The methods of this module the holder may dispatch
+	pub methods: Vec<MethodDomain>,
+}
+
+/// The permissions granted on a single module method
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct MethodDomain {
+	/// The method name or `*`
+	pub name: Vec<u8>,
+	/// Constraints on the method arguments. All must hold for the dispatch to be permitted.
+	pub constraints: Vec<ArgConstraint>,
+}
+
+/// A constraint on a named method argument
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct ArgConstraint {
+	/// The argument name as declared by the module method
+	pub name: Vec<u8>,
+	/// The rule the argument value must satisfy
+	pub rule: ArgRule,
+}
+
+/// A rule applied to the SCALE encoded value of an argument
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum ArgRule {
+	/// The encoded value must equal the given bytes
+	Equal(Vec<u8>),
+	/// The encoded value must equal one of the given byte strings
+	OneOf(Vec<Vec<u8>>),
+	/// The value, decoded as a little endian unsigned integer of up to 128 bits,
+	/// must be less than or equal to the given amount.
+	/// Only applies to arguments of the `NUMERIC_ARG_TYPES`.
+	Max(u128),
+}
+
+impl Encode for PlugDomain {
+	fn encode_to<T: Output>(&self, dest: &mut T) {
+		PLUG_DOMAIN_VERSION.encode_to(dest);
+		self.modules.encode_to(dest);
+		self.limits.encode_to(dest);
+		self.issuer_pays_fees.encode_to(dest);
+	}
+}
+
+impl Decode for PlugDomain {
+	fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+		let version = u8::decode(input)?;
+		if version > PLUG_DOMAIN_VERSION {
+			return Err("Doughnut plug domain version is unsupported".into());
+		}
+		Ok(Self {
+			modules: Decode::decode(input)?,
+			limits: if version >= 1 { Decode::decode(input)? } else { Default::default() },
+			issuer_pays_fees: if version >= 2 { Decode::decode(input)? } else { false },
+		})
+	}
+}
+
+impl PlugDomain {
+	/// Create an empty plug domain which grants no permissions
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add permissions for `module`
+	pub fn module(mut self, module: ModuleDomain) -> Self {
+		self.modules.push(module);
+		self
+	}
+
+	/// Set the usage limits of the doughnut
+	pub fn limits(mut self, limits: UsageLimits) -> Self {
+		self.limits = limits;
+		self
+	}
+
+	/// Oblige the issuer to pay transaction fees on behalf of the holder
+	pub fn issuer_pays_fees(mut self) -> Self {
+		self.issuer_pays_fees = true;
+		self
+	}
+
+	/// Decode a plug domain from raw doughnut domain bytes.
+	/// The input must be consumed entirely.
+	pub fn decode_from(mut input: &[u8]) -> Result<Self, &'static str> {
+		let domain = Self::decode(&mut input).map_err(|_| "Doughnut plug domain is malformed")?;
+		if !input.is_empty() {
+			return Err("Doughnut plug domain has trailing bytes");
+		}
+		Ok(domain)
+	}
+
+	/// Return the permissions for `module`, if any
+	pub fn get_module(&self, module: &str) -> Option<&ModuleDomain> {
+		find_by_name(&self.modules, module, |m| &m.name)
+	}
+
+	/// Verify the domain permits dispatching `module` and `method` with `args`
+	pub fn verify(&self, module: &str, method: &str, args: &[DispatchArg]) -> Result<(), &'static str> {
+		let module_domain = self.get_module(module).ok_or("Doughnut does not grant permission for module")?;
+		let method_domain = module_domain.get_method(method).ok_or("Doughnut does not grant permission for method")?;
+		method_domain.constraints.iter().try_for_each(|constraint| constraint.verify(args))
+	}
+}
+
+impl UsageLimits {
+	/// Create usage limits which limit nothing
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Limit the doughnut to `max_uses` extrinsics
+	pub fn max_uses(mut self, max_uses: u32) -> Self {
+		self.max_uses = Some(max_uses);
+		self
+	}
+
+	/// Limit the total spend of `asset_id` to `amount`
+	pub fn max_spend(mut self, asset_id: u64, amount: u128) -> Self {
+		self.max_spend.push((asset_id, amount));
+		self
+	}
+
+	/// Return whether any limits are set
+	pub fn is_limited(&self) -> bool {
+		self.max_uses.is_some() || !self.max_spend.is_empty()
+	}
+}
+
+impl ModuleDomain {
+	/// Create permissions for the module named `name`
+	pub fn new(name: &[u8]) -> Self {
+		Self { name: name.to_vec(), methods: Vec::new() }
+	}
+
+	/// Add permissions for `method`
+	pub fn method(mut self, method: MethodDomain) -> Self {
+		self.methods.push(method);
+		self
+	}
+
+	/// Return the permissions for `method`, if any
+	pub fn get_method(&self, method: &str) -> Option<&MethodDomain> {
+		find_by_name(&self.methods, method, |m| &m.name)
+	}
+}
+
+impl MethodDomain {
+	/// Create permissions for the method named `name`
+	pub fn new(name: &[u8]) -> Self {
+		Self { name: name.to_vec(), constraints: Vec::new() }
+	}
+
+	/// Add a constraint on the argument `name`
+	pub fn constraint(mut self, name: &[u8], rule: ArgRule) -> Self {
+		self.constraints.push(ArgConstraint { name: name.to_vec(), rule });
+		self
+	}
+}
+
+impl ArgConstraint {
+	/// Verify the constraint holds for `args`.
+	/// A constraint on an argument which is not present fails.
+	pub fn verify(&self, args: &[DispatchArg]) -> Result<(), &'static str> {
+		let arg = args.iter()
+			.find(|arg| arg.name.as_bytes() == self.name.as_slice())
+			.ok_or("Doughnut constrains an unknown method argument")?;
+		let permitted = match &self.rule {
+			ArgRule::Equal(expected) => &arg.value == expected,
+			ArgRule::OneOf(options) => options.contains(&arg.value),
+			ArgRule::Max(max) => {
+				if !NUMERIC_ARG_TYPES.contains(&arg.ty) {
+					return Err("Doughnut constrained method argument is not numeric");
+				}
+				decode_unsigned(&arg.value)? <= *max
+			},
+		};
+		if permitted {
+			Ok(())
+		} else {
+			Err("Doughnut method argument constraint violated")
+		}
+	}
+}
+
+/// Decode a SCALE encoded fixed width unsigned integer of up to 128 bits
+fn decode_unsigned(value: &[u8]) -> Result<u128, &'static str> {
+	if value.is_empty() || value.len() > 16 {
+		return Err("Doughnut constrained method argument is not an unsigned integer");
+	}
+	let mut bytes = [0u8; 16];
+	bytes[..value.len()].copy_from_slice(value);
+	Ok(u128::from_le_bytes(bytes))
+}
+
+/// Find the entry in `items` named `name`, falling back to a wildcard entry
+fn find_by_name<'a, T, F>(items: &'a [T], name: &str, name_of: F) -> Option<&'a T>
+where
+	F: Fn(&T) -> &Vec<u8>,
+{
+	items.iter().find(|item| name_of(item).as_slice() == name.as_bytes())
+		.or_else(|| items.iter().find(|item| name_of(item).as_slice() == WILDCARD))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn arg(name: &'static str, value: Vec<u8>) -> DispatchArg<'static> {
+		DispatchArg { name, ty: "T::Balance", value }
+	}
+
+	#[test]
+	fn plug_domain_encode_decode_roundtrips() {
+		let domain = PlugDomain::new()
+			.module(
+				ModuleDomain::new(b"srml-balances")
+					.method(MethodDomain::new(b"transfer").constraint(b"value", ArgRule::Max(100)))
+			)
+			.module(ModuleDomain::new(b"*").method(MethodDomain::new(b"*")))
+			.limits(UsageLimits::new().max_uses(1).max_spend(16_001, 1_000))
+			.issuer_pays_fees();
+		assert_eq!(PlugDomain::decode_from(&domain.encode()), Ok(domain));
+	}
+
+	#[test]
+	fn plug_domain_decodes_earlier_versions() {
+		let modules = vec![ModuleDomain::new(b"*").method(MethodDomain::new(b"*"))];
+		let limits = UsageLimits::new().max_uses(1);
+		assert_eq!(
+			PlugDomain::decode_from(&(0u8, &modules).encode()),
+			Ok(PlugDomain { modules: modules.clone(), ..Default::default() }),
+		);
+		assert_eq!(
+			PlugDomain::decode_from(&(1u8, &modules, &limits).encode()),
+			Ok(PlugDomain { modules: modules.clone(), limits: limits.clone(), issuer_pays_fees: false }),
+		);
+		assert_eq!(
+			PlugDomain::decode_from(&(2u8, &modules, &limits, true).encode()),
+			Ok(PlugDomain { modules, limits, issuer_pays_fees: true }),
+		);
+	}
+
+	#[test]
+	fn plug_domain_decode_fails_with_unsupported_version() {
+		let mut encoded = PlugDomain::new().encode();
+		encoded[0] = PLUG_DOMAIN_VERSION + 1;
+		assert_eq!(PlugDomain::decode_from(&encoded), Err("Doughnut plug domain is malformed"));
+	}
+
+	#[test]
+	fn plug_domain_decode_fails_with_malformed_input() {
+		assert!(PlugDomain::decode_from(&[1u8]).is_err());
+		let mut encoded = PlugDomain::new().encode();
+		encoded.push(0);
+		assert_eq!(PlugDomain::decode_from(&encoded), Err("Doughnut plug domain has trailing bytes"));
+	}
+
+	#[test]
+	fn plug_domain_verifies_exact_names() {
+		let domain = PlugDomain::new().module(
+			ModuleDomain::new(b"srml-balances").method(MethodDomain::new(b"transfer"))
+		);
+		assert!(domain.verify("srml-balances", "transfer", &[]).is_ok());
+		assert_eq!(domain.verify("srml-balances", "set_balance", &[]), Err("Doughnut does not grant permission for method"));
+		assert_eq!(domain.verify("srml-staking", "transfer", &[]), Err("Doughnut does not grant permission for module"));
+	}
+
+	#[test]
+	fn plug_domain_verifies_wildcards() {
+		let domain = PlugDomain::new()
+			.module(ModuleDomain::new(b"srml-balances").method(MethodDomain::new(b"*")))
+			.module(ModuleDomain::new(b"*").method(MethodDomain::new(b"bond")));
+		assert!(domain.verify("srml-balances", "transfer", &[]).is_ok());
+		assert!(domain.verify("srml-balances", "set_balance", &[]).is_ok());
+		assert!(domain.verify("srml-staking", "bond", &[]).is_ok());
+		assert!(domain.verify("srml-staking", "unbond", &[]).is_err());
+	}
+
+	#[test]
+	fn plug_domain_exact_module_takes_precedence_over_wildcard() {
+		let domain = PlugDomain::new()
+			.module(ModuleDomain::new(b"*").method(MethodDomain::new(b"*")))
+			.module(ModuleDomain::new(b"srml-balances").method(MethodDomain::new(b"transfer")));
+		assert!(domain.verify("srml-staking", "bond", &[]).is_ok());
+		assert!(domain.verify("srml-balances", "set_balance", &[]).is_err());
+	}
+
+	#[test]
+	fn plug_domain_verifies_max_constraint() {
+		let domain = PlugDomain::new().module(
+			ModuleDomain::new(b"srml-balances")
+				.method(MethodDomain::new(b"transfer").constraint(b"value", ArgRule::Max(100)))
+		);
+		assert!(domain.verify("srml-balances", "transfer", &[arg("value", 100u64.encode())]).is_ok());
+		assert!(domain.verify("srml-balances", "transfer", &[arg("value", 100u128.encode())]).is_ok());
+		assert_eq!(
+			domain.verify("srml-balances", "transfer", &[arg("value", 101u64.encode())]),
+			Err("Doughnut method argument constraint violated"),
+		);
+		assert_eq!(
+			domain.verify("srml-balances", "transfer", &[arg("value", vec![0u8; 17])]),
+			Err("Doughnut constrained method argument is not an unsigned integer"),
+		);
+	}
+
+	#[test]
+	fn plug_domain_max_constraint_only_applies_to_numeric_arguments() {
+		let domain = PlugDomain::new().module(
+			ModuleDomain::new(b"srml-balances")
+				.method(MethodDomain::new(b"transfer").constraint(b"dest", ArgRule::Max(u128::max_value())))
+		);
+		let dest = DispatchArg { name: "dest", ty: "<T::Lookup as StaticLookup>::Source", value: vec![1u8; 8] };
+		assert_eq!(
+			domain.verify("srml-balances", "transfer", &[dest]),
+			Err("Doughnut constrained method argument is not numeric"),
+		);
+	}
+
+	#[test]
+	fn plug_domain_verifies_equal_and_one_of_constraints() {
+		let domain = PlugDomain::new().module(
+			ModuleDomain::new(b"srml-balances").method(
+				MethodDomain::new(b"transfer")
+					.constraint(b"dest", ArgRule::OneOf(vec![vec![1u8; 32], vec![2u8; 32]]))
+					.constraint(b"value", ArgRule::Equal(5u64.encode()))
+			)
+		);
+		assert!(
+			domain.verify("srml-balances", "transfer", &[arg("dest", vec![2u8; 32]), arg("value", 5u64.encode())]).is_ok()
+		);
+		assert!(
+			domain.verify("srml-balances", "transfer", &[arg("dest", vec![3u8; 32]), arg("value", 5u64.encode())]).is_err()
+		);
+		assert!(
+			domain.verify("srml-balances", "transfer", &[arg("dest", vec![1u8; 32]), arg("value", 6u64.encode())]).is_err()
+		);
+	}
+
+	#[test]
+	fn plug_domain_rejects_constraint_on_missing_argument() {
+		let domain = PlugDomain::new().module(
+			ModuleDomain::new(b"srml-balances")
+				.method(MethodDomain::new(b"transfer").constraint(b"amount", ArgRule::Max(100)))
+		);
+		assert_eq!(
+			domain.verify("srml-balances", "transfer", &[arg("value", 1u64.encode())]),
+			Err("Doughnut constrains an unknown method argument"),
+		);
+	}
+
+	#[test]
+	fn empty_plug_domain_grants_nothing() {
+		assert!(PlugDomain::new().verify("srml-balances", "transfer", &[]).is_err());
+	}
+}
@@ -12,18 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{DoughnutRuntime, PlugDoughnut};
+use crate::{DoughnutRuntime, PlugDomain, PlugDoughnut, DOUGHNUT_LINK_WEIGHT, PLUG_DOMAIN};
 use primitives::{
 	crypto::UncheckedFrom,
 	ed25519::{self},
 	sr25519::{self},
 };
 use rstd::{self, prelude::*};
-use sr_primitives::traits::{DoughnutApi, DoughnutVerify, Member, SignedExtension, Verify, VerifyError};
+use sr_primitives::traits::{
+	DoughnutApi, DoughnutFees, DoughnutVerify, DoughnutWeight, Member, SignedExtension, Verify, VerifyError,
+};
+use sr_primitives::{ApplyError, ApplyOutcome};
 use sr_primitives::transaction_validity::{InvalidTransaction, TransactionValidityError, ValidTransaction};
-use sr_primitives::weights::DispatchInfo;
+use sr_primitives::weights::{DispatchInfo, Weight};
 use support::{
 	Parameter,
+	additional_traits::{DelegatedCallSpend, DoughnutRevocationRegistry, DoughnutUsageTracker},
 	traits::Time,
 };
 
@@ -65,6 +69,30 @@ where
 	}
 }
 
+impl<Doughnut, Runtime> DoughnutFees for PlugDoughnut<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi,
+	Runtime: DoughnutRuntime,
+{
+	/// The issuer pays fees when its plug domain says so. A missing or malformed plug domain does not.
+	fn issuer_pays_fees(&self) -> bool {
+		self.0.get_domain(PLUG_DOMAIN)
+			.and_then(|domain| PlugDomain::decode_from(domain).ok())
+			.map(|domain| domain.issuer_pays_fees)
+			.unwrap_or(false)
+	}
+}
+
+impl<Doughnut, Runtime> DoughnutWeight for PlugDoughnut<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi,
+	Runtime: DoughnutRuntime,
+{
+	fn verification_weight(&self) -> Weight {
+		DOUGHNUT_LINK_WEIGHT
+	}
+}
+
 // Re-implemented here due to sr25519 verification requiring an external
 // wasm VM call when using `no std`
 impl<Doughnut, Runtime> DoughnutVerify for PlugDoughnut<Doughnut, Runtime>
@@ -105,49 +133,111 @@ where
 	Doughnut: DoughnutApi<Signature=[u8; 64]> + Member + Parameter,
 	<Doughnut as DoughnutApi>::PublicKey: Into<[u8; 32]>,
 	Runtime: DoughnutRuntime + Eq + Clone + Send + Sync,
-	Runtime::AccountId: AsRef<[u8]>,
+	Runtime::AccountId: AsRef<[u8]> + UncheckedFrom<[u8; 32]>,
 {
 	type AccountId = Runtime::AccountId;
 	type AdditionalSigned = ();
 	type Call = Runtime::Call;
-	type Pre = ();
+	/// The doughnut and the amounts spent by the call, when its use must be recorded
+	type Pre = Option<(Self, Vec<(u64, u128)>)>;
 	fn additional_signed(&self) -> rstd::result::Result<(), TransactionValidityError> { Ok(()) }
-	fn validate(&self, who: &Self::AccountId, _call: &Self::Call, _info: DispatchInfo, _len: usize) -> Result<ValidTransaction, TransactionValidityError>
+	fn validate(&self, who: &Self::AccountId, call: &Self::Call, _info: DispatchInfo, _len: usize) -> Result<ValidTransaction, TransactionValidityError>
 	{
+		self.validate_use(who, call).map(|_| ValidTransaction::default())
+	}
+	fn pre_dispatch(&self, who: &Self::AccountId, call: &Self::Call, _info: DispatchInfo, _len: usize) -> Result<Self::Pre, ApplyError>
+	{
+		Ok(self.validate_use(who, call)?.map(|spend| (self.clone(), spend)))
+	}
+	fn post_dispatch(pre: Self::Pre, _info: DispatchInfo, _len: usize, result: &ApplyOutcome) {
+		// A failed dispatch spends nothing, so it does not count against the doughnut's limits
+		if let (Some((doughnut, spend)), Ok(())) = (pre, result) {
+			Runtime::UsageTracker::record_use(&doughnut, &spend);
+		}
+	}
+}
+
+impl<Doughnut, Runtime> PlugDoughnut<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi<Signature=[u8; 64]> + Member + Parameter,
+	<Doughnut as DoughnutApi>::PublicKey: Into<[u8; 32]>,
+	Runtime: DoughnutRuntime,
+	Runtime::AccountId: AsRef<[u8]> + UncheckedFrom<[u8; 32]>,
+{
+	/// Check the doughnut may be used by `who` to dispatch `call`.
+	/// Returns the amounts spent by `call` when the doughnut has usage limits, and so its use must be recorded.
+	fn validate_use(&self, who: &Runtime::AccountId, call: &Runtime::Call) -> Result<Option<Vec<(u64, u128)>>, TransactionValidityError> {
+		self.validate_link(who)?;
+		let spend = Runtime::CallSpend::spend(call);
+		if self.check_usage_limits(spend.as_ref().map(Vec::as_slice))? {
+			Ok(Some(spend.unwrap_or_default()))
+		} else {
+			Ok(None)
+		}
+	}
+
+	/// Check the doughnut has a valid signature, may be used by `holder` at the current timestamp and
+	/// has not been revoked.
+	pub(crate) fn validate_link(&self, holder: &Runtime::AccountId) -> Result<(), TransactionValidityError> {
 		if self.verify().is_err() {
 			// 170 == invalid signature on doughnut
 			return Err(InvalidTransaction::Custom(170).into())
 		}
-		if let Err(_) = self.0.validate(who, Runtime::TimestampProvider::now()) {
+		if let Err(_) = self.0.validate(holder, Runtime::TimestampProvider::now()) {
 			// 171 == use of doughnut by who at the current timestamp is invalid
 			return Err(InvalidTransaction::Custom(171).into())
 		}
-		Ok(ValidTransaction::default())
+		if Runtime::RevocationRegistry::is_revoked(self) {
+			// 172 == doughnut has been revoked by its issuer
+			return Err(InvalidTransaction::Custom(172).into())
+		}
+		Ok(())
+	}
+
+	/// Check the doughnut's usage limits permit another use which spends `spend`, or an unaccounted amount if `None`.
+	/// Returns whether the doughnut has usage limits, and so its use must be recorded.
+	pub(crate) fn check_usage_limits(&self, spend: Option<&[(u64, u128)]>) -> Result<bool, TransactionValidityError> {
+		// A missing or malformed plug domain has no limits, dispatch verification will reject it
+		let limits = match self.0.get_domain(PLUG_DOMAIN).and_then(|domain| PlugDomain::decode_from(domain).ok()) {
+			Some(domain) if domain.limits.is_limited() => domain.limits,
+			_ => return Ok(false),
+		};
+		if !Runtime::UsageTracker::enforces_limits() {
+			// 176 == doughnut has usage limits which the runtime can not enforce
+			return Err(InvalidTransaction::Custom(176).into())
+		}
+		if let Some(max_uses) = limits.max_uses {
+			if Runtime::UsageTracker::uses(self) >= max_uses {
+				// 173 == doughnut has been used the maximum number of times
+				return Err(InvalidTransaction::Custom(173).into())
+			}
+		}
+		if limits.max_spend.is_empty() {
+			return Ok(true)
+		}
+		// 177 == doughnut has spending limits and the call's spend is not accounted
+		let spend = spend.ok_or(InvalidTransaction::Custom(177))?;
+		for (asset_id, max_spend) in &limits.max_spend {
+			let total = spend.iter()
+				.filter(|(spend_asset_id, _)| spend_asset_id == asset_id)
+				.fold(Runtime::UsageTracker::spent(self, *asset_id), |total, (_, amount)| total.saturating_add(*amount));
+			if total > *max_spend {
+				// 174 == call would exceed the doughnut's spending limit
+				return Err(InvalidTransaction::Custom(174).into())
+			}
+		}
+		Ok(true)
 	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::{MethodDomain, ModuleDomain, PlugDoughnutDispatcher, UsageLimits, mock::Runtime};
+	use codec::Encode;
 	use primitives::crypto::Pair;
 	use sr_primitives::DoughnutV0;
-
-	#[derive(Clone, Eq, PartialEq)]
-	pub struct Runtime;
-
-	pub struct TimestampProvider;
-	impl Time for TimestampProvider {
-		type Moment = u64;
-		fn now() -> Self::Moment {
-			0
-		}
-	}
-	impl DoughnutRuntime for Runtime {
-		type AccountId = sr25519::Public;
-		type Call = ();
-		type Doughnut = PlugDoughnut<DoughnutV0, Self>;
-		type TimestampProvider = TimestampProvider;
-	}
+	use support::additional_traits::DelegatedDispatchVerifier;
 
 	// TODO: Re-write using substrate-keyring
 	#[test]
@@ -324,4 +414,78 @@ mod tests {
 		assert_eq!(&doughnut.signature()[..], &signature[..]);
 		assert_eq!(doughnut.payload(), doughnut.0.payload());
 	}
+
+	#[test]
+	fn plug_doughnut_dispatcher_verifies_plug_domain() {
+		let domain = PlugDomain::new().module(
+			ModuleDomain::new(b"srml-balances").method(MethodDomain::new(b"transfer"))
+		);
+		let doughnut = PlugDoughnut::<_, Runtime>::new(DoughnutV0 {
+			issuer: [0u8; 32],
+			holder: [1u8; 32],
+			expiry: 0,
+			not_before: 0,
+			payload_version: 0,
+			signature: [1u8; 64].into(),
+			signature_version: 0,
+			domains: vec![("plug".to_string(), domain.encode())],
+		});
+		assert!(PlugDoughnutDispatcher::<Runtime>::verify_dispatch(&doughnut, "srml-balances", "transfer", &[]).is_ok());
+		assert!(PlugDoughnutDispatcher::<Runtime>::verify_dispatch(&doughnut, "srml-balances", "set_balance", &[]).is_err());
+	}
+
+	#[test]
+	fn plug_doughnut_issuer_pays_fees_when_plug_domain_says_so() {
+		let make_doughnut = |domains| PlugDoughnut::<_, Runtime>::new(DoughnutV0 {
+			issuer: [0u8; 32],
+			holder: [1u8; 32],
+			expiry: 0,
+			not_before: 0,
+			payload_version: 0,
+			signature: [1u8; 64].into(),
+			signature_version: 0,
+			domains,
+		});
+		assert!(make_doughnut(vec![("plug".to_string(), PlugDomain::new().issuer_pays_fees().encode())]).issuer_pays_fees());
+		assert!(!make_doughnut(vec![("plug".to_string(), PlugDomain::new().encode())]).issuer_pays_fees());
+		assert!(!make_doughnut(vec![("test".to_string(), vec![0u8])]).issuer_pays_fees());
+	}
+
+	#[test]
+	fn plug_doughnut_with_usage_limits_is_rejected_when_usage_is_not_tracked() {
+		let make_doughnut = |domain: PlugDomain| PlugDoughnut::<_, Runtime>::new(DoughnutV0 {
+			issuer: [0u8; 32],
+			holder: [1u8; 32],
+			expiry: 0,
+			not_before: 0,
+			payload_version: 0,
+			signature: [1u8; 64].into(),
+			signature_version: 0,
+			domains: vec![("plug".to_string(), domain.encode())],
+		});
+		// The mock runtime's `()` usage tracker can not enforce limits
+		assert_eq!(make_doughnut(PlugDomain::new()).check_usage_limits(Some(&[])), Ok(false));
+		assert_eq!(
+			make_doughnut(PlugDomain::new().limits(UsageLimits::new().max_uses(1))).check_usage_limits(Some(&[])),
+			Err(InvalidTransaction::Custom(176).into()),
+		);
+	}
+
+	#[test]
+	fn plug_doughnut_dispatcher_fails_without_plug_domain() {
+		let doughnut = PlugDoughnut::<_, Runtime>::new(DoughnutV0 {
+			issuer: [0u8; 32],
+			holder: [1u8; 32],
+			expiry: 0,
+			not_before: 0,
+			payload_version: 0,
+			signature: [1u8; 64].into(),
+			signature_version: 0,
+			domains: vec![("test".to_string(), vec![0u8])],
+		});
+		assert_eq!(
+			PlugDoughnutDispatcher::<Runtime>::verify_dispatch(&doughnut, "srml-balances", "transfer", &[]),
+			Err("Doughnut does not grant permission for plug domain"),
+		);
+	}
 }
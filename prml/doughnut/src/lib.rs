@@ -17,13 +17,23 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Encode, Decode};
+use primitives::crypto::UncheckedFrom;
 use rstd::{self};
 use sr_primitives::traits::{DoughnutApi, Member};
+use sr_primitives::weights::Weight;
 use support::Parameter;
-use support::additional_traits::DelegatedDispatchVerifier;
-use support::traits::Time;
+use support::additional_traits::{
+	DelegatedCallSpend, DelegatedDispatchVerifier, DispatchArg, DoughnutRevocationRegistry, DoughnutUsageTracker,
+};
+use support::traits::{Get, Time};
 
+mod chain;
+mod domain;
 mod impls;
+mod mock;
+
+pub use chain::{PlugDoughnutChain, CHAIN_VERSION};
+pub use domain::{ArgConstraint, ArgRule, MethodDomain, ModuleDomain, PlugDomain, UsageLimits, PLUG_DOMAIN, WILDCARD};
 
 // TODO: This should eventually become a super trait for `system::Trait` so that all doughnut functionality may be moved here
 /// A runtime which supports doughnut verification and validation
@@ -32,8 +42,20 @@ pub trait DoughnutRuntime {
 	type Call;
 	type Doughnut: Member + Parameter + DoughnutApi;
 	type TimestampProvider: Time;
+	/// The registry of doughnuts revoked by their issuers
+	type RevocationRegistry: DoughnutRevocationRegistry<Self::AccountId>;
+	/// Tracks doughnut use so that usage limits may be enforced
+	type UsageTracker: DoughnutUsageTracker;
+	/// Determines the amounts a call spends on behalf of the doughnut issuer
+	type CallSpend: DelegatedCallSpend<Self::Call>;
+	/// The maximum number of links in a delegation chain
+	type MaxChainLength: Get<u32>;
 }
 
+/// The weight of verifying a single doughnut, which adds to the weight of the extrinsic it is attached to.
+/// A delegation chain weighs this much per link.
+pub const DOUGHNUT_LINK_WEIGHT: Weight = 10_000;
+
 /// A doughnut wrapped for compatibility with the extrinsic transport layer and the plug runtime types.
 /// It can be passed to the runtime as a `SignedExtension` in an extrinsic.
 #[derive(Encode, Decode, Clone, Eq, PartialEq)]
@@ -61,17 +83,47 @@ where
 	}
 }
 
+/// A doughnut proof made up of one or more delegation links, ordered from the root issuer to the final holder
+pub trait DelegationChain {
+	type Link: DoughnutApi;
+	/// Return the links of the chain
+	fn links(&self) -> &[Self::Link];
+}
+
+impl<Doughnut, Runtime> DelegationChain for PlugDoughnut<Doughnut, Runtime>
+where
+	Doughnut: DoughnutApi<Signature=[u8; 64]>,
+	<Doughnut as DoughnutApi>::PublicKey: Into<[u8; 32]>,
+	Runtime: DoughnutRuntime,
+	Runtime::AccountId: AsRef<[u8]> + UncheckedFrom<[u8; 32]>,
+{
+	type Link = Self;
+	fn links(&self) -> &[Self::Link] {
+		rstd::slice::from_ref(self)
+	}
+}
+
 /// It verifies that a doughnut allows execution of a module+method combination
 pub struct PlugDoughnutDispatcher<Runtime: DoughnutRuntime>(rstd::marker::PhantomData<Runtime>);
 
-impl<Runtime: DoughnutRuntime> DelegatedDispatchVerifier<Runtime::Doughnut> for PlugDoughnutDispatcher<Runtime> {
-	const DOMAIN: &'static str = "plug";
-	/// Verify a Doughnut proof authorizes method dispatch given some input parameters
+impl<Runtime> DelegatedDispatchVerifier<Runtime::Doughnut> for PlugDoughnutDispatcher<Runtime>
+where
+	Runtime: DoughnutRuntime,
+	Runtime::Doughnut: DelegationChain,
+{
+	const DOMAIN: &'static str = PLUG_DOMAIN;
+	/// Verify a Doughnut proof authorizes method dispatch given some input parameters.
+	/// Every link of a delegation chain must authorize the dispatch.
 	fn verify_dispatch(
-		_doughnut: &Runtime::Doughnut,
-		_module: &str,
-		_method: &str,
+		doughnut: &Runtime::Doughnut,
+		module: &str,
+		method: &str,
+		args: &[DispatchArg],
 	) -> Result<(), &'static str> {
-		Err("Doughnut dispatch verification is not implemented for this domain")
+		for link in doughnut.links() {
+			let domain = link.get_domain(Self::DOMAIN).ok_or("Doughnut does not grant permission for plug domain")?;
+			PlugDomain::decode_from(domain)?.verify(module, method, args)?;
+		}
+		Ok(())
 	}
 }
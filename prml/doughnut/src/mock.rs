@@ -0,0 +1,62 @@
+// Copyright 2019 Plug New Zealand Limited
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mock runtimes for the doughnut tests
+
+#![cfg(test)]
+
+use crate::{DoughnutRuntime, PlugDoughnut, PlugDoughnutChain};
+use primitives::sr25519;
+use sr_primitives::DoughnutV0;
+use support::{parameter_types, traits::Time};
+
+pub struct TimestampProvider;
+impl Time for TimestampProvider {
+	type Moment = u64;
+	fn now() -> Self::Moment {
+		0
+	}
+}
+
+parameter_types! {
+	pub const MaxChainLength: u32 = 3;
+}
+
+/// A runtime using single link doughnuts
+#[derive(Clone, Eq, PartialEq)]
+pub struct Runtime;
+impl DoughnutRuntime for Runtime {
+	type AccountId = sr25519::Public;
+	type Call = ();
+	type Doughnut = PlugDoughnut<DoughnutV0, Self>;
+	type TimestampProvider = TimestampProvider;
+	type RevocationRegistry = ();
+	type UsageTracker = ();
+	type CallSpend = ();
+	type MaxChainLength = MaxChainLength;
+}
+
+/// A runtime using chained doughnuts
+#[derive(Clone, Eq, PartialEq)]
+pub struct ChainRuntime;
+impl DoughnutRuntime for ChainRuntime {
+	type AccountId = sr25519::Public;
+	type Call = ();
+	type Doughnut = PlugDoughnutChain<DoughnutV0, Self>;
+	type TimestampProvider = TimestampProvider;
+	type RevocationRegistry = ();
+	type UsageTracker = ();
+	type CallSpend = ();
+	type MaxChainLength = MaxChainLength;
+}
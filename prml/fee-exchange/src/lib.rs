@@ -0,0 +1,429 @@
+// Copyright 2019 Plug New Zealand Limited
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Fee Exchange
+//!
+//! Provides the `TakeFees` signed extension, which lets a transactor pay transaction fees in any Generic Asset
+//! asset rather than in the Balances currency.
+//!
+//! ## Overview
+//!
+//! When a transaction includes a `FeeExchange`, its fee is computed in the Generic Asset spending asset from the
+//! module's own fee schedule, `SpendingBaseFee`, `SpendingByteFee` and `SpendingWeightToFee`, and any tip is
+//! taken to be an amount of the spending asset. The fee is converted into the chosen asset using the runtime's
+//! `AssetPricing` source, and the converted amount is withdrawn from the payer and handed to `ExchangePayment`.
+//!
+//! The signer sets a `max_payment` to protect themselves from price movements between signing and inclusion.
+//! The transaction is invalid if the converted fee exceeds it.
+//!
+//! Transactions without a `FeeExchange` are charged in the Balances currency exactly as by `balances::TakeFees`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use generic_asset::DustImbalance;
+use sr_primitives::{
+	traits::{Convert, SaturatedConversion, Saturating, SignedExtension, Zero},
+	transaction_validity::{
+		InvalidTransaction, TransactionPriority, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	weights::{DispatchInfo, Weight},
+	ApplyError,
+};
+use support::{
+	additional_traits::AssetPricing,
+	traits::{Get, OnUnbalanced, WithdrawReason},
+};
+
+type AssetIdOf<T> = <T as generic_asset::Trait>::AssetId;
+type BalanceOf<T> = <T as balances::Trait>::Balance;
+
+pub trait Trait: balances::Trait + generic_asset::Trait<Balance = BalanceOf<Self>> {
+	/// The price source used to convert fees from the spending asset into the asset they are paid with
+	type AssetPricing: AssetPricing<AssetIdOf<Self>, BalanceOf<Self>>;
+	/// The fee to be paid for making a transaction; the base, in the spending asset.
+	type SpendingBaseFee: Get<BalanceOf<Self>>;
+	/// The fee to be paid for making a transaction; the per-byte portion, in the spending asset.
+	type SpendingByteFee: Get<BalanceOf<Self>>;
+	/// Convert a weight value into a deductible fee in the spending asset.
+	type SpendingWeightToFee: Convert<Weight, BalanceOf<Self>>;
+	/// Handler for the fees withdrawn in exchange assets. Dropping them burns them.
+	type ExchangePayment: OnUnbalanced<DustImbalance<Self>>;
+}
+
+/// The asset a transactor chooses to pay fees with
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct FeeExchange<AssetId, Balance> {
+	/// The asset to pay fees with
+	#[codec(compact)]
+	pub asset_id: AssetId,
+	/// The maximum amount of `asset_id` the transactor will pay
+	#[codec(compact)]
+	pub max_payment: Balance,
+}
+
+impl<AssetId, Balance> FeeExchange<AssetId, Balance> {
+	/// Create a new `FeeExchange`
+	pub fn new(asset_id: AssetId, max_payment: Balance) -> Self {
+		Self { asset_id, max_payment }
+	}
+}
+
+/// Require the transactor pay for themselves, either in the Balances currency or an asset of their choosing,
+/// and maybe include a tip to gain additional priority in the queue.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct TakeFees<T: Trait>(
+	#[codec(compact)] <T as balances::Trait>::Balance,
+	Option<FeeExchange<<T as generic_asset::Trait>::AssetId, <T as balances::Trait>::Balance>>,
+);
+
+impl<T: Trait> TakeFees<T> {
+	/// utility constructor. Used only in client/factory code.
+	pub fn new(tip: BalanceOf<T>, fee_exchange: Option<FeeExchange<AssetIdOf<T>, BalanceOf<T>>>) -> Self {
+		Self(tip, fee_exchange)
+	}
+
+	/// Compute the fee for a transaction in the spending asset, including `tip`.
+	///
+	/// The same as `balances::TakeFees::compute_fee`, using the spending asset's fee schedule.
+	pub fn compute_spending_fee(len: usize, info: DispatchInfo, tip: BalanceOf<T>) -> BalanceOf<T> {
+		let len_fee = if info.pay_length_fee() {
+			let len = BalanceOf::<T>::from(len as u32);
+			let base = T::SpendingBaseFee::get();
+			let per_byte = T::SpendingByteFee::get();
+			base.saturating_add(per_byte.saturating_mul(len))
+		} else {
+			Zero::zero()
+		};
+
+		let weight_fee = {
+			// cap the weight to the maximum defined in runtime, otherwise it will be the `Bounded`
+			// maximum of its data type, which is not desired.
+			let capped_weight = info.weight.min(<T as system::Trait>::MaximumBlockWeight::get());
+			let weight_update = <system::Module<T>>::next_weight_multiplier();
+			let adjusted_weight = weight_update.apply_to(capped_weight);
+			T::SpendingWeightToFee::convert(adjusted_weight)
+		};
+
+		len_fee.saturating_add(weight_fee).saturating_add(tip)
+	}
+
+	/// Charge the fee for a transaction to `payer`, in the exchange asset if one was chosen.
+	fn charge_fee(&self, payer: &T::AccountId, info: DispatchInfo, len: usize) -> TransactionValidity {
+		let exchange = match self.1 {
+			Some(ref exchange) => exchange,
+			None => return balances::TakeFees::<T>::from(self.0).charge_fee(payer, info, len),
+		};
+
+		let fee = Self::compute_spending_fee(len, info, self.0);
+		let spending_asset_id = <generic_asset::Module<T>>::spending_asset_id();
+		// the chosen asset can not be priced
+		let payment = T::AssetPricing::convert(&spending_asset_id, &exchange.asset_id, fee)
+			.ok_or(InvalidTransaction::Custom(180))?;
+		// the price has moved beyond what the transactor agreed to pay
+		if payment > exchange.max_payment {
+			return Err(InvalidTransaction::Custom(181).into());
+		}
+		let imbalance = match <generic_asset::Module<T>>::withdraw_into_imbalance(
+			&exchange.asset_id,
+			payer,
+			payment,
+			WithdrawReason::TransactionPayment,
+		) {
+			Ok(imbalance) => imbalance,
+			Err(_) => return InvalidTransaction::Payment.into(),
+		};
+		T::ExchangePayment::on_unbalanced(imbalance);
+
+		let mut r = ValidTransaction::default();
+		r.priority = fee.saturated_into::<TransactionPriority>();
+		Ok(r)
+	}
+}
+
+#[cfg(feature = "std")]
+impl<T: Trait> rstd::fmt::Debug for TakeFees<T> {
+	fn fmt(&self, f: &mut rstd::fmt::Formatter) -> rstd::fmt::Result {
+		write!(f, "TakeFees({:?}, {:?})", self.0, self.1)
+	}
+}
+
+impl<T: Trait> SignedExtension for TakeFees<T> {
+	type AccountId = T::AccountId;
+	type Call = T::Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+	fn additional_signed(&self) -> rstd::result::Result<(), TransactionValidityError> { Ok(()) }
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		_call: &Self::Call,
+		info: DispatchInfo,
+		len: usize,
+	) -> TransactionValidity {
+		self.charge_fee(who, info, len)
+	}
+
+	fn validate_sponsored(
+		&self,
+		_who: &Self::AccountId,
+		payer: &Self::AccountId,
+		_call: &Self::Call,
+		info: DispatchInfo,
+		len: usize,
+	) -> TransactionValidity {
+		// a delegated transaction, fees are paid by the doughnut issuer
+		self.charge_fee(payer, info, len)
+	}
+
+	fn pre_dispatch_sponsored(
+		&self,
+		who: &Self::AccountId,
+		payer: &Self::AccountId,
+		call: &Self::Call,
+		info: DispatchInfo,
+		len: usize,
+	) -> rstd::result::Result<(), ApplyError> {
+		self.validate_sponsored(who, payer, call, info, len)
+			.map(|_| ())
+			.map_err(Into::into)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use primitives::{Blake2Hasher, H256};
+	use sr_primitives::{
+		Perbill,
+		testing::Header,
+		traits::{ConvertInto, IdentityLookup},
+	};
+	use support::{impl_outer_origin, parameter_types};
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: u32 = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+		pub const ExistentialDeposit: u64 = 0;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+		pub const TransactionBaseFee: u64 = 1;
+		pub const TransactionByteFee: u64 = 1;
+		pub const SpendingBaseFee: u64 = 2;
+		pub const SpendingByteFee: u64 = 1;
+		pub const MaxSymbolLength: u32 = 8;
+		pub const MaxNameLength: u32 = 32;
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Call = ();
+		type Hash = H256;
+		type Hashing = sr_primitives::traits::BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<u64>;
+		type Header = Header;
+		type Event = ();
+		type WeightMultiplierUpdate = ();
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type BlockHashCount = BlockHashCount;
+		type Version = ();
+		type Doughnut = ();
+		type DelegatedDispatchVerifier = ();
+	}
+	impl balances::Trait for Test {
+		type Balance = u64;
+		type OnFreeBalanceZero = ();
+		type OnNewAccount = ();
+		type Event = ();
+		type TransactionPayment = ();
+		type DustRemoval = ();
+		type TransferPayment = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+		type TransactionBaseFee = TransactionBaseFee;
+		type TransactionByteFee = TransactionByteFee;
+		type WeightToFee = ConvertInto;
+	}
+	impl generic_asset::Trait for Test {
+		type Balance = u64;
+		type AssetId = u32;
+		type Event = ();
+		type DustRemoval = ();
+		type OnFreeBalanceZero = ();
+		type OnNewAccount = ();
+		type RestrictedTransferOrigin = system::EnsureSigned<u64, ()>;
+		type MaxSymbolLength = MaxSymbolLength;
+		type MaxNameLength = MaxNameLength;
+	}
+	impl Trait for Test {
+		type AssetPricing = TestPricing;
+		type SpendingBaseFee = SpendingBaseFee;
+		type SpendingByteFee = SpendingByteFee;
+		type SpendingWeightToFee = ConvertInto;
+		type ExchangePayment = ToFeeCollector;
+	}
+	type Balances = balances::Module<Test>;
+	type GenericAsset = generic_asset::Module<Test>;
+
+	const SPENDING_ASSET_ID: u32 = 10;
+	const PAYMENT_ASSET_ID: u32 = 1;
+	const UNPRICED_ASSET_ID: u32 = 2;
+
+	/// Prices `PAYMENT_ASSET_ID` at half the value of `SPENDING_ASSET_ID`
+	pub struct TestPricing;
+	impl AssetPricing<u32, u64> for TestPricing {
+		fn convert(from_asset: &u32, to_asset: &u32, amount: u64) -> Option<u64> {
+			match (*from_asset, *to_asset) {
+				(SPENDING_ASSET_ID, PAYMENT_ASSET_ID) => Some(amount * 2),
+				_ => None,
+			}
+		}
+	}
+
+	const FEE_COLLECTOR: u64 = 99;
+
+	/// Credits fees paid in exchange assets to `FEE_COLLECTOR`
+	pub struct ToFeeCollector;
+	impl OnUnbalanced<DustImbalance<Test>> for ToFeeCollector {
+		fn on_unbalanced(imbalance: DustImbalance<Test>) {
+			GenericAsset::resolve_imbalance(&FEE_COLLECTOR, imbalance);
+		}
+	}
+
+	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		balances::GenesisConfig::<Test> {
+			balances: vec![(1, 100), (2, 100)],
+			vesting: vec![],
+		}.assimilate_storage(&mut t).unwrap();
+		generic_asset::GenesisConfig::<Test> {
+			assets: vec![SPENDING_ASSET_ID, PAYMENT_ASSET_ID, UNPRICED_ASSET_ID],
+			endowed_accounts: vec![1],
+			initial_balance: 100,
+			next_asset_id: 1000,
+			staking_asset_id: 16000,
+			spending_asset_id: SPENDING_ASSET_ID,
+			asset_meta: vec![],
+			vesting: vec![],
+		}.assimilate_storage(&mut t).unwrap();
+		t.into()
+	}
+
+	fn info_from_weight(w: u32) -> DispatchInfo {
+		DispatchInfo { weight: w, ..Default::default() }
+	}
+
+	// fee = base fee (1) + byte fee (10 * 1) + weight fee (5)
+	const LEN: usize = 10;
+	const WEIGHT: u32 = 5;
+	const FEE: u64 = 16;
+	// spending fee = spending base fee (2) + spending byte fee (10 * 1) + weight fee (5)
+	const SPENDING_FEE: u64 = 17;
+
+	#[test]
+	fn fees_are_charged_in_balances_without_exchange() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			assert!(TakeFees::<Test>::new(0, None).pre_dispatch(&1, &(), info_from_weight(WEIGHT), LEN).is_ok());
+			assert_eq!(Balances::free_balance(&1), 100 - FEE);
+			assert_eq!(GenericAsset::free_balance(&PAYMENT_ASSET_ID, &1), 100);
+		});
+	}
+
+	#[test]
+	fn fees_are_paid_in_the_exchange_asset() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			let exchange = FeeExchange::new(PAYMENT_ASSET_ID, SPENDING_FEE * 2);
+			assert!(
+				TakeFees::<Test>::new(0, Some(exchange)).pre_dispatch(&1, &(), info_from_weight(WEIGHT), LEN).is_ok()
+			);
+			assert_eq!(Balances::free_balance(&1), 100);
+			assert_eq!(GenericAsset::free_balance(&PAYMENT_ASSET_ID, &1), 100 - SPENDING_FEE * 2);
+			// the fee is handed to the fee collector, rather than burned
+			assert_eq!(GenericAsset::free_balance(&PAYMENT_ASSET_ID, &FEE_COLLECTOR), SPENDING_FEE * 2);
+			assert_eq!(GenericAsset::total_issuance(&PAYMENT_ASSET_ID), 100);
+		});
+	}
+
+	#[test]
+	fn max_payment_protects_against_slippage() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			let exchange = FeeExchange::new(PAYMENT_ASSET_ID, SPENDING_FEE * 2 - 1);
+			assert_eq!(
+				<TakeFees<Test> as SignedExtension>::validate(
+					&TakeFees::new(0, Some(exchange)), &1, &(), info_from_weight(WEIGHT), LEN
+				),
+				Err(InvalidTransaction::Custom(181).into())
+			);
+			assert_eq!(Balances::free_balance(&1), 100);
+			assert_eq!(GenericAsset::free_balance(&PAYMENT_ASSET_ID, &1), 100);
+		});
+	}
+
+	#[test]
+	fn unpriced_assets_can_not_pay_fees() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			let exchange = FeeExchange::new(UNPRICED_ASSET_ID, 1_000);
+			assert_eq!(
+				<TakeFees<Test> as SignedExtension>::validate(
+					&TakeFees::new(0, Some(exchange)), &1, &(), info_from_weight(WEIGHT), LEN
+				),
+				Err(InvalidTransaction::Custom(180).into())
+			);
+			assert_eq!(GenericAsset::free_balance(&UNPRICED_ASSET_ID, &1), 100);
+		});
+	}
+
+	#[test]
+	fn exchange_fails_with_insufficient_asset_balance() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			let exchange = FeeExchange::new(PAYMENT_ASSET_ID, SPENDING_FEE * 2);
+			assert_eq!(
+				<TakeFees<Test> as SignedExtension>::validate(
+					&TakeFees::new(0, Some(exchange)), &2, &(), info_from_weight(WEIGHT), LEN
+				),
+				Err(InvalidTransaction::Payment.into())
+			);
+			assert_eq!(Balances::free_balance(&2), 100);
+		});
+	}
+
+	#[test]
+	fn sponsored_fees_are_paid_in_the_exchange_asset_by_the_sponsor() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			let exchange = FeeExchange::new(PAYMENT_ASSET_ID, SPENDING_FEE * 2);
+			assert!(
+				TakeFees::<Test>::new(0, Some(exchange))
+					.pre_dispatch_sponsored(&2, &1, &(), info_from_weight(WEIGHT), LEN)
+					.is_ok()
+			);
+			assert_eq!(GenericAsset::free_balance(&PAYMENT_ASSET_ID, &1), 100 - SPENDING_FEE * 2);
+			assert_eq!(Balances::free_balance(&2), 100);
+		});
+	}
+}
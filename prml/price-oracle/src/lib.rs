@@ -0,0 +1,319 @@
+// Copyright 2019 Plug New Zealand Limited
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Price Oracle Module
+//!
+//! The Price Oracle module stores asset prices reported on-chain by a set of trusted oracle accounts.
+//!
+//! ## Terminology
+//!
+//! Oracle: an account permitted to report prices. Oracles are added and removed by root.
+//! Price: the value of some units of an asset in a common unit of account, shared by all assets.
+//! A price is stored along with the block it was reported in.
+//! Stale price: a price which has not been updated for more than `PriceTimeout` blocks. Stale prices are not used.
+//!
+//! ## Usage
+//!
+//! The module implements `AssetPricing`, converting an amount of one asset into an amount of another
+//! with equal value. Conversions are rounded up, so they never undervalue the asset converted into,
+//! and fail if either asset has no current price.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use rstd::prelude::*;
+use sr_primitives::traits::{CheckedAdd, CheckedDiv, CheckedMul, One, Saturating, SimpleArithmetic, Zero};
+use support::{
+	decl_event, decl_module, decl_storage, dispatch::Result, ensure, Parameter, StorageMap, StorageValue,
+	additional_traits::AssetPricing,
+	traits::Get,
+};
+use system::{ensure_root, ensure_signed};
+
+pub trait Trait: system::Trait {
+	/// The asset identifier type
+	type AssetId: Parameter + Copy + Default;
+	/// The asset balance type
+	type Balance: Parameter + SimpleArithmetic + Copy + Default;
+	/// The number of blocks after which an unchanged price is considered stale
+	type PriceTimeout: Get<Self::BlockNumber>;
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+}
+
+/// The price of an asset in the common unit of account
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Price<Balance, BlockNumber> {
+	/// An amount of the asset
+	pub units: Balance,
+	/// The value of `units` of the asset
+	pub value: Balance,
+	/// The block at which the price was reported
+	pub updated_at: BlockNumber,
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// The number of blocks after which an unchanged price is considered stale.
+		const PriceTimeout: T::BlockNumber = T::PriceTimeout::get();
+
+		/// Report that `units` of `asset_id` are worth `value` in the common unit of account.
+		///
+		/// The origin must be an oracle.
+		pub fn set_price(
+			origin,
+			#[compact] asset_id: T::AssetId,
+			#[compact] units: T::Balance,
+			#[compact] value: T::Balance
+		) -> Result {
+			let oracle = ensure_signed(origin)?;
+			ensure!(Self::oracles().contains(&oracle), "origin is not an oracle");
+			ensure!(!units.is_zero() && !value.is_zero(), "price units and value must be non-zero");
+
+			let updated_at = <system::Module<T>>::block_number();
+			<Prices<T>>::insert(asset_id, Price { units, value, updated_at });
+			Self::deposit_event(RawEvent::PriceSet(asset_id, units, value));
+
+			Ok(())
+		}
+
+		/// Remove the price of `asset_id`, it can no longer be converted.
+		///
+		/// The origin must be an oracle.
+		pub fn clear_price(origin, #[compact] asset_id: T::AssetId) -> Result {
+			let oracle = ensure_signed(origin)?;
+			ensure!(Self::oracles().contains(&oracle), "origin is not an oracle");
+
+			<Prices<T>>::remove(asset_id);
+			Self::deposit_event(RawEvent::PriceCleared(asset_id));
+
+			Ok(())
+		}
+
+		/// Permit `who` to report prices. Requires root.
+		pub fn add_oracle(origin, who: T::AccountId) -> Result {
+			ensure_root(origin)?;
+			ensure!(!Self::oracles().contains(&who), "account is already an oracle");
+
+			<Oracles<T>>::mutate(|oracles| oracles.push(who.clone()));
+			Self::deposit_event(RawEvent::OracleAdded(who));
+
+			Ok(())
+		}
+
+		/// Stop `who` from reporting prices. Requires root.
+		pub fn remove_oracle(origin, who: T::AccountId) -> Result {
+			ensure_root(origin)?;
+			ensure!(Self::oracles().contains(&who), "account is not an oracle");
+
+			<Oracles<T>>::mutate(|oracles| oracles.retain(|oracle| *oracle != who));
+			Self::deposit_event(RawEvent::OracleRemoved(who));
+
+			Ok(())
+		}
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as Trait>::AssetId,
+		<T as Trait>::Balance
+	{
+		/// A price was reported (asset_id, units, value).
+		PriceSet(AssetId, Balance, Balance),
+		/// A price was removed (asset_id).
+		PriceCleared(AssetId),
+		/// An account was permitted to report prices (oracle).
+		OracleAdded(AccountId),
+		/// An account was stopped from reporting prices (oracle).
+		OracleRemoved(AccountId),
+	}
+);
+
+decl_storage! {
+	trait Store for Module<T: Trait> as PriceOracle {
+		/// The accounts permitted to report prices
+		pub Oracles get(oracles) config(): Vec<T::AccountId>;
+		/// The last reported price of an asset. AssetId => Price
+		pub Prices get(price): map T::AssetId => Option<Price<T::Balance, T::BlockNumber>>;
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Return the price of `asset_id`, if it has one which is not stale
+	pub fn current_price(asset_id: &T::AssetId) -> Option<Price<T::Balance, T::BlockNumber>> {
+		let price = Self::price(asset_id)?;
+		let now = <system::Module<T>>::block_number();
+		if now.saturating_sub(price.updated_at) > T::PriceTimeout::get() {
+			return None;
+		}
+		Some(price)
+	}
+}
+
+/// Return `a * b / c` rounded up, or `None` on overflow or division by zero
+fn mul_div_ceil<Balance: SimpleArithmetic + Copy>(a: Balance, b: Balance, c: Balance) -> Option<Balance> {
+	let product = a.checked_mul(&b)?;
+	let quotient = product.checked_div(&c)?;
+	if quotient * c < product {
+		quotient.checked_add(&One::one())
+	} else {
+		Some(quotient)
+	}
+}
+
+impl<T: Trait> AssetPricing<T::AssetId, T::Balance> for Module<T> {
+	fn convert(from_asset: &T::AssetId, to_asset: &T::AssetId, amount: T::Balance) -> Option<T::Balance> {
+		if from_asset == to_asset {
+			return Some(amount);
+		}
+		let from_price = Self::current_price(from_asset)?;
+		let to_price = Self::current_price(to_asset)?;
+
+		let value = mul_div_ceil(amount, from_price.value, from_price.units)?;
+		mul_div_ceil(value, to_price.units, to_price.value)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use primitives::{Blake2Hasher, H256};
+	use sr_primitives::{
+		Perbill,
+		testing::Header,
+		traits::{BlakeTwo256, IdentityLookup},
+	};
+	use support::{assert_noop, assert_ok, impl_outer_origin, parameter_types};
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: u32 = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+		pub const PriceTimeout: u64 = 10;
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Call = ();
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<u64>;
+		type Header = Header;
+		type Event = ();
+		type WeightMultiplierUpdate = ();
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type BlockHashCount = BlockHashCount;
+		type Version = ();
+		type Doughnut = ();
+		type DelegatedDispatchVerifier = ();
+	}
+	impl Trait for Test {
+		type AssetId = u32;
+		type Balance = u64;
+		type PriceTimeout = PriceTimeout;
+		type Event = ();
+	}
+	type PriceOracle = Module<Test>;
+	type System = system::Module<Test>;
+
+	const ORACLE: u64 = 1;
+
+	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		GenesisConfig::<Test> {
+			oracles: vec![ORACLE],
+		}.assimilate_storage(&mut t).unwrap();
+		t.into()
+	}
+
+	#[test]
+	fn only_oracles_can_set_prices() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			assert_noop!(PriceOracle::set_price(Origin::signed(2), 0, 1, 1), "origin is not an oracle");
+			assert_noop!(PriceOracle::set_price(Origin::signed(ORACLE), 0, 0, 1), "price units and value must be non-zero");
+
+			assert_ok!(PriceOracle::set_price(Origin::signed(ORACLE), 0, 1, 2));
+			assert_eq!(PriceOracle::price(0), Some(Price { units: 1, value: 2, updated_at: 0 }));
+
+			assert_ok!(PriceOracle::clear_price(Origin::signed(ORACLE), 0));
+			assert_eq!(PriceOracle::price(0), None);
+		});
+	}
+
+	#[test]
+	fn root_manages_oracles() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			assert_noop!(PriceOracle::add_oracle(Origin::signed(ORACLE), 2), "RequireRootOrigin");
+			assert_ok!(PriceOracle::add_oracle(Origin::ROOT, 2));
+			assert_eq!(PriceOracle::oracles(), vec![ORACLE, 2]);
+			assert_ok!(PriceOracle::set_price(Origin::signed(2), 0, 1, 1));
+
+			assert_ok!(PriceOracle::remove_oracle(Origin::ROOT, 2));
+			assert_eq!(PriceOracle::oracles(), vec![ORACLE]);
+			assert_noop!(PriceOracle::set_price(Origin::signed(2), 0, 1, 1), "origin is not an oracle");
+		});
+	}
+
+	#[test]
+	fn convert_uses_relative_prices() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			// 1 unit of asset 0 is worth 2, 10 units of asset 1 are worth 1
+			assert_ok!(PriceOracle::set_price(Origin::signed(ORACLE), 0, 1, 2));
+			assert_ok!(PriceOracle::set_price(Origin::signed(ORACLE), 1, 10, 1));
+
+			assert_eq!(<PriceOracle as AssetPricing<_, _>>::convert(&0, &1, 5), Some(100));
+			assert_eq!(<PriceOracle as AssetPricing<_, _>>::convert(&1, &0, 100), Some(5));
+			// rounded up
+			assert_eq!(<PriceOracle as AssetPricing<_, _>>::convert(&1, &0, 3), Some(1));
+			// unpriced
+			assert_eq!(<PriceOracle as AssetPricing<_, _>>::convert(&0, &2, 5), None);
+			// the same asset needs no price
+			assert_eq!(<PriceOracle as AssetPricing<_, _>>::convert(&2, &2, 5), Some(5));
+		});
+	}
+
+	#[test]
+	fn stale_prices_are_not_used() {
+		runtime_io::with_externalities(&mut new_test_ext(), || {
+			assert_ok!(PriceOracle::set_price(Origin::signed(ORACLE), 0, 1, 2));
+			assert_ok!(PriceOracle::set_price(Origin::signed(ORACLE), 1, 1, 1));
+
+			System::set_block_number(10);
+			assert_eq!(<PriceOracle as AssetPricing<_, _>>::convert(&0, &1, 5), Some(10));
+
+			System::set_block_number(11);
+			assert_eq!(<PriceOracle as AssetPricing<_, _>>::convert(&0, &1, 5), None);
+
+			// a fresh report makes the price current again
+			assert_ok!(PriceOracle::set_price(Origin::signed(ORACLE), 1, 1, 1));
+			assert_ok!(PriceOracle::set_price(Origin::signed(ORACLE), 0, 1, 2));
+			assert_eq!(<PriceOracle as AssetPricing<_, _>>::convert(&0, &1, 5), Some(10));
+		});
+	}
+}
@@ -171,6 +171,7 @@ use support::{
 	dispatch::Result,
 };
 use sr_primitives::{
+	ApplyError,
 	transaction_validity::{
 		TransactionPriority, ValidTransaction, InvalidTransaction, TransactionValidityError,
 		TransactionValidity,
@@ -1209,7 +1210,7 @@ impl<T: Trait<I>, I: Instance> TakeFees<T, I> {
 	///      and the time it consumes.
 	///   - (optional) _tip_: if included in the transaction, it will be added on top. Only signed
 	///      transactions can have a tip.
-	fn compute_fee(len: usize, info: DispatchInfo, tip: T::Balance) -> T::Balance {
+	pub fn compute_fee(len: usize, info: DispatchInfo, tip: T::Balance) -> T::Balance {
 		let len_fee = if info.pay_length_fee() {
 			let len = T::Balance::from(len as u32);
 			let base = T::TransactionBaseFee::get();
@@ -1230,6 +1231,28 @@ impl<T: Trait<I>, I: Instance> TakeFees<T, I> {
 
 		len_fee.saturating_add(weight_fee).saturating_add(tip)
 	}
+
+	/// Charge the fee for a transaction to `payer`.
+	pub fn charge_fee(&self, payer: &T::AccountId, info: DispatchInfo, len: usize) -> TransactionValidity {
+		// pay any fees.
+		let fee = Self::compute_fee(len, info, self.0);
+		let imbalance = match <Module<T, I>>::withdraw(
+			payer,
+			fee,
+			WithdrawReason::TransactionPayment,
+			ExistenceRequirement::KeepAlive,
+		) {
+			Ok(imbalance) => imbalance,
+			Err(_) => return InvalidTransaction::Payment.into(),
+		};
+		T::TransactionPayment::on_unbalanced(imbalance);
+
+		let mut r = ValidTransaction::default();
+		// NOTE: we probably want to maximize the _fee (of any type) per weight unit_ here, which
+		// will be a bit more than setting the priority to tip. For now, this is enough.
+		r.priority = fee.saturated_into::<TransactionPriority>();
+		Ok(r)
+	}
 }
 
 #[cfg(feature = "std")]
@@ -1253,24 +1276,32 @@ impl<T: Trait<I>, I: Instance + Clone + Eq> SignedExtension for TakeFees<T, I> {
 		info: DispatchInfo,
 		len: usize,
 	) -> TransactionValidity {
-		// pay any fees.
-		let fee = Self::compute_fee(len, info, self.0);
-		let imbalance = match <Module<T, I>>::withdraw(
-			who,
-			fee,
-			WithdrawReason::TransactionPayment,
-			ExistenceRequirement::KeepAlive,
-		) {
-			Ok(imbalance) => imbalance,
-			Err(_) => return InvalidTransaction::Payment.into(),
-		};
-		T::TransactionPayment::on_unbalanced(imbalance);
+		self.charge_fee(who, info, len)
+	}
 
-		let mut r = ValidTransaction::default();
-		// NOTE: we probably want to maximize the _fee (of any type) per weight unit_ here, which
-		// will be a bit more than setting the priority to tip. For now, this is enough.
-		r.priority = fee.saturated_into::<TransactionPriority>();
-		Ok(r)
+	fn validate_sponsored(
+		&self,
+		_who: &Self::AccountId,
+		payer: &Self::AccountId,
+		_call: &Self::Call,
+		info: DispatchInfo,
+		len: usize,
+	) -> TransactionValidity {
+		// a delegated transaction, fees are paid by the doughnut issuer
+		self.charge_fee(payer, info, len)
+	}
+
+	fn pre_dispatch_sponsored(
+		&self,
+		who: &Self::AccountId,
+		payer: &Self::AccountId,
+		call: &Self::Call,
+		info: DispatchInfo,
+		len: usize,
+	) -> rstd::result::Result<(), ApplyError> {
+		self.validate_sponsored(who, payer, call, info, len)
+			.map(|_| ())
+			.map_err(Into::into)
 	}
 }
 
@@ -786,6 +786,24 @@ fn signed_extension_take_fees_work() {
 	);
 }
 
+#[test]
+fn signed_extension_take_fees_charges_sponsor() {
+	with_externalities(
+		&mut ExtBuilder::default()
+			.existential_deposit(10)
+			.transaction_fees(10, 1, 5)
+			.monied(true)
+			.build(),
+		|| {
+			let len = 10;
+			// account 2 sponsors a transaction signed by account 1
+			assert!(TakeFees::<Runtime>::from(0).pre_dispatch_sponsored(&1, &2, CALL, info_from_weight(5), len).is_ok());
+			assert_eq!(Balances::free_balance(&1), 100);
+			assert_eq!(Balances::free_balance(&2), 200 - 20 - 25);
+		}
+	);
+}
+
 #[test]
 fn signed_extension_take_fees_is_bounded() {
 	with_externalities(
@@ -17,16 +17,17 @@
 //! Auxiliaries to help with managing partial changes to accounts state.
 
 use super::{
-	AliveContractInfo, BalanceOf, CodeHash, ContractInfo, ContractInfoOf, Trait, TrieId,
-	TrieIdGenerator,
+	AliveContractInfo, AssetBalanceOf, AssetIdOf, BalanceOf, CodeHash, ContractInfo, ContractInfoOf,
+	Trait, TrieId, TrieIdGenerator,
 };
 use crate::exec::StorageKey;
 use rstd::cell::RefCell;
 use rstd::collections::btree_map::{BTreeMap, Entry};
+use rstd::mem;
 use rstd::prelude::*;
 use runtime_io::blake2_256;
-use sr_primitives::traits::{Bounded, Zero};
-use support::traits::{Currency, Get, Imbalance, SignedImbalance, UpdateBalanceOutcome};
+use sr_primitives::traits::{Bounded, Saturating, Zero};
+use support::traits::{Currency, Get, Imbalance, MultiCurrency, SignedImbalance, UpdateBalanceOutcome};
 use support::{storage::child, StorageMap};
 use system;
 
@@ -44,6 +45,9 @@ pub struct ChangeEntry<T: Trait> {
 	/// the rent allowance is unmodified. If None and `reset` is true, the contract is deleted.
 	rent_allowance: Option<BalanceOf<T>>,
 	storage: BTreeMap<StorageKey, Option<Vec<u8>>>,
+	/// The asset balances modified to the given values. Unlike the other fields, these are kept
+	/// when `reset` is true, as the account holds its assets independently of any contract.
+	asset_balances: Vec<(AssetIdOf<T>, AssetBalanceOf<T>)>,
 	/// If true, indicates that the existing contract and all its storage entries should be removed
 	/// and replaced with the fields on this change entry. Otherwise, the fields on this change
 	/// entry are updates merged into the existing contract info and storage.
@@ -85,6 +89,20 @@ impl<T: Trait> ChangeEntry<T> {
 			value
 		}
 	}
+
+	fn asset_balance(&self, asset_id: &AssetIdOf<T>) -> Option<AssetBalanceOf<T>> {
+		self.asset_balances
+			.iter()
+			.find(|(id, _)| id == asset_id)
+			.map(|(_, balance)| *balance)
+	}
+
+	fn set_asset_balance(&mut self, asset_id: AssetIdOf<T>, balance: AssetBalanceOf<T>) {
+		match self.asset_balances.iter_mut().find(|(id, _)| *id == asset_id) {
+			Some(entry) => entry.1 = balance,
+			None => self.asset_balances.push((asset_id, balance)),
+		}
+	}
 }
 
 // Cannot derive(Default) since it erroneously bounds T by Default.
@@ -95,6 +113,7 @@ impl<T: Trait> Default for ChangeEntry<T> {
 			balance: Default::default(),
 			code_hash: Default::default(),
 			storage: Default::default(),
+			asset_balances: Default::default(),
 			reset: false,
 		}
 	}
@@ -116,6 +135,7 @@ pub trait AccountDb<T: Trait> {
 	/// Returns false iff account has no alive contract nor tombstone.
 	fn contract_exists(&self, account: &T::AccountId) -> bool;
 	fn get_balance(&self, account: &T::AccountId) -> BalanceOf<T>;
+	fn get_asset_balance(&self, account: &T::AccountId, asset_id: &AssetIdOf<T>) -> AssetBalanceOf<T>;
 
 	fn commit(&mut self, change_set: ChangeSet<T>);
 }
@@ -142,9 +162,20 @@ impl<T: Trait> AccountDb<T> for DirectAccountDb {
 	fn get_balance(&self, account: &T::AccountId) -> BalanceOf<T> {
 		T::Currency::free_balance(account)
 	}
+	fn get_asset_balance(&self, account: &T::AccountId, asset_id: &AssetIdOf<T>) -> AssetBalanceOf<T> {
+		T::MultiCurrency::free_balance(asset_id, account)
+	}
 	fn commit(&mut self, s: ChangeSet<T>) {
+		// Asset balances are forced, so check they don't create any of the asset before writing.
+		ensure_no_asset_created(&s);
+
 		let mut total_imbalance = SignedImbalance::zero();
 		for (address, changed) in s.into_iter() {
+			for (asset_id, balance) in changed.asset_balances.iter() {
+				// This enforces the minimum balance of the asset, reaping the account below it.
+				T::MultiCurrency::make_free_balance_be(asset_id, &address, *balance);
+			}
+
 			if let Some(balance) = changed.balance() {
 				let (imbalance, outcome) = T::Currency::make_free_balance_be(&address, balance);
 				total_imbalance = total_imbalance.merge(imbalance);
@@ -248,6 +279,38 @@ impl<T: Trait> AccountDb<T> for DirectAccountDb {
 		}
 	}
 }
+
+/// Panic if committing the asset balances of `s` would create any of an asset, i.e. the balances
+/// of an asset would increase by more than they decrease in total.
+///
+/// As with the positive imbalance of `Currency`, this is indicative of a buggy contracts system.
+fn ensure_no_asset_created<T: Trait>(s: &ChangeSet<T>) {
+	// (asset_id, total increase, total decrease) of each asset's balances.
+	let mut asset_imbalances: Vec<(AssetIdOf<T>, AssetBalanceOf<T>, AssetBalanceOf<T>)> = Vec::new();
+	for (address, changed) in s.iter() {
+		for (asset_id, balance) in changed.asset_balances.iter() {
+			let original = T::MultiCurrency::free_balance(asset_id, address);
+			let index = match asset_imbalances.iter().position(|(id, _, _)| id == asset_id) {
+				Some(index) => index,
+				None => {
+					asset_imbalances.push((*asset_id, Zero::zero(), Zero::zero()));
+					asset_imbalances.len() - 1
+				}
+			};
+			let imbalance = &mut asset_imbalances[index];
+			if original <= *balance {
+				imbalance.1 = imbalance.1.saturating_add(*balance - original);
+			} else {
+				imbalance.2 = imbalance.2.saturating_add(original - *balance);
+			}
+		}
+	}
+
+	if asset_imbalances.iter().any(|(_, increase, decrease)| increase > decrease) {
+		panic!("contract subsystem resulting in positive asset imbalance!");
+	}
+}
+
 pub struct OverlayAccountDb<'a, T: Trait + 'a> {
 	local: RefCell<ChangeSet<T>>,
 	underlying: &'a dyn AccountDb<T>,
@@ -296,12 +359,25 @@ impl<'a, T: Trait> OverlayAccountDb<'a, T> {
 		Ok(())
 	}
 
+	/// Replace the code of an existing contract, keeping its storage, balance and rent allowance.
+	pub fn set_code_hash(&mut self, account: &T::AccountId, code_hash: CodeHash<T>) {
+		self.local
+			.borrow_mut()
+			.entry(account.clone())
+			.or_insert(Default::default())
+			.code_hash = Some(code_hash);
+	}
+
 	/// Mark a contract as deleted.
 	pub fn destroy_contract(&mut self, account: &T::AccountId) {
 		let mut local = self.local.borrow_mut();
+		let asset_balances = local.remove(account)
+			.map(|changes| changes.asset_balances)
+			.unwrap_or_default();
 		local.insert(
 			account.clone(),
 			ChangeEntry {
+				asset_balances,
 				reset: true,
 				..Default::default()
 			}
@@ -323,6 +399,18 @@ impl<'a, T: Trait> OverlayAccountDb<'a, T> {
 			.or_insert(Default::default())
 			.balance = Some(balance);
 	}
+	pub fn set_asset_balance(
+		&mut self,
+		account: &T::AccountId,
+		asset_id: AssetIdOf<T>,
+		balance: AssetBalanceOf<T>,
+	) {
+		self.local
+			.borrow_mut()
+			.entry(account.clone())
+			.or_insert(Default::default())
+			.set_asset_balance(asset_id, balance);
+	}
 }
 
 impl<'a, T: Trait> AccountDb<T> for OverlayAccountDb<'a, T> {
@@ -366,6 +454,13 @@ impl<'a, T: Trait> AccountDb<T> for OverlayAccountDb<'a, T> {
 			.and_then(|changes| changes.balance())
 			.unwrap_or_else(|| self.underlying.get_balance(account))
 	}
+	fn get_asset_balance(&self, account: &T::AccountId, asset_id: &AssetIdOf<T>) -> AssetBalanceOf<T> {
+		self.local
+			.borrow()
+			.get(account)
+			.and_then(|changes| changes.asset_balance(asset_id))
+			.unwrap_or_else(|| self.underlying.get_asset_balance(account, asset_id))
+	}
 	fn commit(&mut self, s: ChangeSet<T>) {
 		let mut local = self.local.borrow_mut();
 
@@ -373,13 +468,23 @@ impl<'a, T: Trait> AccountDb<T> for OverlayAccountDb<'a, T> {
 			match local.entry(address) {
 				Entry::Occupied(e) => {
 					let mut value = e.into_mut();
+					let asset_balances = mem::replace(&mut value.asset_balances, Vec::new());
 					if changed.reset {
 						*value = changed;
+						for (asset_id, balance) in asset_balances {
+							if value.asset_balance(&asset_id).is_none() {
+								value.set_asset_balance(asset_id, balance);
+							}
+						}
 					} else {
 						value.balance = changed.balance.or(value.balance);
 						value.code_hash = changed.code_hash.or(value.code_hash);
 						value.rent_allowance = changed.rent_allowance.or(value.rent_allowance);
 						value.storage.extend(changed.storage.into_iter());
+						value.asset_balances = asset_balances;
+						for (asset_id, balance) in changed.asset_balances {
+							value.set_asset_balance(asset_id, balance);
+						}
 					}
 				}
 				Entry::Vacant(e) => {
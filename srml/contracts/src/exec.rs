@@ -15,14 +15,15 @@
 // along with Substrate. If not, see <http://www.gnu.org/licenses/>.
 
 use super::{CodeHash, Config, ContractAddressFor, Event, RawEvent, Trait,
-	TrieId, BalanceOf, ContractInfo};
+	TrieId, BalanceOf, GasBalanceOf, ContractInfo, AssetIdOf, AssetBalanceOf, DoughnutOf};
 use crate::account_db::{AccountDb, DirectAccountDb, OverlayAccountDb};
 use crate::gas::{Gas, GasMeter, Token, approx_gas_for_balance};
 use crate::rent;
 
 use rstd::prelude::*;
+use rstd::cell::RefCell;
 use sr_primitives::traits::{Bounded, CheckedAdd, CheckedSub, Zero};
-use support::traits::{WithdrawReason, Currency, Time};
+use support::traits::{WithdrawReason, Currency, MultiCurrency, Time};
 
 pub type AccountIdOf<T> = <T as system::Trait>::AccountId;
 pub type CallOf<T> = <T as Trait>::Call;
@@ -56,6 +57,40 @@ impl ExecReturnValue {
 	}
 }
 
+/// The trace of a call or instantiation, collected when tracing is enabled on the top level
+/// execution context.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct CallTrace<AccountId> {
+	/// The account called or instantiated.
+	pub dest: AccountId,
+	/// The gas used by the call, including the gas used by its nested calls.
+	pub gas_used: Gas,
+	/// Whether the call ran to completion with a successful status code.
+	///
+	/// The changes made by a successful call are still reverted if one of its parents fails.
+	pub success: bool,
+	/// The storage keys read by the contract, in the order they were first read.
+	pub storage_reads: Vec<StorageKey>,
+	/// The storage keys written or removed by the contract, in the order they were first written.
+	pub storage_writes: Vec<StorageKey>,
+	/// The calls and instantiations made by the contract, in order.
+	pub nested: Vec<CallTrace<AccountId>>,
+}
+
+impl<AccountId> CallTrace<AccountId> {
+	fn new(dest: AccountId) -> Self {
+		CallTrace {
+			dest,
+			gas_used: 0,
+			success: false,
+			storage_reads: Vec::new(),
+			storage_writes: Vec::new(),
+			nested: Vec::new(),
+		}
+	}
+}
+
 /// An error indicating some failure to execute a contract call or instantiation. This can include
 /// VM-specific errors during execution (eg. division by 0, OOB access, failure to satisfy some
 /// precondition of a system call, etc.) or errors with the orchestration (eg. out-of-gas errors, a
@@ -137,9 +172,22 @@ pub trait Ext {
 		delta: Vec<StorageKey>,
 	);
 
+	/// Replace the code of the current contract with the code of the given hash.
+	///
+	/// The storage, balance and rent allowance of the contract are kept. The code that is running
+	/// finishes executing; the new code is used from the next call into the contract on.
+	///
+	/// Returns the length of the new code in bytes, so that loading it may be charged for.
+	fn set_code_hash(&mut self, code_hash: CodeHash<Self::T>) -> Result<u32, &'static str>;
+
 	/// Returns a reference to the account id of the caller.
 	fn caller(&self) -> &AccountIdOf<Self::T>;
 
+	/// Returns a reference to the doughnut the caller is acting under, if any.
+	///
+	/// Only the contract called directly by a delegated origin acts under its doughnut.
+	fn doughnut(&self) -> Option<&DoughnutOf<Self::T>>;
+
 	/// Returns a reference to the account id of the current contract.
 	fn address(&self) -> &AccountIdOf<Self::T>;
 
@@ -151,6 +199,17 @@ pub trait Ext {
 	/// Returns the value transferred along with this call or as endowment.
 	fn value_transferred(&self) -> BalanceOf<Self::T>;
 
+	/// Returns the balance of the current contract in the given asset.
+	fn asset_balance(&self, asset_id: &AssetIdOf<Self::T>) -> AssetBalanceOf<Self::T>;
+
+	/// Transfer some amount of the given asset from the current contract to the specified account.
+	fn asset_transfer(
+		&mut self,
+		asset_id: &AssetIdOf<Self::T>,
+		to: &AccountIdOf<Self::T>,
+		value: AssetBalanceOf<Self::T>,
+	) -> Result<(), &'static str>;
+
 	/// Returns a reference to the timestamp of the current block
 	fn now(&self) -> &MomentOf<Self::T>;
 
@@ -189,6 +248,8 @@ pub trait Loader<T: Trait> {
 	/// Load the main portion of the code specified by the `code_hash`. This executable
 	/// is called for each call to a contract.
 	fn load_main(&self, code_hash: &CodeHash<T>) -> Result<Self::Executable, &'static str>;
+	/// The length in bytes of the code of an executable loaded by this loader.
+	fn code_len(&self, executable: &Self::Executable) -> u32;
 }
 
 /// A trait that represent a virtual machine.
@@ -272,6 +333,12 @@ pub struct ExecutionContext<'a, T: Trait + 'a, V, L> {
 	pub loader: &'a L,
 	pub timestamp: MomentOf<T>,
 	pub block_number: T::BlockNumber,
+	/// The doughnut the origin is acting under. Only ever set on the top level context.
+	pub doughnut: Option<DoughnutOf<T>>,
+	/// The trace of the call made in this context, if tracing is enabled.
+	///
+	/// The top level context has no call of its own, its trace only collects the nested calls.
+	pub trace: Option<RefCell<CallTrace<T::AccountId>>>,
 }
 
 impl<'a, T, E, V, L> ExecutionContext<'a, T, V, L>
@@ -297,12 +364,33 @@ where
 			loader: &loader,
 			timestamp: T::Time::now(),
 			block_number: <system::Module<T>>::block_number(),
+			doughnut: None,
+			trace: None,
 		}
 	}
 
+	/// Set the doughnut the origin of the top level context is acting under.
+	pub fn with_doughnut(self, doughnut: Option<DoughnutOf<T>>) -> Self {
+		ExecutionContext { doughnut, ..self }
+	}
+
+	/// Enable the collection of a `CallTrace` for every call and instantiation made in this context.
+	pub fn with_tracing(self) -> Self {
+		let trace = Some(RefCell::new(CallTrace::new(self.self_account.clone())));
+		ExecutionContext { trace, ..self }
+	}
+
+	/// Take the traces of the calls and instantiations made in this context, if tracing is enabled.
+	pub fn take_traces(&mut self) -> Vec<CallTrace<T::AccountId>> {
+		self.trace.as_mut()
+			.map(|trace| rstd::mem::replace(&mut trace.get_mut().nested, Vec::new()))
+			.unwrap_or_default()
+	}
+
 	fn nested<'b, 'c: 'b>(&'c self, dest: T::AccountId, trie_id: Option<TrieId>)
 		-> ExecutionContext<'b, T, V, L>
 	{
+		let trace = self.trace.as_ref().map(|_| RefCell::new(CallTrace::new(dest.clone())));
 		ExecutionContext {
 			parent: Some(self),
 			self_trie_id: trie_id,
@@ -315,6 +403,8 @@ where
 			loader: self.loader,
 			timestamp: self.timestamp.clone(),
 			block_number: self.block_number.clone(),
+			doughnut: None,
+			trace,
 		}
 	}
 
@@ -326,6 +416,8 @@ where
 		gas_meter: &mut GasMeter<T>,
 		input_data: Vec<u8>,
 	) -> ExecResult {
+		let gas_left_before = gas_meter.gas_left();
+
 		if self.depth == self.config.max_depth as usize {
 			return Err(ExecError {
 				reason: "reached maximum depth, cannot make a call",
@@ -359,7 +451,7 @@ where
 		let caller = self.self_account.clone();
 		let dest_trie_id = contract_info.and_then(|i| i.as_alive().map(|i| i.trie_id.clone()));
 
-		self.with_nested_context(dest.clone(), dest_trie_id, |nested| {
+		let result = self.with_nested_context(dest.clone(), dest_trie_id, |nested| {
 			if value > BalanceOf::<T>::zero() {
 				try_or_exec_error!(
 					transfer(
@@ -408,7 +500,10 @@ where
 				}
 				None => Ok(ExecReturnValue { status: STATUS_SUCCESS, data: Vec::new() }),
 			}
-		})
+		});
+
+		self.trace_gas_used(gas_left_before - gas_meter.gas_left());
+		result
 	}
 
 	pub fn instantiate(
@@ -418,6 +513,8 @@ where
 		code_hash: &CodeHash<T>,
 		input_data: Vec<u8>,
 	) -> Result<(T::AccountId, ExecReturnValue), ExecError> {
+		let gas_left_before = gas_meter.gas_left();
+
 		if self.depth == self.config.max_depth as usize {
 			return Err(ExecError {
 				reason: "reached maximum depth, cannot instantiate",
@@ -445,7 +542,7 @@ where
 		// TrieId has not been generated yet and storage is empty since contract is new.
 		let dest_trie_id = None;
 
-		let output = self.with_nested_context(dest.clone(), dest_trie_id, |nested| {
+		let result = self.with_nested_context(dest.clone(), dest_trie_id, |nested| {
 			try_or_exec_error!(
 				nested.overlay.instantiate_contract(&dest, code_hash.clone()),
 				input_data
@@ -492,9 +589,10 @@ where
 			});
 
 			Ok(output)
-		})?;
+		});
 
-		Ok((dest, output))
+		self.trace_gas_used(gas_left_before - gas_meter.gas_left());
+		Ok((dest, result?))
 	}
 
 	fn new_call_context<'b>(&'b mut self, caller: T::AccountId, value: BalanceOf<T>)
@@ -515,12 +613,19 @@ where
 		-> ExecResult
 		where F: FnOnce(&mut ExecutionContext<T, V, L>) -> ExecResult
 	{
-		let (output, change_set, deferred) = {
+		let (output, change_set, deferred, trace) = {
 			let mut nested = self.nested(dest, trie_id);
-			let output = func(&mut nested)?;
-			(output, nested.overlay.into_change_set(), nested.deferred)
+			let output = func(&mut nested);
+			(output, nested.overlay.into_change_set(), nested.deferred, nested.trace)
 		};
 
+		if let (Some(parent_trace), Some(trace)) = (self.trace.as_mut(), trace) {
+			let mut trace = trace.into_inner();
+			trace.success = output.as_ref().map(ExecReturnValue::is_success).unwrap_or(false);
+			parent_trace.get_mut().nested.push(trace);
+		}
+
+		let output = output?;
 		if output.is_success() {
 			self.overlay.commit(change_set);
 			self.deferred.extend(deferred);
@@ -529,6 +634,13 @@ where
 		Ok(output)
 	}
 
+	/// Record the gas used by the latest call or instantiation made in this context on its trace.
+	fn trace_gas_used(&mut self, gas_used: Gas) {
+		if let Some(trace) = self.trace.as_mut().and_then(|trace| trace.get_mut().nested.last_mut()) {
+			trace.gas_used = gas_used;
+		}
+	}
+
 	/// Returns whether a contract, identified by address, is currently live in the execution
 	/// stack, meaning it is in the middle of an execution.
 	fn is_live(&self, account: &T::AccountId) -> bool {
@@ -552,7 +664,7 @@ pub struct TransferFeeToken<Balance> {
 	gas_price: Balance,
 }
 
-impl<T: Trait> Token<T> for TransferFeeToken<BalanceOf<T>> {
+impl<T: Trait> Token<T> for TransferFeeToken<GasBalanceOf<T>> {
 	type Metadata = Config<T>;
 
 	#[inline]
@@ -581,7 +693,7 @@ enum TransferCause {
 /// (transferring endowment) or because of a transfer via `call`. This
 /// is specified using the `cause` parameter.
 ///
-/// NOTE: that the fee is denominated in `BalanceOf<T>` units, but
+/// NOTE: that the fee is denominated in `GasBalanceOf<T>` units, but
 /// charged in `Gas` from the provided `gas_meter`. This means
 /// that the actual amount charged might differ.
 ///
@@ -660,6 +772,60 @@ fn transfer<'a, T: Trait, V: Vm<T>, L: Loader<T>>(
 	Ok(())
 }
 
+/// Transfer some amount of an asset from `transactor` to `dest`.
+///
+/// All balance changes are performed in the `overlay`. Unlike `transfer`, no fee is charged here;
+/// the cost of an asset transfer is part of the `Schedule`.
+///
+/// The transfer is subject to the asset's transfer restrictions, e.g. allow-lists.
+///
+/// NOTE: as with `transfer`, we allow the balance of `transactor` to go below the minimum balance
+/// of the asset, in which case the remainder is lost once the changes are committed.
+fn asset_transfer<'a, T: Trait, V: Vm<T>, L: Loader<T>>(
+	asset_id: &AssetIdOf<T>,
+	transactor: &T::AccountId,
+	dest: &T::AccountId,
+	value: AssetBalanceOf<T>,
+	ctx: &mut ExecutionContext<'a, T, V, L>,
+) -> Result<(), &'static str> {
+	// The balances are committed without further checks, so the asset's transfer restrictions
+	// must be checked here.
+	T::MultiCurrency::ensure_can_transfer(asset_id, transactor, dest)?;
+
+	let to_balance = ctx.overlay.get_asset_balance(dest, asset_id);
+	let from_balance = ctx.overlay.get_asset_balance(transactor, asset_id);
+	let new_from_balance = match from_balance.checked_sub(&value) {
+		Some(b) => b,
+		None => return Err("balance too low to send value"),
+	};
+	if to_balance.is_zero() && value < T::MultiCurrency::minimum_balance(asset_id) {
+		return Err("value too low to create account");
+	}
+	T::MultiCurrency::ensure_can_withdraw(
+		asset_id,
+		transactor,
+		value,
+		WithdrawReason::Transfer,
+		new_from_balance,
+	)?;
+
+	let new_to_balance = match to_balance.checked_add(&value) {
+		Some(b) => b,
+		None => return Err("destination balance too high to receive value"),
+	};
+
+	if transactor != dest {
+		ctx.overlay.set_asset_balance(transactor, *asset_id, new_from_balance);
+		ctx.overlay.set_asset_balance(dest, *asset_id, new_to_balance);
+		ctx.deferred.push(DeferredAction::DepositEvent {
+			event: RawEvent::AssetTransfer(*asset_id, transactor.clone(), dest.clone(), value),
+			topics: Vec::new(),
+		});
+	}
+
+	Ok(())
+}
+
 struct CallContext<'a, 'b: 'a, T: Trait + 'b, V: Vm<T> + 'b, L: Loader<T>> {
 	ctx: &'a mut ExecutionContext<'b, T, V, L>,
 	caller: T::AccountId,
@@ -677,6 +843,13 @@ where
 	type T = T;
 
 	fn get_storage(&self, key: &StorageKey) -> Option<Vec<u8>> {
+		if let Some(trace) = self.ctx.trace.as_ref() {
+			let mut trace = trace.borrow_mut();
+			if !trace.storage_reads.contains(key) {
+				trace.storage_reads.push(*key);
+			}
+		}
+
 		self.ctx.overlay.get_storage(&self.ctx.self_account, self.ctx.self_trie_id.as_ref(), key)
 	}
 
@@ -687,6 +860,13 @@ where
 			}
 		}
 
+		if let Some(trace) = self.ctx.trace.as_mut() {
+			let writes = &mut trace.get_mut().storage_writes;
+			if !writes.contains(&key) {
+				writes.push(key);
+			}
+		}
+
 		self.ctx
 			.overlay
 			.set_storage(&self.ctx.self_account, key, value);
@@ -736,6 +916,20 @@ where
 		});
 	}
 
+	fn set_code_hash(&mut self, code_hash: CodeHash<T>) -> Result<u32, &'static str> {
+		// Make sure the code exists and can be called into.
+		let executable = self.ctx.loader.load_main(&code_hash)?;
+		let code_len = self.ctx.loader.code_len(&executable);
+
+		let self_account = self.ctx.self_account.clone();
+		self.ctx.overlay.set_code_hash(&self_account, code_hash);
+		self.ctx.deferred.push(DeferredAction::DepositEvent {
+			event: RawEvent::CodeHashUpdated(self_account, code_hash),
+			topics: Vec::new(),
+		});
+		Ok(code_len)
+	}
+
 	fn address(&self) -> &T::AccountId {
 		&self.ctx.self_account
 	}
@@ -744,6 +938,14 @@ where
 		&self.caller
 	}
 
+	fn doughnut(&self) -> Option<&DoughnutOf<T>> {
+		// The doughnut is only visible to the contract called directly by the top level origin.
+		match self.ctx.parent {
+			Some(parent) if parent.parent.is_none() => parent.doughnut.as_ref(),
+			_ => None,
+		}
+	}
+
 	fn balance(&self) -> BalanceOf<T> {
 		self.ctx.overlay.get_balance(&self.ctx.self_account)
 	}
@@ -752,6 +954,20 @@ where
 		self.value_transferred
 	}
 
+	fn asset_balance(&self, asset_id: &AssetIdOf<T>) -> AssetBalanceOf<T> {
+		self.ctx.overlay.get_asset_balance(&self.ctx.self_account, asset_id)
+	}
+
+	fn asset_transfer(
+		&mut self,
+		asset_id: &AssetIdOf<T>,
+		to: &T::AccountId,
+		value: AssetBalanceOf<T>,
+	) -> Result<(), &'static str> {
+		let self_account = self.ctx.self_account.clone();
+		asset_transfer(asset_id, &self_account, to, value, self.ctx)
+	}
+
 	fn random(&self, subject: &[u8]) -> SeedOf<T> {
 		system::Module::<T>::random(subject)
 	}
@@ -806,9 +1022,10 @@ mod tests {
 	use crate::account_db::AccountDb;
 	use crate::exec::{ExecReturnValue, ExecError, STATUS_SUCCESS};
 	use crate::gas::GasMeter;
-	use crate::tests::{ExtBuilder, Test};
+	use crate::tests::{ExtBuilder, MockDoughnut, Test};
 	use crate::{CodeHash, Config};
 	use runtime_io::with_externalities;
+	use support::{StorageDoubleMap, StorageMap};
 	use std::cell::RefCell;
 	use std::rc::Rc;
 	use std::collections::HashMap;
@@ -818,6 +1035,7 @@ mod tests {
 	const ALICE: u64 = 1;
 	const BOB: u64 = 2;
 	const CHARLIE: u64 = 3;
+	const DJANGO: u64 = 4;
 
 	impl<'a, T, V, L> ExecutionContext<'a, T, V, L>
 		where T: crate::Trait
@@ -897,6 +1115,9 @@ mod tests {
 				.cloned()
 				.ok_or_else(|| "code not found")
 		}
+		fn code_len(&self, _executable: &Self::Executable) -> u32 {
+			0
+		}
 	}
 
 	impl<'a> Vm<Test> for MockVm<'a> {
@@ -1024,6 +1245,236 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn asset_transfers_are_reverted_on_failing_call() {
+		// This test verifies that a contract is able to transfer some of an asset
+		// to another account, and that the transfer is reverted if the call fails.
+		const ASSET: u32 = 16000;
+		let vm = MockVm::new();
+		let mut loader = MockLoader::empty();
+		let transfer_ch = loader.insert(|ctx| {
+			assert_eq!(ctx.ext.asset_balance(&ASSET), 100);
+			assert_eq!(
+				ctx.ext.asset_transfer(&ASSET, &CHARLIE, 101),
+				Err("balance too low to send value")
+			);
+			assert_eq!(ctx.ext.asset_transfer(&ASSET, &CHARLIE, 30), Ok(()));
+			assert_eq!(ctx.ext.asset_balance(&ASSET), 70);
+			Ok(ExecReturnValue { status: ctx.input_data[0], data: Vec::new() })
+		});
+
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			let cfg = Config::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			ctx.overlay.instantiate_contract(&BOB, transfer_ch).unwrap();
+			ctx.overlay.set_asset_balance(&BOB, ASSET, 100);
+
+			let output = ctx.call(
+				BOB,
+				0,
+				&mut GasMeter::<Test>::with_limit(1000, 1),
+				vec![1],
+			).unwrap();
+
+			assert!(!output.is_success());
+			assert_eq!(ctx.overlay.get_asset_balance(&BOB, &ASSET), 100);
+			assert_eq!(ctx.overlay.get_asset_balance(&CHARLIE, &ASSET), 0);
+
+			let output = ctx.call(
+				BOB,
+				0,
+				&mut GasMeter::<Test>::with_limit(1000, 1),
+				vec![0],
+			).unwrap();
+
+			assert!(output.is_success());
+			assert_eq!(ctx.overlay.get_asset_balance(&BOB, &ASSET), 70);
+			assert_eq!(ctx.overlay.get_asset_balance(&CHARLIE, &ASSET), 30);
+			assert_eq!(&ctx.events(), &[
+				DeferredAction::DepositEvent {
+					event: RawEvent::AssetTransfer(ASSET, BOB, CHARLIE, 30),
+					topics: Vec::new(),
+				}
+			]);
+		});
+	}
+
+	#[test]
+	fn asset_transfers_respect_asset_restrictions() {
+		// This test verifies that a contract may only transfer a restricted asset
+		// to the accounts on its allow-list.
+		const ASSET: u32 = 16000;
+		let vm = MockVm::new();
+		let mut loader = MockLoader::empty();
+		let transfer_ch = loader.insert(|ctx| {
+			assert_eq!(
+				ctx.ext.asset_transfer(&ASSET, &CHARLIE, 30),
+				Err("destination is not allowed to receive asset")
+			);
+			assert_eq!(ctx.ext.asset_transfer(&ASSET, &DJANGO, 30), Ok(()));
+			Ok(ExecReturnValue { status: STATUS_SUCCESS, data: Vec::new() })
+		});
+
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			<generic_asset::Restricted<Test>>::insert(ASSET, true);
+			<generic_asset::AllowList<Test>>::insert(ASSET, DJANGO, true);
+
+			let cfg = Config::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			ctx.overlay.instantiate_contract(&BOB, transfer_ch).unwrap();
+			ctx.overlay.set_asset_balance(&BOB, ASSET, 100);
+
+			let output = ctx.call(
+				BOB,
+				0,
+				&mut GasMeter::<Test>::with_limit(1000, 1),
+				vec![],
+			).unwrap();
+
+			assert!(output.is_success());
+			assert_eq!(ctx.overlay.get_asset_balance(&BOB, &ASSET), 70);
+			assert_eq!(ctx.overlay.get_asset_balance(&CHARLIE, &ASSET), 0);
+			assert_eq!(ctx.overlay.get_asset_balance(&DJANGO, &ASSET), 30);
+		});
+	}
+
+	#[test]
+	fn set_code_hash_keeps_storage_and_is_reverted_on_failing_call() {
+		// This test verifies that a contract is able to replace its code, that its storage
+		// is kept for the new code, and that the replacement is reverted if the call fails.
+		let vm = MockVm::new();
+		let mut loader = MockLoader::empty();
+		let new_ch = loader.insert(|ctx| {
+			assert_eq!(ctx.ext.get_storage(&[1; 32]), Some(vec![42]));
+			exec_success()
+		});
+		let unknown_ch = <Test as system::Trait>::Hash::repeat_byte(0xff);
+		let old_ch = loader.insert(move |ctx| {
+			ctx.ext.set_storage([1; 32], Some(vec![42])).unwrap();
+			assert_eq!(ctx.ext.set_code_hash(unknown_ch), Err("code not found"));
+			assert_eq!(ctx.ext.set_code_hash(new_ch), Ok(0));
+			Ok(ExecReturnValue { status: ctx.input_data[0], data: Vec::new() })
+		});
+
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			let cfg = Config::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			ctx.overlay.instantiate_contract(&BOB, old_ch).unwrap();
+
+			let output = ctx.call(
+				BOB,
+				0,
+				&mut GasMeter::<Test>::with_limit(1000, 1),
+				vec![1],
+			).unwrap();
+
+			assert!(!output.is_success());
+			assert_eq!(ctx.overlay.get_code_hash(&BOB), Some(old_ch));
+			assert!(ctx.events().is_empty());
+
+			let output = ctx.call(
+				BOB,
+				0,
+				&mut GasMeter::<Test>::with_limit(1000, 1),
+				vec![0],
+			).unwrap();
+
+			assert!(output.is_success());
+			assert_eq!(ctx.overlay.get_code_hash(&BOB), Some(new_ch));
+			assert_eq!(&ctx.events(), &[
+				DeferredAction::DepositEvent {
+					event: RawEvent::CodeHashUpdated(BOB, new_ch),
+					topics: Vec::new(),
+				}
+			]);
+
+			// The next call runs the new code.
+			let output = ctx.call(
+				BOB,
+				0,
+				&mut GasMeter::<Test>::with_limit(1000, 1),
+				vec![],
+			).unwrap();
+
+			assert!(output.is_success());
+		});
+	}
+
+	#[test]
+	fn doughnut_is_only_visible_to_the_contract_called_by_the_origin() {
+		let vm = MockVm::new();
+		let mut loader = MockLoader::empty();
+		let doughnut = MockDoughnut::new(ALICE, CHARLIE, None);
+
+		let charlie_ch = loader.insert(|ctx| {
+			assert_eq!(ctx.ext.doughnut(), None);
+			exec_success()
+		});
+		let bob_ch = loader.insert(|ctx| {
+			assert_eq!(ctx.ext.doughnut(), Some(&MockDoughnut::new(ALICE, CHARLIE, None)));
+			assert_matches!(
+				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![]),
+				Ok(_)
+			);
+			exec_success()
+		});
+
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			let cfg = Config::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader)
+				.with_doughnut(Some(doughnut));
+			ctx.overlay.instantiate_contract(&BOB, bob_ch).unwrap();
+			ctx.overlay.instantiate_contract(&CHARLIE, charlie_ch).unwrap();
+
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::with_limit(10000, 1), vec![]),
+				Ok(_)
+			);
+		});
+	}
+
+	#[test]
+	fn tracing_records_nested_calls_and_storage_accesses() {
+		let vm = MockVm::new();
+		let mut loader = MockLoader::empty();
+
+		let charlie_ch = loader.insert(|_| Ok(ExecReturnValue { status: 1, data: Vec::new() }));
+		let bob_ch = loader.insert(|ctx| {
+			assert_eq!(ctx.ext.get_storage(&[1; 32]), None);
+			assert_eq!(ctx.ext.get_storage(&[1; 32]), None);
+			assert_eq!(ctx.ext.set_storage([2; 32], Some(vec![1])), Ok(()));
+			let output = ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![]).unwrap();
+			assert!(!output.is_success());
+			exec_success()
+		});
+
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			let cfg = Config::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader).with_tracing();
+			ctx.overlay.instantiate_contract(&BOB, bob_ch).unwrap();
+			ctx.overlay.instantiate_contract(&CHARLIE, charlie_ch).unwrap();
+
+			let mut gas_meter = GasMeter::<Test>::with_limit(10000, 1);
+			assert_matches!(ctx.call(BOB, 0, &mut gas_meter, vec![]), Ok(_));
+
+			let traces = ctx.take_traces();
+			assert_eq!(traces.len(), 1);
+			let trace = &traces[0];
+			assert_eq!(trace.dest, BOB);
+			assert!(trace.success);
+			assert_eq!(trace.gas_used, 10000 - gas_meter.gas_left());
+			assert_eq!(trace.storage_reads, vec![[1; 32]]);
+			assert_eq!(trace.storage_writes, vec![[2; 32]]);
+
+			// The failed call is traced, even though its changes are reverted.
+			assert_eq!(trace.nested.len(), 1);
+			assert_eq!(trace.nested[0].dest, CHARLIE);
+			assert!(!trace.nested[0].success);
+			assert!(trace.nested[0].gas_used > 0);
+			assert!(trace.nested[0].nested.is_empty());
+		});
+	}
+
 	#[test]
 	fn changes_are_reverted_on_failing_call() {
 		// This test verifies that a contract is able to transfer
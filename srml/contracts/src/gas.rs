@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate. If not, see <http://www.gnu.org/licenses/>.
 
-use crate::{GasSpent, Module, Trait, BalanceOf, NegativeImbalanceOf};
+use crate::{GasSpent, Module, Trait, GasBalanceOf, GasNegativeImbalanceOf};
 use rstd::convert::TryFrom;
 use sr_primitives::traits::{
 	CheckedMul, Zero, SaturatedConversion, SimpleArithmetic, UniqueSaturatedInto,
@@ -90,13 +90,13 @@ pub struct GasMeter<T: Trait> {
 	limit: Gas,
 	/// Amount of gas left from initial gas limit. Can reach zero.
 	gas_left: Gas,
-	gas_price: BalanceOf<T>,
+	gas_price: GasBalanceOf<T>,
 
 	#[cfg(test)]
 	tokens: Vec<ErasedToken>,
 }
 impl<T: Trait> GasMeter<T> {
-	pub fn with_limit(gas_limit: Gas, gas_price: BalanceOf<T>) -> GasMeter<T> {
+	pub fn with_limit(gas_limit: Gas, gas_price: GasBalanceOf<T>) -> GasMeter<T> {
 		GasMeter {
 			limit: gas_limit,
 			gas_left: gas_limit,
@@ -174,7 +174,7 @@ impl<T: Trait> GasMeter<T> {
 		}
 	}
 
-	pub fn gas_price(&self) -> BalanceOf<T> {
+	pub fn gas_price(&self) -> GasBalanceOf<T> {
 		self.gas_price
 	}
 
@@ -201,18 +201,18 @@ impl<T: Trait> GasMeter<T> {
 pub fn buy_gas<T: Trait>(
 	transactor: &T::AccountId,
 	gas_limit: Gas,
-) -> Result<(GasMeter<T>, NegativeImbalanceOf<T>), &'static str> {
+) -> Result<(GasMeter<T>, GasNegativeImbalanceOf<T>), &'static str> {
 	// Buy the specified amount of gas.
 	let gas_price = <Module<T>>::gas_price();
 	let cost = if gas_price.is_zero() {
-		<BalanceOf<T>>::zero()
+		<GasBalanceOf<T>>::zero()
 	} else {
-		<BalanceOf<T> as TryFrom<Gas>>::try_from(gas_limit).ok()
+		<GasBalanceOf<T> as TryFrom<Gas>>::try_from(gas_limit).ok()
 			.and_then(|gas_limit| gas_price.checked_mul(&gas_limit))
 			.ok_or("overflow multiplying gas limit by price")?
 	};
 
-	let imbalance = T::Currency::withdraw(
+	let imbalance = T::GasCurrency::withdraw(
 		transactor,
 		cost,
 		WithdrawReason::Fee,
@@ -226,7 +226,7 @@ pub fn buy_gas<T: Trait>(
 pub fn refund_unused_gas<T: Trait>(
 	transactor: &T::AccountId,
 	gas_meter: GasMeter<T>,
-	imbalance: NegativeImbalanceOf<T>,
+	imbalance: GasNegativeImbalanceOf<T>,
 ) {
 	let gas_spent = gas_meter.spent();
 	let gas_left = gas_meter.gas_left();
@@ -238,7 +238,7 @@ pub fn refund_unused_gas<T: Trait>(
 
 	// Refund gas left by the price it was bought at.
 	let refund = gas_meter.gas_price * gas_left.unique_saturated_into();
-	let refund_imbalance = T::Currency::deposit_creating(transactor, refund);
+	let refund_imbalance = T::GasCurrency::deposit_creating(transactor, refund);
 	if let Ok(imbalance) = imbalance.offset(refund_imbalance) {
 		T::GasPayment::on_unbalanced(imbalance);
 	}
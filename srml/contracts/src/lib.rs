@@ -43,6 +43,9 @@
 //! Senders must specify a gas limit with every call, as all instructions invoked by the smart-contract require gas.
 //! Unused gas is refunded after the call, regardless of the execution outcome.
 //!
+//! Gas is bought with `GasCurrency`, which need not be the `Currency` used for value transfers and rent. The
+//! fees that are charged as gas (transfer, creation and dispatch fees) are denominated in `GasCurrency` too.
+//!
 //! If the gas limit is reached, then all calls and state changes (including balance transfers) are only
 //! reverted at the current call's contract level. For example, if contract A calls B and B runs out of gas mid-call,
 //! then all of B's calls are reverted. Assuming correct error handling by contract A, A's other calls and state
@@ -54,6 +57,23 @@
 //! and the call will only revert at the specific contract level. For example, if contract A calls contract B, and B
 //! fails, A can decide how to handle that failure, either proceeding or reverting A's changes.
 //!
+//! ### Delegation
+//!
+//! `call` and `instantiate` may be dispatched under a doughnut, i.e. with a `Delegated` origin. The
+//! contract called (or instantiated) directly by such an origin can inspect the doughnut via
+//! `ext_doughnut`. Delegation does not propagate any further:
+//!
+//! - Contracts called or instantiated by that contract through `ext_call` or `ext_instantiate` are
+//! not acting under the doughnut, as their caller is the contract rather than the doughnut issuer.
+//! - Runtime calls made through `ext_dispatch_call` are always dispatched with a signed origin of the
+//! contract's own account, never a delegated one.
+//!
+//! ### Code Upgrades
+//!
+//! A contract can replace its own code with any stored code via `ext_set_code_hash`. Its address, storage, balance
+//! and rent allowance are kept. As the tombstone of an evicted contract is made from the code hash it had when it was
+//! evicted, restoring an upgraded contract requires the code hash it was upgraded to.
+//!
 //! ## Interface
 //!
 //! ### Dispatchable functions
@@ -104,7 +124,7 @@ use crate::account_db::{AccountDb, DirectAccountDb};
 use crate::wasm::{WasmLoader, WasmVm};
 
 pub use crate::gas::{Gas, GasMeter};
-pub use crate::exec::{ExecResult, ExecReturnValue, ExecError, StatusCode};
+pub use crate::exec::{ExecResult, ExecReturnValue, ExecError, StatusCode, CallTrace};
 
 #[cfg(feature = "std")]
 use serde::{Serialize, Deserialize};
@@ -113,7 +133,9 @@ use rstd::{prelude::*, marker::PhantomData};
 use codec::{Codec, Encode, Decode};
 use runtime_io::blake2_256;
 use sr_primitives::{
-	traits::{Hash, StaticLookup, Zero, MaybeSerializeDebug, Member, SignedExtension},
+	traits::{
+		Hash, StaticLookup, Zero, MaybeSerializeDebug, Member, SignedExtension, Convert, DoughnutApi,
+	},
 	weights::DispatchInfo,
 	transaction_validity::{
 		ValidTransaction, InvalidTransaction, TransactionValidity, TransactionValidityError,
@@ -124,7 +146,7 @@ use support::{
 	Parameter, decl_module, decl_event, decl_storage, storage::child,
 	parameter_types,
 };
-use support::{traits::{OnFreeBalanceZero, OnUnbalanced, Currency, Get, Time}, IsSubType};
+use support::{traits::{OnFreeBalanceZero, OnUnbalanced, Currency, Get, MultiCurrency, Time}, IsSubType};
 use system::{ensure_signed, RawOrigin, ensure_root};
 use primitives::storage::well_known_keys::CHILD_STORAGE_KEY_PREFIX;
 
@@ -296,6 +318,20 @@ where
 pub type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
 pub type NegativeImbalanceOf<T> =
 	<<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
+pub type GasBalanceOf<T> = <<T as Trait>::GasCurrency as Currency<<T as system::Trait>::AccountId>>::Balance;
+pub type GasNegativeImbalanceOf<T> =
+	<<T as Trait>::GasCurrency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
+pub type AssetIdOf<T> =
+	<<T as Trait>::MultiCurrency as MultiCurrency<<T as system::Trait>::AccountId>>::AssetId;
+pub type AssetBalanceOf<T> =
+	<<T as Trait>::MultiCurrency as MultiCurrency<<T as system::Trait>::AccountId>>::Balance;
+pub type DoughnutOf<T> = <T as system::Trait>::Doughnut;
+pub type DoughnutPublicKeyOf<T> = <DoughnutOf<T> as DoughnutApi>::PublicKey;
+
+/// The doughnut domain holding the permissions a doughnut grants to contracts.
+///
+/// Contracts called under delegation can read this domain through `ext_doughnut`.
+pub const CONTRACTS_DOMAIN: &str = "contracts";
 
 parameter_types! {
 	/// A reasonable default value for [`Trait::SignedClaimedHandicap`].
@@ -334,6 +370,13 @@ parameter_types! {
 
 pub trait Trait: system::Trait {
 	type Currency: Currency<Self::AccountId>;
+	/// The currency gas is bought with and refunded in. It may differ from `Currency`, which is
+	/// used for value transfers and rent.
+	type GasCurrency: Currency<Self::AccountId>;
+	/// The multi-asset system whose assets contracts may query and transfer, besides `Currency`.
+	type MultiCurrency: MultiCurrency<Self::AccountId>;
+	/// Converts the issuer and holder of a doughnut into accounts, for contracts called under it.
+	type DoughnutAccount: Convert<DoughnutPublicKeyOf<Self>, Self::AccountId>;
 	type Time: Time;
 
 	/// The outer call dispatch type.
@@ -349,13 +392,13 @@ pub trait Trait: system::Trait {
 	///
 	/// It is recommended (though not required) for this function to return a fee that would be
 	/// taken by the Executive module for regular dispatch.
-	type ComputeDispatchFee: ComputeDispatchFee<<Self as Trait>::Call, BalanceOf<Self>>;
+	type ComputeDispatchFee: ComputeDispatchFee<<Self as Trait>::Call, GasBalanceOf<Self>>;
 
 	/// trie id generator
 	type TrieIdGenerator: TrieIdGenerator<Self::AccountId>;
 
 	/// Handler for the unbalanced reduction when making a gas payment.
-	type GasPayment: OnUnbalanced<NegativeImbalanceOf<Self>>;
+	type GasPayment: OnUnbalanced<GasNegativeImbalanceOf<Self>>;
 
 	/// Number of block delay an extrinsic claim surcharge has.
 	///
@@ -387,19 +430,19 @@ pub trait Trait: system::Trait {
 	type SurchargeReward: Get<BalanceOf<Self>>;
 
 	/// The fee required to make a transfer.
-	type TransferFee: Get<BalanceOf<Self>>;
+	type TransferFee: Get<GasBalanceOf<Self>>;
 
 	/// The fee required to create an account.
-	type CreationFee: Get<BalanceOf<Self>>;
+	type CreationFee: Get<GasBalanceOf<Self>>;
 
 	/// The fee to be paid for making a transaction; the base.
-	type TransactionBaseFee: Get<BalanceOf<Self>>;
+	type TransactionBaseFee: Get<GasBalanceOf<Self>>;
 
 	/// The fee to be paid for making a transaction; the per-byte portion.
-	type TransactionByteFee: Get<BalanceOf<Self>>;
+	type TransactionByteFee: Get<GasBalanceOf<Self>>;
 
 	/// The fee required to instantiate a contract instance.
-	type ContractFee: Get<BalanceOf<Self>>;
+	type ContractFee: Get<GasBalanceOf<Self>>;
 
 	/// The base fee charged for calling into a contract.
 	type CallBaseFee: Get<Gas>;
@@ -444,8 +487,8 @@ where
 /// the implementation of `TakeFees` for the Balances module does. Note that this only takes a fixed
 /// fee based on size. Unlike the balances module, weight-fee is applied.
 pub struct DefaultDispatchFeeComputor<T: Trait>(PhantomData<T>);
-impl<T: Trait> ComputeDispatchFee<<T as Trait>::Call, BalanceOf<T>> for DefaultDispatchFeeComputor<T> {
-	fn compute_dispatch_fee(call: &<T as Trait>::Call) -> BalanceOf<T> {
+impl<T: Trait> ComputeDispatchFee<<T as Trait>::Call, GasBalanceOf<T>> for DefaultDispatchFeeComputor<T> {
+	fn compute_dispatch_fee(call: &<T as Trait>::Call) -> GasBalanceOf<T> {
 		let encoded_len = call.using_encoded(|encoded| encoded.len() as u32);
 		let base_fee = T::TransactionBaseFee::get();
 		let byte_fee = T::TransactionByteFee::get();
@@ -486,20 +529,20 @@ decl_module! {
 		const SurchargeReward: BalanceOf<T> = T::SurchargeReward::get();
 
 		/// The fee required to make a transfer.
-		const TransferFee: BalanceOf<T> = T::TransferFee::get();
+		const TransferFee: GasBalanceOf<T> = T::TransferFee::get();
 
 		/// The fee required to create an account.
-		const CreationFee: BalanceOf<T> = T::CreationFee::get();
+		const CreationFee: GasBalanceOf<T> = T::CreationFee::get();
 
 		/// The fee to be paid for making a transaction; the base.
-		const TransactionBaseFee: BalanceOf<T> = T::TransactionBaseFee::get();
+		const TransactionBaseFee: GasBalanceOf<T> = T::TransactionBaseFee::get();
 
 		/// The fee to be paid for making a transaction; the per-byte portion.
-		const TransactionByteFee: BalanceOf<T> = T::TransactionByteFee::get();
+		const TransactionByteFee: GasBalanceOf<T> = T::TransactionByteFee::get();
 
 		/// The fee required to instantiate a contract instance. A reasonable default value
 		/// is 21.
-		const ContractFee: BalanceOf<T> = T::ContractFee::get();
+		const ContractFee: GasBalanceOf<T> = T::ContractFee::get();
 
 		/// The base fee charged for calling into a contract. A reasonable default
 		/// value is 135.
@@ -573,12 +616,14 @@ decl_module! {
 			#[compact] gas_limit: Gas,
 			data: Vec<u8>
 		) -> Result {
-			let origin = ensure_signed(origin)?;
+			let (origin, doughnut) = ensure_signed_or_delegated::<T>(origin)?;
 			let dest = T::Lookup::lookup(dest)?;
 
-			Self::bare_call(origin, dest, value, gas_limit, data)
-				.map(|_| ())
-				.map_err(|e| e.reason)
+			Self::execute_wasm(origin, doughnut, gas_limit, |ctx, gas_meter| {
+				ctx.call(dest, value, gas_meter, data)
+			})
+			.map(|_| ())
+			.map_err(|e| e.reason)
 		}
 
 		/// Instantiates a new contract from the `codehash` generated by `put_code`, optionally transferring some balance.
@@ -598,9 +643,9 @@ decl_module! {
 			code_hash: CodeHash<T>,
 			data: Vec<u8>
 		) -> Result {
-			let origin = ensure_signed(origin)?;
+			let (origin, doughnut) = ensure_signed_or_delegated::<T>(origin)?;
 
-			Self::execute_wasm(origin, gas_limit, |ctx, gas_meter| {
+			Self::execute_wasm(origin, doughnut, gas_limit, |ctx, gas_meter| {
 				ctx.instantiate(endowment, gas_meter, &code_hash, data)
 					.map(|(_address, output)| output)
 			})
@@ -649,6 +694,38 @@ decl_module! {
 	}
 }
 
+/// Ensure `o` is a signed or delegated origin, returning its account and the doughnut, if any,
+/// it is acting under.
+fn ensure_signed_or_delegated<T: Trait>(
+	o: T::Origin,
+) -> rstd::result::Result<(T::AccountId, Option<DoughnutOf<T>>), &'static str> {
+	match o.into() {
+		Ok(RawOrigin::Signed(who)) => Ok((who, None)),
+		Ok(RawOrigin::Delegated(who, doughnut)) => Ok((who, Some(doughnut))),
+		_ => Err("bad origin: expected to be a signed origin"),
+	}
+}
+
+/// The outcome of a dry run of a contract call, see `Module::dry_run_call`.
+pub struct DryRun<T: Trait> {
+	/// The result of the call.
+	pub result: ExecResult,
+	/// The gas used by the call.
+	pub gas_used: Gas,
+	/// The trace of the call and its nested calls.
+	///
+	/// `None` if the call failed before it was made.
+	pub trace: Option<CallTrace<T::AccountId>>,
+	/// The events that would be deposited, with their topics.
+	pub events: Vec<(Event<T>, Vec<T::Hash>)>,
+	/// The transfers that would be made, as `(from, to, value)`.
+	pub transfers: Vec<(T::AccountId, T::AccountId, BalanceOf<T>)>,
+	/// The asset transfers that would be made, as `(asset_id, from, to, value)`.
+	pub asset_transfers: Vec<(AssetIdOf<T>, T::AccountId, T::AccountId, AssetBalanceOf<T>)>,
+	/// The runtime calls that would be dispatched, with the contract dispatching each.
+	pub dispatches: Vec<(T::AccountId, <T as Trait>::Call)>,
+}
+
 impl<T: Trait> Module<T> {
 	/// Perform a call to a specified contract.
 	///
@@ -661,35 +738,89 @@ impl<T: Trait> Module<T> {
 		gas_limit: Gas,
 		input_data: Vec<u8>,
 	) -> ExecResult {
-		Self::execute_wasm(origin, gas_limit, |ctx, gas_meter| {
+		Self::execute_wasm(origin, None, gas_limit, |ctx, gas_meter| {
 			ctx.call(dest, value, gas_meter, input_data)
 		})
 	}
 
+	/// Perform a dry run of a call to a specified contract, tracing its execution.
+	///
+	/// The call is executed as by `Self::bare_call`, except that no gas is bought, none of its
+	/// changes are committed and none of its deferred actions, e.g. events and dispatched calls,
+	/// are executed. These are reported instead.
+	pub fn dry_run_call(
+		origin: T::AccountId,
+		dest: T::AccountId,
+		value: BalanceOf<T>,
+		gas_limit: Gas,
+		input_data: Vec<u8>,
+	) -> DryRun<T> {
+		let mut dry_run = DryRun {
+			result: Ok(ExecReturnValue { status: exec::STATUS_SUCCESS, data: Vec::new() }),
+			gas_used: 0,
+			trace: None,
+			events: Vec::new(),
+			transfers: Vec::new(),
+			asset_transfers: Vec::new(),
+			dispatches: Vec::new(),
+		};
+
+		let result = Self::execute_wasm_with(origin, None, gas_limit, Some(&mut dry_run), |ctx, gas_meter| {
+			ctx.call(dest, value, gas_meter, input_data)
+		});
+		dry_run.result = result;
+
+		dry_run
+	}
+
 	fn execute_wasm(
 		origin: T::AccountId,
+		doughnut: Option<DoughnutOf<T>>,
 		gas_limit: Gas,
 		func: impl FnOnce(&mut ExecutionContext<T, WasmVm, WasmLoader>, &mut GasMeter<T>) -> ExecResult
+	) -> ExecResult {
+		Self::execute_wasm_with(origin, doughnut, gas_limit, None, func)
+	}
+
+	/// Execute `func`, recording its trace, changes and deferred actions in `dry_run` instead of
+	/// buying gas, committing the changes and executing the deferred actions, if given.
+	fn execute_wasm_with(
+		origin: T::AccountId,
+		doughnut: Option<DoughnutOf<T>>,
+		gas_limit: Gas,
+		mut dry_run: Option<&mut DryRun<T>>,
+		func: impl FnOnce(&mut ExecutionContext<T, WasmVm, WasmLoader>, &mut GasMeter<T>) -> ExecResult
 	) -> ExecResult {
 		// Pay for the gas upfront.
 		//
 		// NOTE: it is very important to avoid any state changes before
 		// paying for the gas.
-		let (mut gas_meter, imbalance) =
-			try_or_exec_error!(
+		let (mut gas_meter, imbalance) = if dry_run.is_some() {
+			(GasMeter::with_limit(gas_limit, <Module<T>>::gas_price()), None)
+		} else {
+			let (gas_meter, imbalance) = try_or_exec_error!(
 				gas::buy_gas::<T>(&origin, gas_limit),
 				// We don't have a spare buffer here in the first place, so create a new empty one.
 				Vec::new()
 			);
+			(gas_meter, Some(imbalance))
+		};
 
 		let cfg = Config::preload();
 		let vm = WasmVm::new(&cfg.schedule);
 		let loader = WasmLoader::new(&cfg.schedule);
-		let mut ctx = ExecutionContext::top_level(origin.clone(), &cfg, &vm, &loader);
+		let mut ctx = ExecutionContext::top_level(origin.clone(), &cfg, &vm, &loader)
+			.with_doughnut(doughnut);
+		if dry_run.is_some() {
+			ctx = ctx.with_tracing();
+		}
 
 		let result = func(&mut ctx, &mut gas_meter);
 
-		if result.as_ref().map(|output| output.is_success()).unwrap_or(false) {
+		if let Some(ref mut dry_run) = dry_run {
+			dry_run.gas_used = gas_limit - gas_meter.gas_left();
+			dry_run.trace = ctx.take_traces().pop();
+		} else if result.as_ref().map(|output| output.is_success()).unwrap_or(false) {
 			// Commit all changes that made it thus far into the persistent storage.
 			DirectAccountDb.commit(ctx.overlay.into_change_set());
 		}
@@ -698,11 +829,31 @@ impl<T: Trait> Module<T> {
 		//
 		// NOTE: This should go after the commit to the storage, since the storage changes
 		// can alter the balance of the caller.
-		gas::refund_unused_gas::<T>(&origin, gas_meter, imbalance);
+		if let Some(imbalance) = imbalance {
+			gas::refund_unused_gas::<T>(&origin, gas_meter, imbalance);
+		}
 
-		// Execute deferred actions.
+		// Execute deferred actions, or only record them in a dry run.
 		ctx.deferred.into_iter().for_each(|deferred| {
 			use self::exec::DeferredAction::*;
+			if let Some(ref mut dry_run) = dry_run {
+				match deferred {
+					DepositEvent { topics, event } => {
+						match &event {
+							RawEvent::Transfer(from, to, value) =>
+								dry_run.transfers.push((from.clone(), to.clone(), *value)),
+							RawEvent::AssetTransfer(asset_id, from, to, value) =>
+								dry_run.asset_transfers.push((*asset_id, from.clone(), to.clone(), *value)),
+							_ => (),
+						}
+						dry_run.events.push((event, topics));
+					}
+					DispatchRuntimeCall { origin: who, call } => dry_run.dispatches.push((who, call)),
+					RestoreTo { .. } => (),
+				}
+				return;
+			}
+
 			match deferred {
 				DepositEvent {
 					topics,
@@ -810,18 +961,26 @@ decl_event! {
 	pub enum Event<T>
 	where
 		Balance = BalanceOf<T>,
+		AssetId = AssetIdOf<T>,
+		AssetBalance = AssetBalanceOf<T>,
 		<T as system::Trait>::AccountId,
 		<T as system::Trait>::Hash
 	{
 		/// Transfer happened `from` to `to` with given `value` as part of a `call` or `instantiate`.
 		Transfer(AccountId, AccountId, Balance),
 
+		/// Transfer of an asset happened `from` to `to` with given `value` by a contract.
+		AssetTransfer(AssetId, AccountId, AccountId, AssetBalance),
+
 		/// Contract deployed by address at the specified address.
 		Instantiated(AccountId, AccountId),
 
 		/// Code with the specified hash has been stored.
 		CodeStored(Hash),
 
+		/// The contract at the given address replaced its code with the code of the specified hash.
+		CodeHashUpdated(AccountId, Hash),
+
 		/// Triggered when the current schedule is updated.
 		ScheduleUpdated(u32),
 
@@ -849,7 +1008,7 @@ decl_storage! {
 		/// The code associated with a given account.
 		pub ContractInfoOf: map T::AccountId => Option<ContractInfo<T>>;
 		/// The price of one unit of gas.
-		GasPrice get(gas_price) config(): BalanceOf<T> = 1.into();
+		GasPrice get(gas_price) config(): GasBalanceOf<T> = 1.into();
 	}
 }
 
@@ -870,9 +1029,9 @@ pub struct Config<T: Trait> {
 	pub existential_deposit: BalanceOf<T>,
 	pub max_depth: u32,
 	pub max_value_size: u32,
-	pub contract_account_instantiate_fee: BalanceOf<T>,
-	pub account_create_fee: BalanceOf<T>,
-	pub transfer_fee: BalanceOf<T>,
+	pub contract_account_instantiate_fee: GasBalanceOf<T>,
+	pub account_create_fee: GasBalanceOf<T>,
+	pub transfer_fee: GasBalanceOf<T>,
 }
 
 impl<T: Trait> Config<T> {
@@ -929,6 +1088,18 @@ pub struct Schedule {
 	/// Gas cost per one byte written to the sandbox memory.
 	pub sandbox_data_write_cost: Gas,
 
+	/// Gas cost to query the balance of an asset.
+	pub asset_balance_cost: Gas,
+
+	/// Gas cost to transfer an asset.
+	pub asset_transfer_cost: Gas,
+
+	/// Base gas cost for a contract to replace its own code.
+	pub set_code_hash_cost: Gas,
+
+	/// Gas cost per byte of the code a contract replaces its own code with.
+	pub set_code_hash_per_byte_cost: Gas,
+
 	/// The maximum number of topics supported by an event.
 	pub max_event_topics: u32,
 
@@ -967,6 +1138,10 @@ impl Default for Schedule {
 			instantiate_base_cost: 175,
 			sandbox_data_read_cost: 1,
 			sandbox_data_write_cost: 1,
+			asset_balance_cost: 50,
+			asset_transfer_cost: 100,
+			set_code_hash_cost: 100,
+			set_code_hash_per_byte_cost: 1,
 			max_event_topics: 4,
 			max_stack_height: 64 * 1024,
 			max_memory_pages: 16,
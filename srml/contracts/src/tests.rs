@@ -23,7 +23,7 @@ use crate::account_db::{AccountDb, DirectAccountDb, OverlayAccountDb};
 use crate::{
 	BalanceOf, ComputeDispatchFee, ContractAddressFor, ContractInfo, ContractInfoOf, GenesisConfig,
 	Module, RawAliveContractInfo, RawEvent, Trait, TrieId, TrieIdFromParentCounter, Schedule,
-	TrieIdGenerator, CheckBlockGasLimit,
+	TrieIdGenerator, CheckBlockGasLimit, TombstoneContractInfo, CONTRACTS_DOMAIN,
 };
 use assert_matches::assert_matches;
 use hex_literal::*;
@@ -32,17 +32,18 @@ use runtime_io;
 use runtime_io::with_externalities;
 use sr_primitives::{
 	Perbill, BuildStorage, transaction_validity::{InvalidTransaction, ValidTransaction},
-	traits::{BlakeTwo256, Hash, IdentityLookup, SignedExtension},
+	traits::{BlakeTwo256, Convert, DoughnutApi, Hash, IdentityLookup, SignedExtension},
 	weights::{DispatchInfo, DispatchClass},
-	testing::{Digest, DigestItem, Header, UintAuthorityId, H256},
+	testing::{Digest, DigestItem, Header, TestAccountId, UintAuthorityId, H256},
 };
 use support::{
 	assert_ok, assert_err, impl_outer_dispatch, impl_outer_event, impl_outer_origin, parameter_types,
-	storage::child, StorageMap, StorageValue, traits::{Currency, Get},
+	storage::child, StorageDoubleMap, StorageLinkedMap, StorageMap, StorageValue,
+	traits::{Currency, Get, MultiCurrency},
 };
 use std::{cell::RefCell, sync::atomic::{AtomicUsize, Ordering}};
 use primitives::{storage::well_known_keys, Blake2Hasher};
-use system::{self, EventRecord, Phase};
+use system::{self, EventRecord, Phase, RawOrigin};
 
 mod contract {
 	// Re-export contents of the root. This basically
@@ -53,7 +54,7 @@ mod contract {
 }
 impl_outer_event! {
 	pub enum MetaEvent for Test {
-		balances<T>, contract<T>,
+		balances<T>, contract<T>, generic_asset<T>,
 	}
 }
 impl_outer_origin! {
@@ -93,6 +94,47 @@ impl Get<u64> for BlockGasLimit {
 	fn get() -> u64 { BLOCK_GAS_LIMIT.with(|v| *v.borrow()) }
 }
 
+/// A doughnut with an optional "contracts" domain.
+#[derive(Clone, Eq, PartialEq, Debug, Encode, Decode)]
+pub struct MockDoughnut {
+	issuer: u64,
+	holder: u64,
+	contracts_domain: Option<Vec<u8>>,
+}
+
+impl MockDoughnut {
+	pub fn new(issuer: u64, holder: u64, contracts_domain: Option<Vec<u8>>) -> Self {
+		MockDoughnut { issuer, holder, contracts_domain }
+	}
+}
+
+impl DoughnutApi for MockDoughnut {
+	type PublicKey = TestAccountId;
+	type Signature = [u8; 64];
+	type Timestamp = u32;
+	fn holder(&self) -> Self::PublicKey { self.holder.into() }
+	fn issuer(&self) -> Self::PublicKey { self.issuer.into() }
+	fn expiry(&self) -> Self::Timestamp { u32::max_value() }
+	fn not_before(&self) -> Self::Timestamp { 0 }
+	fn payload(&self) -> Vec<u8> { Default::default() }
+	fn signature(&self) -> Self::Signature { [0u8; 64] }
+	fn signature_version(&self) -> u8 { 0 }
+	fn get_domain(&self, domain: &str) -> Option<&[u8]> {
+		if domain == CONTRACTS_DOMAIN {
+			self.contracts_domain.as_ref().map(|d| &d[..])
+		} else {
+			None
+		}
+	}
+}
+
+pub struct MockDoughnutAccount;
+impl Convert<TestAccountId, u64> for MockDoughnutAccount {
+	fn convert(account: TestAccountId) -> u64 {
+		u64::from_le_bytes(account.0)
+	}
+}
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Test;
 parameter_types! {
@@ -120,7 +162,7 @@ impl system::Trait for Test {
 	type AvailableBlockRatio = AvailableBlockRatio;
 	type MaximumBlockLength = MaximumBlockLength;
 	type Version = ();
-        type Doughnut = ();
+        type Doughnut = MockDoughnut;
         type DelegatedDispatchVerifier = ();
 }
 impl balances::Trait for Test {
@@ -138,6 +180,21 @@ impl balances::Trait for Test {
 	type TransactionByteFee = BalancesTransactionByteFee;
 	type WeightToFee = ();
 }
+impl generic_asset::Trait for Test {
+	type Balance = u64;
+	type AssetId = u32;
+	type Event = MetaEvent;
+	type DustRemoval = ();
+	type OnFreeBalanceZero = ();
+	type OnNewAccount = ();
+	type RestrictedTransferOrigin = system::EnsureSigned<u64, MockDoughnut>;
+	type MaxSymbolLength = MaxSymbolLength;
+	type MaxNameLength = MaxNameLength;
+}
+parameter_types! {
+	pub const MaxSymbolLength: u32 = 8;
+	pub const MaxNameLength: u32 = 32;
+}
 parameter_types! {
 	pub const MinimumPeriod: u64 = 1;
 }
@@ -163,6 +220,9 @@ parameter_types! {
 }
 impl Trait for Test {
 	type Currency = Balances;
+	type GasCurrency = Balances;
+	type MultiCurrency = GenericAsset;
+	type DoughnutAccount = MockDoughnutAccount;
 	type Time = Timestamp;
 	type Call = Call;
 	type DetermineContractAddress = DummyContractAddressFor;
@@ -189,6 +249,7 @@ impl Trait for Test {
 }
 
 type Balances = balances::Module<Test>;
+type GenericAsset = generic_asset::Module<Test>;
 type Timestamp = timestamp::Module<Test>;
 type Contract = Module<Test>;
 type System = system::Module<Test>;
@@ -319,6 +380,73 @@ fn refunds_unused_gas() {
 	});
 }
 
+#[test]
+fn asset_balances_are_committed() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		assert_ok!(<GenericAsset as MultiCurrency<_>>::deposit(&16000, &1, 100));
+
+		let mut overlay = OverlayAccountDb::<Test>::new(&DirectAccountDb);
+		overlay.set_asset_balance(&1, 16000, 40);
+		overlay.set_asset_balance(&2, 16000, 60);
+		assert_eq!(GenericAsset::free_balance(&16000, &2), 0);
+		DirectAccountDb.commit(overlay.into_change_set());
+
+		assert_eq!(GenericAsset::free_balance(&16000, &1), 40);
+		assert_eq!(GenericAsset::free_balance(&16000, &2), 60);
+		assert_eq!(GenericAsset::total_issuance(&16000), 100);
+	});
+}
+
+#[test]
+#[should_panic(expected = "contract subsystem resulting in positive asset imbalance!")]
+fn committing_new_asset_balance_should_panic() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		let mut overlay = OverlayAccountDb::<Test>::new(&DirectAccountDb);
+		overlay.set_asset_balance(&1, 16000, 40);
+		DirectAccountDb.commit(overlay.into_change_set());
+	});
+}
+
+#[test]
+fn committing_new_asset_balance_should_panic_before_writing() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		assert_ok!(<GenericAsset as MultiCurrency<_>>::deposit(&16000, &1, 100));
+
+		let mut overlay = OverlayAccountDb::<Test>::new(&DirectAccountDb);
+		overlay.set_asset_balance(&1, 16000, 40);
+		overlay.set_asset_balance(&2, 16000, 100);
+		let commit = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+			DirectAccountDb.commit(overlay.into_change_set());
+		}));
+
+		assert!(commit.is_err());
+		assert_eq!(GenericAsset::free_balance(&16000, &1), 100);
+		assert_eq!(GenericAsset::free_balance(&16000, &2), 0);
+		assert_eq!(GenericAsset::total_issuance(&16000), 100);
+	});
+}
+
+#[test]
+fn committing_asset_balance_below_minimum_should_reap_account() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		<generic_asset::AssetMeta<Test>>::insert(16000, generic_asset::AssetInfo {
+			existential_deposit: Some(10),
+			..Default::default()
+		});
+		assert_ok!(<GenericAsset as MultiCurrency<_>>::deposit(&16000, &1, 100));
+
+		let mut overlay = OverlayAccountDb::<Test>::new(&DirectAccountDb);
+		overlay.set_asset_balance(&1, 16000, 5);
+		overlay.set_asset_balance(&2, 16000, 95);
+		DirectAccountDb.commit(overlay.into_change_set());
+
+		// the dust left to account 1 is removed along with the account
+		assert!(!<generic_asset::FreeBalance<Test>>::exists(&16000, &1));
+		assert_eq!(GenericAsset::free_balance(&16000, &2), 95);
+		assert_eq!(GenericAsset::total_issuance(&16000), 95);
+	});
+}
+
 #[test]
 fn account_removal_removes_storage() {
 	with_externalities(
@@ -598,6 +726,250 @@ fn dispatch_call() {
 	);
 }
 
+const CODE_DOUGHNUT: &str = r#"
+(module
+	(import "env" "ext_scratch_size" (func $ext_scratch_size (result i32)))
+	(import "env" "ext_scratch_read" (func $ext_scratch_read (param i32 i32 i32)))
+	(import "env" "ext_address" (func $ext_address))
+	(import "env" "ext_doughnut" (func $ext_doughnut (result i32)))
+	(import "env" "ext_deposit_event" (func $ext_deposit_event (param i32 i32 i32 i32)))
+	(import "env" "ext_call" (func $ext_call (param i32 i32 i64 i32 i32 i32 i32) (result i32)))
+	(import "env" "ext_dispatch_call" (func $ext_dispatch_call (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	;; Deposit an event with the doughnut the contract is called under, or no data if there is none.
+	(func $deposit_doughnut
+		(local $size i32)
+		(drop (call $ext_doughnut))
+		(set_local $size (call $ext_scratch_size))
+		(call $ext_scratch_read
+			(i32.const 64)		;; Pointer in memory to the place where to copy.
+			(i32.const 0)		;; Offset from the start of the scratch buffer.
+			(get_local $size)	;; Count of bytes to copy.
+		)
+		(call $ext_deposit_event
+			(i32.const 0)		;; The topics buffer
+			(i32.const 0)		;; The topics buffer's length
+			(i32.const 64)		;; The data buffer
+			(get_local $size)	;; The data buffer's length
+		)
+	)
+
+	(func (export "deploy")
+		(call $deposit_doughnut)
+	)
+
+	(func (export "call")
+		(local $top_level i32)
+
+		;; The contract calls itself with some input data, so only the outer call has none.
+		(set_local $top_level (i32.eqz (call $ext_scratch_size)))
+
+		(call $deposit_doughnut)
+
+		(if (get_local $top_level)
+			(then
+				;; Call this contract again.
+				(call $ext_address)
+				(call $ext_scratch_read
+					(i32.const 16)
+					(i32.const 0)
+					(i32.const 8)
+				)
+				(call $assert
+					(i32.eqz
+						(call $ext_call
+							(i32.const 16)	;; Pointer to "callee" address.
+							(i32.const 8)	;; Length of "callee" address.
+							(i64.const 0)	;; How much gas to devote for the execution. 0 = all.
+							(i32.const 24)	;; Pointer to the buffer with value to transfer
+							(i32.const 8)	;; Length of the buffer with value to transfer.
+							(i32.const 32)	;; Pointer to input data buffer address
+							(i32.const 1)	;; Length of input data buffer
+						)
+					)
+				)
+
+				;; Dispatch `Balances::transfer(CHARLIE, 50)`.
+				(call $ext_dispatch_call
+					(i32.const 40)	;; Pointer to the start of encoded call buffer
+					(i32.const 11)	;; Length of the buffer
+				)
+			)
+		)
+	)
+
+	;; Value to transfer to the nested call.
+	(data (i32.const 24) "\00\00\00\00\00\00\00\00")
+	;; Input data of the nested call.
+	(data (i32.const 32) "\01")
+	;; Encoded `Balances::transfer(CHARLIE, 50)`.
+	(data (i32.const 40) "\00\00\03\00\00\00\00\00\00\00\C8")
+)
+"#;
+
+#[test]
+fn delegation_only_reaches_the_contract_called_by_the_delegated_origin() {
+	let (wasm, code_hash) = compile_module::<Test>(CODE_DOUGHNUT).unwrap();
+
+	with_externalities(
+		&mut ExtBuilder::default().existential_deposit(50).build(),
+		|| {
+			let doughnut = MockDoughnut::new(ALICE, DJANGO, Some(vec![7]));
+			let delegated = || Origin::from(RawOrigin::Delegated(ALICE, doughnut.clone()));
+			let doughnut_events = || System::events().into_iter().filter_map(|record| match record.event {
+				MetaEvent::contract(RawEvent::Contract(BOB, data)) => Some(data),
+				_ => None,
+			}).collect::<Vec<_>>();
+			let encoded_doughnut = (ALICE, DJANGO, Some(vec![7u8])).encode();
+
+			Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, wasm));
+
+			// The constructor is called directly by the delegated origin.
+			assert_ok!(Contract::instantiate(delegated(), 1_000, 100_000, code_hash.into(), vec![]));
+			assert_eq!(doughnut_events(), vec![encoded_doughnut.clone()]);
+
+			// Only the outer call acts under the doughnut, the nested call to the same contract does not.
+			assert_ok!(Contract::call(delegated(), BOB, 0, 100_000, vec![]));
+			assert_eq!(doughnut_events()[1..], [encoded_doughnut.clone(), vec![]]);
+
+			// Runtime calls are dispatched by the contract itself.
+			assert!(System::events().contains(&EventRecord {
+				phase: Phase::ApplyExtrinsic(0),
+				event: MetaEvent::contract(RawEvent::Dispatched(BOB, true)),
+				topics: vec![],
+			}));
+			assert_eq!(Balances::free_balance(&BOB), 950);
+			assert_eq!(Balances::free_balance(&CHARLIE), 50);
+
+			// A signed origin is not acting under any doughnut.
+			assert_ok!(Contract::call(Origin::signed(ALICE), BOB, 0, 100_000, vec![]));
+			assert_eq!(doughnut_events()[3..], [vec![], vec![]]);
+		},
+	);
+}
+
+const CODE_DRY_RUN: &str = r#"
+(module
+	(import "env" "ext_get_storage" (func $ext_get_storage (param i32) (result i32)))
+	(import "env" "ext_set_storage" (func $ext_set_storage (param i32 i32 i32 i32)))
+	(import "env" "ext_call" (func $ext_call (param i32 i32 i64 i32 i32 i32 i32) (result i32)))
+	(import "env" "ext_dispatch_call" (func $ext_dispatch_call (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		;; Read the first key and write the second one.
+		(drop (call $ext_get_storage (i32.const 0)))
+		(call $ext_set_storage
+			(i32.const 32)	;; Pointer to the key
+			(i32.const 1)	;; Value is not null
+			(i32.const 64)	;; Pointer to the value
+			(i32.const 4)	;; Length of the value
+		)
+
+		;; Transfer 10 to CHARLIE.
+		(call $assert
+			(i32.eqz
+				(call $ext_call
+					(i32.const 68)	;; Pointer to "callee" address.
+					(i32.const 8)	;; Length of "callee" address.
+					(i64.const 0)	;; How much gas to devote for the execution. 0 = all.
+					(i32.const 76)	;; Pointer to the buffer with value to transfer
+					(i32.const 8)	;; Length of the buffer with value to transfer.
+					(i32.const 0)	;; Pointer to input data buffer address
+					(i32.const 0)	;; Length of input data buffer
+				)
+			)
+		)
+
+		;; Dispatch `Balances::transfer(CHARLIE, 50)`.
+		(call $ext_dispatch_call
+			(i32.const 84)	;; Pointer to the start of encoded call buffer
+			(i32.const 11)	;; Length of the buffer
+		)
+	)
+	(func (export "deploy"))
+
+	;; The storage keys read and written.
+	(data (i32.const 0) "\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01")
+	(data (i32.const 32) "\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02")
+	;; The value written.
+	(data (i32.const 64) "\01\02\03\04")
+	;; CHARLIE's address.
+	(data (i32.const 68) "\03\00\00\00\00\00\00\00")
+	;; The value transferred to CHARLIE.
+	(data (i32.const 76) "\0a\00\00\00\00\00\00\00")
+	;; Encoded `Balances::transfer(CHARLIE, 50)`.
+	(data (i32.const 84) "\00\00\03\00\00\00\00\00\00\00\C8")
+)
+"#;
+
+#[test]
+fn dry_run_call_traces_without_committing() {
+	let (wasm, code_hash) = compile_module::<Test>(CODE_DRY_RUN).unwrap();
+
+	with_externalities(
+		&mut ExtBuilder::default().existential_deposit(1).build(),
+		|| {
+			Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, wasm));
+			assert_ok!(Contract::instantiate(Origin::signed(ALICE), 1_000, 100_000, code_hash.into(), vec![]));
+
+			let alice_balance = Balances::free_balance(&ALICE);
+			let gas_spent = Contract::gas_spent();
+			let dry_run = Contract::dry_run_call(ALICE, BOB, 5, 100_000, vec![]);
+
+			assert!(dry_run.result.unwrap().is_success());
+			let trace = dry_run.trace.unwrap();
+			assert_eq!(trace.dest, BOB);
+			assert!(trace.success);
+			assert_eq!(trace.gas_used, dry_run.gas_used);
+			assert_eq!(trace.storage_reads, vec![[1; 32]]);
+			assert_eq!(trace.storage_writes, vec![[2; 32]]);
+			assert_eq!(trace.nested.len(), 1);
+			assert_eq!(trace.nested[0].dest, CHARLIE);
+			assert!(trace.nested[0].success);
+			assert!(trace.nested[0].gas_used > 0);
+			assert!(trace.nested[0].gas_used < trace.gas_used);
+			assert!(trace.nested[0].nested.is_empty());
+
+			assert_eq!(dry_run.transfers, vec![(ALICE, BOB, 5), (BOB, CHARLIE, 10)]);
+			assert_eq!(dry_run.events, vec![
+				(RawEvent::Transfer(ALICE, BOB, 5), vec![]),
+				(RawEvent::Transfer(BOB, CHARLIE, 10), vec![]),
+			]);
+			assert_eq!(dry_run.dispatches, vec![
+				(BOB, Call::Balances(balances::Call::transfer(CHARLIE, 50))),
+			]);
+
+			// Nothing was committed or dispatched, and no gas was bought.
+			assert_eq!(Balances::free_balance(&ALICE), alice_balance);
+			assert_eq!(Balances::free_balance(&BOB), 1_000);
+			assert_eq!(Balances::free_balance(&CHARLIE), 0);
+			assert_eq!(Contract::gas_spent(), gas_spent);
+		},
+	);
+}
+
 const CODE_DISPATCH_CALL_THEN_TRAP: &str = r#"
 (module
 	(import "env" "ext_dispatch_call" (func $ext_dispatch_call (param i32 i32)))
@@ -1263,6 +1635,179 @@ fn default_rent_allowance_on_instantiate() {
 	);
 }
 
+const CODE_SET_CODE_HASH: &str = r#"
+(module
+	(import "env" "ext_set_storage" (func $ext_set_storage (param i32 i32 i32 i32)))
+	(import "env" "ext_set_rent_allowance" (func $ext_set_rent_allowance (param i32 i32)))
+	(import "env" "ext_set_code_hash" (func $ext_set_code_hash (param i32 i32) (result i32)))
+	(import "env" "ext_scratch_size" (func $ext_scratch_size (result i32)))
+	(import "env" "ext_scratch_read" (func $ext_scratch_read (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	;; Replace the code with the code of the hash given as input
+	(func (export "call")
+		(call $ext_scratch_read
+			(i32.const 100)
+			(i32.const 0)
+			(i32.const 32)
+		)
+		(call $assert
+			(i32.eq
+				(call $ext_set_code_hash
+					(i32.const 100)
+					(i32.const 32)
+				)
+				(i32.const 0)
+			)
+		)
+	)
+
+	;; Set into storage a 4 bytes value
+	;; Set call set_rent_allowance with input
+	(func (export "deploy")
+		(local $input_size i32)
+		(set_local $input_size
+			(call $ext_scratch_size)
+		)
+		(call $ext_set_storage
+			(i32.const 0)
+			(i32.const 1)
+			(i32.const 0)
+			(i32.const 4)
+		)
+		(call $ext_scratch_read
+			(i32.const 0)
+			(i32.const 0)
+			(get_local $input_size)
+		)
+		(call $ext_set_rent_allowance
+			(i32.const 0)
+			(get_local $input_size)
+		)
+	)
+
+	;; Encoding of 10 in balance
+	(data (i32.const 0) "\28")
+)
+"#;
+
+#[test]
+fn set_code_hash_keeps_storage_and_balance() {
+	let (wasm, code_hash) = compile_module::<Test>(CODE_SET_CODE_HASH).unwrap();
+	let (set_rent_wasm, set_rent_code_hash) = compile_module::<Test>(CODE_SET_RENT).unwrap();
+
+	with_externalities(
+		&mut ExtBuilder::default().existential_deposit(50).build(),
+		|| {
+			Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, wasm));
+			assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, set_rent_wasm));
+
+			// Create `BOB` with a rent allowance of 0, so that it is evicted as soon as it owes rent.
+			assert_ok!(Contract::instantiate(
+				Origin::signed(ALICE),
+				30_000,
+				100_000,
+				code_hash.into(),
+				<Test as balances::Trait>::Balance::from(0u32).encode()
+			));
+			let bob_contract = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap();
+			let bob_balance = Balances::free_balance(&BOB);
+
+			// Replacing the code with code that was never stored fails and changes nothing.
+			assert!(Contract::call(
+				Origin::signed(ALICE),
+				BOB,
+				0,
+				100_000,
+				H256::repeat_byte(0xff).encode(),
+			).is_err());
+			assert_eq!(ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap(), bob_contract);
+
+			assert_ok!(Contract::call(
+				Origin::signed(ALICE),
+				BOB,
+				0,
+				100_000,
+				set_rent_code_hash.encode(),
+			));
+			assert!(System::events().contains(&EventRecord {
+				phase: Phase::ApplyExtrinsic(0),
+				event: MetaEvent::contract(RawEvent::CodeHashUpdated(BOB, set_rent_code_hash.into())),
+				topics: vec![],
+			}));
+
+			// Only the code has changed.
+			let upgraded_contract = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap();
+			assert_eq!(upgraded_contract.code_hash, set_rent_code_hash);
+			assert_eq!(upgraded_contract.trie_id, bob_contract.trie_id);
+			assert_eq!(upgraded_contract.storage_size, bob_contract.storage_size);
+			assert_eq!(upgraded_contract.rent_allowance, 0);
+			assert_eq!(upgraded_contract.deduct_block, bob_contract.deduct_block);
+			assert_eq!(Balances::free_balance(&BOB), bob_balance);
+
+			// The next call runs the new code, which accepts input the old code traps on.
+			assert_ok!(Contract::call(Origin::signed(ALICE), BOB, 0, 100_000, call::null()));
+		}
+	);
+}
+
+#[test]
+fn set_code_hash_is_committed_to_by_tombstone() {
+	let (wasm, code_hash) = compile_module::<Test>(CODE_SET_CODE_HASH).unwrap();
+	let (set_rent_wasm, set_rent_code_hash) = compile_module::<Test>(CODE_SET_RENT).unwrap();
+
+	with_externalities(
+		&mut ExtBuilder::default().existential_deposit(50).build(),
+		|| {
+			Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, wasm));
+			assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, set_rent_wasm));
+			assert_ok!(Contract::instantiate(
+				Origin::signed(ALICE),
+				30_000,
+				100_000,
+				code_hash.into(),
+				<Test as balances::Trait>::Balance::from(0u32).encode()
+			));
+			assert_ok!(Contract::call(
+				Origin::signed(ALICE),
+				BOB,
+				0,
+				100_000,
+				set_rent_code_hash.encode(),
+			));
+			let trie_id = ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap().trie_id;
+			let storage_root = runtime_io::child_storage_root(&trie_id);
+
+			// Advance 4 blocks, to the 5th.
+			System::initialize(&5, &[0u8; 32].into(), &[0u8; 32].into(), &Default::default());
+
+			// Call `BOB`, which makes it pay rent. Since the rent allowance is set to 0
+			// we expect that it will get removed leaving tombstone.
+			assert_err!(
+				Contract::call(Origin::signed(ALICE), BOB, 0, 100_000, call::null()),
+				"contract has been evicted"
+			);
+
+			// The tombstone is made from the new code hash, which a restoration must therefore use.
+			assert_eq!(
+				ContractInfoOf::<Test>::get(BOB).unwrap().get_tombstone().unwrap(),
+				TombstoneContractInfo::<Test>::new(&storage_root[..], set_rent_code_hash),
+			);
+		}
+	);
+}
+
 const CODE_RESTORATION: &str = r#"
 (module
 	(import "env" "ext_set_storage" (func $ext_set_storage (param i32 i32 i32 i32)))
@@ -2413,3 +2958,214 @@ fn check_block_gas_limit_works() {
 		}
 	);
 }
+
+/// A runtime which buys gas with a generic asset, rather than with `Currency`.
+mod separate_gas_currency {
+	use super::*;
+
+	impl_outer_event! {
+		pub enum MetaEvent for GasTest {
+			balances<T>, contract<T>, generic_asset<T>,
+		}
+	}
+	impl_outer_origin! {
+		pub enum Origin for GasTest { }
+	}
+	impl_outer_dispatch! {
+		pub enum Call for GasTest where origin: Origin {
+			balances::Balances,
+			contract::Contract,
+		}
+	}
+
+	#[derive(Clone, Eq, PartialEq, Debug)]
+	pub struct GasTest;
+	impl system::Trait for GasTest {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Call = ();
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type WeightMultiplierUpdate = ();
+		type Event = MetaEvent;
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type MaximumBlockLength = MaximumBlockLength;
+		type Version = ();
+		type Doughnut = MockDoughnut;
+		type DelegatedDispatchVerifier = ();
+	}
+	impl balances::Trait for GasTest {
+		type Balance = u64;
+		type OnFreeBalanceZero = Contract;
+		type OnNewAccount = ();
+		type Event = MetaEvent;
+		type TransactionPayment = ();
+		type DustRemoval = ();
+		type TransferPayment = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+		type TransactionBaseFee = BalancesTransactionBaseFee;
+		type TransactionByteFee = BalancesTransactionByteFee;
+		type WeightToFee = ();
+	}
+	impl generic_asset::Trait for GasTest {
+		type Balance = u64;
+		type AssetId = u32;
+		type Event = MetaEvent;
+		type DustRemoval = ();
+		type OnFreeBalanceZero = ();
+		type OnNewAccount = ();
+		type RestrictedTransferOrigin = system::EnsureSigned<u64, MockDoughnut>;
+		type MaxSymbolLength = MaxSymbolLength;
+		type MaxNameLength = MaxNameLength;
+	}
+	impl timestamp::Trait for GasTest {
+		type Moment = u64;
+		type OnTimestampSet = ();
+		type MinimumPeriod = MinimumPeriod;
+	}
+	impl Trait for GasTest {
+		type Currency = Balances;
+		type GasCurrency = generic_asset::SpendingAssetCurrency<GasTest>;
+		type MultiCurrency = GenericAsset;
+		type DoughnutAccount = MockDoughnutAccount;
+		type Time = Timestamp;
+		type Call = Call;
+		type DetermineContractAddress = DummyContractAddressFor;
+		type Event = MetaEvent;
+		type ComputeDispatchFee = DummyComputeDispatchFee;
+		type TrieIdGenerator = DummyTrieIdGenerator;
+		type GasPayment = ();
+		type SignedClaimHandicap = SignedClaimHandicap;
+		type TombstoneDeposit = TombstoneDeposit;
+		type StorageSizeOffset = StorageSizeOffset;
+		type RentByteFee = RentByteFee;
+		type RentDepositOffset = RentDepositOffset;
+		type SurchargeReward = SurchargeReward;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+		type TransactionBaseFee = TransactionBaseFee;
+		type TransactionByteFee = TransactionByteFee;
+		type ContractFee = ContractFee;
+		type CallBaseFee = CallBaseFee;
+		type InstantiateBaseFee = InstantiateBaseFee;
+		type MaxDepth = MaxDepth;
+		type MaxValueSize = MaxValueSize;
+		type BlockGasLimit = BlockGasLimit;
+	}
+
+	type Balances = balances::Module<GasTest>;
+	type GenericAsset = generic_asset::Module<GasTest>;
+	type Timestamp = timestamp::Module<GasTest>;
+	type Contract = Module<GasTest>;
+
+	pub struct DummyComputeDispatchFee;
+	impl ComputeDispatchFee<Call, u64> for DummyComputeDispatchFee {
+		fn compute_dispatch_fee(_call: &Call) -> u64 {
+			69
+		}
+	}
+
+	const GAS_ASSET: u32 = 16001;
+
+	/// Build the externalities of `builder` for `GasTest`, endowing `ALICE` with `gas` of the gas asset.
+	fn build(builder: ExtBuilder, gas: u64) -> runtime_io::TestExternalities<Blake2Hasher> {
+		builder.set_associated_consts();
+		let mut t = system::GenesisConfig::default().build_storage::<GasTest>().unwrap();
+		generic_asset::GenesisConfig::<GasTest> {
+			assets: vec![GAS_ASSET],
+			endowed_accounts: vec![ALICE],
+			initial_balance: gas,
+			next_asset_id: 17000,
+			staking_asset_id: 16000,
+			spending_asset_id: GAS_ASSET,
+			asset_meta: vec![],
+			vesting: vec![],
+		}.assimilate_storage(&mut t).unwrap();
+		GenesisConfig::<GasTest> {
+			current_schedule: Schedule {
+				enable_println: true,
+				..Default::default()
+			},
+			gas_price: builder.gas_price,
+		}.assimilate_storage(&mut t).unwrap();
+		runtime_io::TestExternalities::new(t)
+	}
+
+	#[test]
+	fn gas_is_bought_and_refunded_in_gas_currency() {
+		with_externalities(&mut build(ExtBuilder::default().gas_price(2), 1_000_000), || {
+			Balances::deposit_creating(&ALICE, 1_000);
+
+			assert_ok!(Contract::call(Origin::signed(ALICE), BOB, 50, 100_000, Vec::new()));
+
+			// the value is transferred in `Currency`, the gas is paid for in `GasCurrency`:
+			// 2 * 135 - gas price multiplied by the call base fee.
+			assert_eq!(Balances::free_balance(&ALICE), 950);
+			assert_eq!(Balances::free_balance(&BOB), 50);
+			assert_eq!(GenericAsset::free_balance(&GAS_ASSET, &ALICE), 1_000_000 - 2 * 135);
+			assert_eq!(GenericAsset::free_balance(&GAS_ASSET, &BOB), 0);
+			assert_eq!(GenericAsset::total_issuance(&GAS_ASSET), 1_000_000 - 2 * 135);
+		});
+	}
+
+	#[test]
+	fn gas_can_not_be_bought_with_currency() {
+		with_externalities(&mut build(ExtBuilder::default().gas_price(2), 0), || {
+			Balances::deposit_creating(&ALICE, 1_000_000);
+
+			assert!(Contract::call(Origin::signed(ALICE), BOB, 50, 100_000, Vec::new()).is_err());
+			assert_eq!(Balances::free_balance(&ALICE), 1_000_000);
+			assert_eq!(Balances::free_balance(&BOB), 0);
+		});
+	}
+
+	#[test]
+	fn transfer_fees_are_charged_in_gas_currency() {
+		with_externalities(
+			&mut build(ExtBuilder::default().gas_price(2).instantiation_fee(30).transfer_fee(10), 1_000_000),
+			|| {
+				Balances::deposit_creating(&ALICE, 1_000);
+
+				// creating BOB costs 30 / 2 gas on top of the call base fee.
+				assert_ok!(Contract::call(Origin::signed(ALICE), BOB, 50, 100_000, Vec::new()));
+				assert_eq!(GenericAsset::free_balance(&GAS_ASSET, &ALICE), 1_000_000 - 2 * (135 + 15));
+
+				// transferring to BOB costs 10 / 2 gas on top of the call base fee.
+				assert_ok!(Contract::call(Origin::signed(ALICE), BOB, 50, 100_000, Vec::new()));
+				assert_eq!(GenericAsset::free_balance(&GAS_ASSET, &ALICE), 1_000_000 - 2 * (135 + 15) - 2 * (135 + 5));
+
+				// only the transferred value is taken from `Currency`.
+				assert_eq!(Balances::free_balance(&ALICE), 900);
+				assert_eq!(Balances::free_balance(&BOB), 100);
+			},
+		);
+	}
+
+	#[test]
+	fn check_block_gas_limit_counts_gas_spent_in_gas_currency() {
+		with_externalities(
+			&mut build(ExtBuilder::default().gas_price(2).block_gas_limit(1_000), 1_000_000),
+			|| {
+				let info = DispatchInfo { weight: 100, class: DispatchClass::Normal };
+				let check = CheckBlockGasLimit::<GasTest>(Default::default());
+				let call: Call = crate::Call::call(BOB, 0, 900, Vec::new()).into();
+				assert_eq!(check.validate(&ALICE, &call, info, 0), Ok(Default::default()));
+
+				Balances::deposit_creating(&ALICE, 1_000);
+				assert_ok!(Contract::call(Origin::signed(ALICE), BOB, 50, 900, Vec::new()));
+				assert_eq!(Contract::gas_spent(), 135);
+
+				// 1_000 - 135 gas is left in the block.
+				assert_eq!(check.validate(&ALICE, &call, info, 0), InvalidTransaction::ExhaustsResources.into());
+			},
+		);
+	}
+}
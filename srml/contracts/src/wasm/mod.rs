@@ -90,6 +90,9 @@ impl<'a, T: Trait> crate::exec::Loader<T> for WasmLoader<'a> {
 			prefab_module,
 		})
 	}
+	fn code_len(&self, executable: &WasmExecutable) -> u32 {
+		executable.prefab_module.code.len() as u32
+	}
 }
 
 /// Implementation of `Vm` that takes `WasmExecutable` and executes it.
@@ -154,13 +157,16 @@ mod tests {
 	use primitives::H256;
 	use crate::exec::{Ext, StorageKey, ExecError, ExecReturnValue, STATUS_SUCCESS};
 	use crate::gas::{Gas, GasMeter};
-	use crate::tests::{Test, Call};
+	use crate::tests::{Test, Call, MockDoughnut};
 	use crate::wasm::prepare::prepare_contract;
 	use crate::CodeHash;
 	use wabt;
 	use hex_literal::hex;
 	use assert_matches::assert_matches;
 
+	/// The length of the code `MockExt` replaces the code of the contract with.
+	const MOCK_CODE_LEN: u32 = 10_000;
+
 	#[derive(Debug, PartialEq, Eq)]
 	struct DispatchEntry(Call);
 
@@ -188,17 +194,27 @@ mod tests {
 		gas_left: u64,
 	}
 
+	#[derive(Debug, PartialEq, Eq)]
+	struct AssetTransferEntry {
+		asset_id: u32,
+		to: u64,
+		value: u64,
+	}
+
 	#[derive(Default)]
 	pub struct MockExt {
 		storage: HashMap<StorageKey, Vec<u8>>,
 		rent_allowance: u64,
 		instantiates: Vec<InstantiateEntry>,
 		transfers: Vec<TransferEntry>,
+		asset_transfers: Vec<AssetTransferEntry>,
 		dispatches: Vec<DispatchEntry>,
 		restores: Vec<RestoreEntry>,
+		code_hashes: Vec<H256>,
 		// (topics, data)
 		events: Vec<(Vec<H256>, Vec<u8>)>,
 		next_account_id: u64,
+		doughnut: Option<MockDoughnut>,
 	}
 
 	impl Ext for MockExt {
@@ -265,9 +281,16 @@ mod tests {
 				delta,
 			});
 		}
+		fn set_code_hash(&mut self, code_hash: H256) -> Result<u32, &'static str> {
+			self.code_hashes.push(code_hash);
+			Ok(MOCK_CODE_LEN)
+		}
 		fn caller(&self) -> &u64 {
 			&42
 		}
+		fn doughnut(&self) -> Option<&MockDoughnut> {
+			self.doughnut.as_ref()
+		}
 		fn address(&self) -> &u64 {
 			&69
 		}
@@ -278,6 +301,22 @@ mod tests {
 			1337
 		}
 
+		fn asset_balance(&self, asset_id: &u32) -> u64 {
+			*asset_id as u64 * 10
+		}
+
+		fn asset_transfer(&mut self, asset_id: &u32, to: &u64, value: u64) -> Result<(), &'static str> {
+			if value > self.asset_balance(asset_id) {
+				return Err("balance too low to send value");
+			}
+			self.asset_transfers.push(AssetTransferEntry {
+				asset_id: *asset_id,
+				to: *to,
+				value,
+			});
+			Ok(())
+		}
+
 		fn now(&self) -> &u64 {
 			&1111
 		}
@@ -353,9 +392,15 @@ mod tests {
 				delta,
 			)
 		}
+		fn set_code_hash(&mut self, code_hash: H256) -> Result<u32, &'static str> {
+			(**self).set_code_hash(code_hash)
+		}
 		fn caller(&self) -> &u64 {
 			(**self).caller()
 		}
+		fn doughnut(&self) -> Option<&MockDoughnut> {
+			(**self).doughnut()
+		}
 		fn address(&self) -> &u64 {
 			(**self).address()
 		}
@@ -365,6 +410,12 @@ mod tests {
 		fn value_transferred(&self) -> u64 {
 			(**self).value_transferred()
 		}
+		fn asset_balance(&self, asset_id: &u32) -> u64 {
+			(**self).asset_balance(asset_id)
+		}
+		fn asset_transfer(&mut self, asset_id: &u32, to: &u64, value: u64) -> Result<(), &'static str> {
+			(**self).asset_transfer(asset_id, to, value)
+		}
 		fn now(&self) -> &u64 {
 			(**self).now()
 		}
@@ -745,6 +796,85 @@ mod tests {
 		).unwrap();
 	}
 
+	const CODE_DOUGHNUT: &str = r#"
+(module
+	(import "env" "ext_doughnut" (func $ext_doughnut (result i32)))
+	(import "env" "ext_scratch_size" (func $ext_scratch_size (result i32)))
+	(import "env" "ext_scratch_read" (func $ext_scratch_read (param i32 i32 i32)))
+	(import "env" "ext_return" (func $ext_return (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		(local $status i32)
+		(local $size i32)
+
+		;; fill the scratch buffer with the doughnut.
+		(set_local $status (call $ext_doughnut))
+		(set_local $size (call $ext_scratch_size))
+
+		;; assert the scratch buffer is empty if and only if there is no doughnut.
+		(call $assert
+			(i32.eq
+				(get_local $status)
+				(i32.eqz (get_local $size))
+			)
+		)
+
+		;; copy contents of the scratch buffer into the contract's memory.
+		(call $ext_scratch_read
+			(i32.const 8)		;; Pointer in memory to the place where to copy.
+			(i32.const 0)		;; Offset from the start of the scratch buffer.
+			(get_local $size)	;; Count of bytes to copy.
+		)
+
+		(call $ext_return
+			(i32.const 8)
+			(get_local $size)
+		)
+
+		(unreachable)
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn doughnut() {
+		let output = execute(
+			CODE_DOUGHNUT,
+			vec![],
+			MockExt {
+				doughnut: Some(MockDoughnut::new(42, 7, Some(vec![1, 2, 3]))),
+				..Default::default()
+			},
+			&mut GasMeter::with_limit(50_000, 1),
+		).unwrap();
+
+		assert_eq!(output.data, (42u64, 7u64, Some(vec![1u8, 2, 3])).encode());
+	}
+
+	#[test]
+	fn doughnut_missing() {
+		let output = execute(
+			CODE_DOUGHNUT,
+			vec![],
+			MockExt::default(),
+			&mut GasMeter::with_limit(50_000, 1),
+		).unwrap();
+
+		assert!(output.data.is_empty());
+	}
+
 	/// calls `ext_address`, loads the address from the scratch buffer and
 	/// compares it with the constant 69.
 	const CODE_ADDRESS: &str = r#"
@@ -867,6 +997,233 @@ mod tests {
 		).unwrap();
 	}
 
+	const CODE_ASSET_BALANCE: &str = r#"
+(module
+	(import "env" "ext_asset_balance" (func $ext_asset_balance (param i32 i32)))
+	(import "env" "ext_scratch_size" (func $ext_scratch_size (result i32)))
+	(import "env" "ext_scratch_read" (func $ext_scratch_read (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		;; This stores the balance of asset 16 in the scratch buffer
+		(call $ext_asset_balance
+			(i32.const 4)		;; Pointer to the asset id.
+			(i32.const 4)		;; Length of the asset id.
+		)
+
+		;; assert $ext_scratch_size == 8
+		(call $assert
+			(i32.eq
+				(call $ext_scratch_size)
+				(i32.const 8)
+			)
+		)
+
+		;; copy contents of the scratch buffer into the contract's memory.
+		(call $ext_scratch_read
+			(i32.const 8)		;; Pointer in memory to the place where to copy.
+			(i32.const 0)		;; Offset from the start of the scratch buffer.
+			(i32.const 8)		;; Count of bytes to copy.
+		)
+
+		;; assert that contents of the buffer is equal to the i64 value of 160.
+		(call $assert
+			(i64.eq
+				(i64.load
+					(i32.const 8)
+				)
+				(i64.const 160)
+			)
+		)
+	)
+	(func (export "deploy"))
+
+	;; The asset id, represented by u32 (4 bytes long) in little endian.
+	(data (i32.const 4) "\10\00\00\00")
+)
+"#;
+
+	#[test]
+	fn asset_balance() {
+		let mut gas_meter = GasMeter::with_limit(50_000, 1);
+		let _ = execute(
+			CODE_ASSET_BALANCE,
+			vec![],
+			MockExt::default(),
+			&mut gas_meter,
+		).unwrap();
+	}
+
+	const CODE_ASSET_TRANSFER: &str = r#"
+(module
+	;; ext_asset_transfer(
+	;;    asset_id_ptr: u32,
+	;;    asset_id_len: u32,
+	;;    dest_ptr: u32,
+	;;    dest_len: u32,
+	;;    value_ptr: u32,
+	;;    value_len: u32
+	;;) -> u32
+	(import "env" "ext_asset_transfer" (func $ext_asset_transfer (param i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		;; a transfer within the balance succeeds
+		(call $assert
+			(i32.eq
+				(call $ext_asset_transfer
+					(i32.const 4)	;; Pointer to the asset id.
+					(i32.const 4)	;; Length of the asset id.
+					(i32.const 8)	;; Pointer to the destination address.
+					(i32.const 8)	;; Length of the destination address.
+					(i32.const 16)	;; Pointer to the value to transfer.
+					(i32.const 8)	;; Length of the value to transfer.
+				)
+				(i32.const 0)
+			)
+		)
+
+		;; a transfer exceeding the balance fails
+		(call $assert
+			(i32.eq
+				(call $ext_asset_transfer
+					(i32.const 4)	;; Pointer to the asset id.
+					(i32.const 4)	;; Length of the asset id.
+					(i32.const 8)	;; Pointer to the destination address.
+					(i32.const 8)	;; Length of the destination address.
+					(i32.const 24)	;; Pointer to the value to transfer.
+					(i32.const 8)	;; Length of the value to transfer.
+				)
+				(i32.const 1)
+			)
+		)
+	)
+	(func (export "deploy"))
+
+	;; The asset id, represented by u32 (4 bytes long) in little endian.
+	(data (i32.const 4) "\10\00\00\00")
+	;; Destination AccountId to transfer the asset to.
+	;; Represented by u64 (8 bytes long) in little endian.
+	(data (i32.const 8) "\09\00\00\00\00\00\00\00")
+	;; Amounts of the asset to transfer.
+	;; Represented by u64 (8 bytes long) in little endian.
+	(data (i32.const 16) "\64\00\00\00\00\00\00\00")
+	(data (i32.const 24) "\c8\00\00\00\00\00\00\00")
+)
+"#;
+
+	#[test]
+	fn asset_transfer() {
+		let mut mock_ext = MockExt::default();
+		let _ = execute(
+			CODE_ASSET_TRANSFER,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::with_limit(50_000, 1),
+		).unwrap();
+
+		assert_eq!(
+			&mock_ext.asset_transfers,
+			&[AssetTransferEntry {
+				asset_id: 16,
+				to: 9,
+				value: 100,
+			}]
+		);
+	}
+
+	const CODE_SET_CODE_HASH: &str = r#"
+(module
+	;; ext_set_code_hash(code_hash_ptr: u32, code_hash_len: u32) -> u32
+	(import "env" "ext_set_code_hash" (func $ext_set_code_hash (param i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		(call $assert
+			(i32.eq
+				(call $ext_set_code_hash
+					(i32.const 4)	;; Pointer to the code hash.
+					(i32.const 32)	;; Length of the code hash.
+				)
+				(i32.const 0)
+			)
+		)
+	)
+	(func (export "deploy"))
+
+	;; The hash of the new code.
+	(data (i32.const 4)
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+	)
+)
+"#;
+
+	#[test]
+	fn set_code_hash() {
+		let mut mock_ext = MockExt::default();
+		let mut gas_meter = GasMeter::with_limit(50_000, 1);
+		let _ = execute(
+			CODE_SET_CODE_HASH,
+			vec![],
+			&mut mock_ext,
+			&mut gas_meter,
+		).unwrap();
+
+		assert_eq!(
+			&mock_ext.code_hashes,
+			&[H256::repeat_byte(0x11)]
+		);
+		// the base cost and the cost of loading the new code are charged.
+		let schedule = crate::Schedule::default();
+		assert!(
+			50_000 - gas_meter.gas_left() >=
+				schedule.set_code_hash_cost + schedule.set_code_hash_per_byte_cost * Gas::from(MOCK_CODE_LEN)
+		);
+	}
+
+	#[test]
+	fn set_code_hash_charges_by_code_length() {
+		// enough gas for the base cost, but not for loading the new code.
+		let mut gas_meter = GasMeter::with_limit(5_000, 1);
+		assert_matches!(
+			execute(
+				CODE_SET_CODE_HASH,
+				vec![],
+				MockExt::default(),
+				&mut gas_meter,
+			),
+			Err(ExecError { reason: "during execution", buffer: _ })
+		);
+	}
+
 	const CODE_GAS_PRICE: &str = r#"
 (module
 	(import "env" "ext_gas_price" (func $ext_gas_price))
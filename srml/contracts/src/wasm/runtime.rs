@@ -16,7 +16,9 @@
 
 //! Environment definition of the wasm smart-contract runtime.
 
-use crate::{Schedule, Trait, CodeHash, ComputeDispatchFee, BalanceOf};
+use crate::{
+	Schedule, Trait, CodeHash, ComputeDispatchFee, BalanceOf, AssetIdOf, AssetBalanceOf, CONTRACTS_DOMAIN,
+};
 use crate::exec::{
 	Ext, ExecResult, ExecError, ExecReturnValue, StorageKey, TopicOf, STATUS_SUCCESS,
 };
@@ -27,7 +29,7 @@ use rstd::prelude::*;
 use rstd::convert::TryInto;
 use rstd::mem;
 use codec::{Decode, Encode};
-use sr_primitives::traits::{Bounded, SaturatedConversion};
+use sr_primitives::traits::{Bounded, Convert, DoughnutApi, SaturatedConversion};
 
 /// The value returned from ext_call and ext_instantiate contract external functions if the call or
 /// instantiation traps. This value is chosen as if the execution does not trap, the return value
@@ -131,6 +133,14 @@ pub enum RuntimeToken {
 	/// (topic_count, data_bytes): A buffer of the given size is posted as an event indexed with the
 	/// given number of topics.
 	DepositEvent(u32, u32),
+	/// The balance of an asset is queried.
+	AssetBalance,
+	/// An asset is transferred.
+	AssetTransfer,
+	/// The code of the current contract is replaced.
+	SetCodeHash,
+	/// The code of the given number of bytes is loaded to replace the code of the current contract.
+	SetCodeHashLoad(u32),
 }
 
 impl<T: Trait> Token<T> for RuntimeToken {
@@ -169,6 +179,12 @@ impl<T: Trait> Token<T> for RuntimeToken {
 					)
 			},
 			ComputedDispatchFee(gas) => Some(gas),
+			AssetBalance => Some(metadata.asset_balance_cost),
+			AssetTransfer => Some(metadata.asset_transfer_cost),
+			SetCodeHash => Some(metadata.set_code_hash_cost),
+			SetCodeHashLoad(code_len) => metadata
+				.set_code_hash_per_byte_cost
+				.checked_mul(code_len.into()),
 		};
 
 		value.unwrap_or_else(|| Bounded::max_value())
@@ -546,6 +562,29 @@ define_env!(Env, <E: Ext>,
 		Ok(())
 	},
 
+	// Stores the doughnut the caller is acting under into the scratch buffer.
+	//
+	// Only the contract called or instantiated directly by a delegated origin is acting under its
+	// doughnut. Contracts further down the call stack are not.
+	//
+	// Returns 0 and fills the scratch buffer with the doughnut's issuer and holder, encoded as
+	// `T::AccountId`s, followed by the doughnut's "contracts" domain, encoded as an
+	// `Option<Vec<u8>>`. Returns 1 and clears the scratch buffer if there is no doughnut.
+	ext_doughnut(ctx) -> u32 => {
+		ctx.scratch_buf.clear();
+		if let Some(doughnut) = ctx.ext.doughnut() {
+			let to_account = <<E as Ext>::T as Trait>::DoughnutAccount::convert;
+			(
+				to_account(doughnut.issuer()),
+				to_account(doughnut.holder()),
+				doughnut.get_domain(CONTRACTS_DOMAIN),
+			).encode_to(&mut ctx.scratch_buf);
+			Ok(0)
+		} else {
+			Ok(1)
+		}
+	},
+
 	// Stores the address of the current contract into the scratch buffer.
 	ext_address(ctx) => {
 		ctx.scratch_buf.clear();
@@ -555,7 +594,7 @@ define_env!(Env, <E: Ext>,
 
 	// Stores the gas price for the current transaction into the scratch buffer.
 	//
-	// The data is encoded as T::Balance. The current contents of the scratch buffer are overwritten.
+	// The data is encoded as the balance of `T::GasCurrency`. The current contents of the scratch buffer are overwritten.
 	ext_gas_price(ctx) => {
 		ctx.scratch_buf.clear();
 		ctx.gas_meter.gas_price().encode_to(&mut ctx.scratch_buf);
@@ -589,6 +628,63 @@ define_env!(Env, <E: Ext>,
 		Ok(())
 	},
 
+	// Stores the balance of the current account in the given asset into the scratch buffer.
+	//
+	// - asset_id_ptr: a pointer to the id of the asset.
+	//   Should be decodable as an `AssetIdOf<T>`. Traps otherwise.
+	// - asset_id_len: length of the asset id buffer.
+	//
+	// The data is encoded as `AssetBalanceOf<T>`. The current contents of the scratch buffer are
+	// overwritten.
+	ext_asset_balance(ctx, asset_id_ptr: u32, asset_id_len: u32) => {
+		let asset_id: AssetIdOf<<E as Ext>::T> =
+			read_sandbox_memory_as(ctx, asset_id_ptr, asset_id_len)?;
+
+		charge_gas(ctx.gas_meter, ctx.schedule, RuntimeToken::AssetBalance)?;
+
+		ctx.scratch_buf.clear();
+		ctx.ext.asset_balance(&asset_id).encode_to(&mut ctx.scratch_buf);
+		Ok(())
+	},
+
+	// Transfer some amount of the given asset from the current account to another account.
+	//
+	// Returns 0 on success. If the transfer fails, e.g. as the balance is too low, then this
+	// returns 1 and no changes are made.
+	//
+	// - asset_id_ptr: a pointer to the id of the asset.
+	//   Should be decodable as an `AssetIdOf<T>`. Traps otherwise.
+	// - asset_id_len: length of the asset id buffer.
+	// - dest_ptr: a pointer to the address of the destination account.
+	//   Should be decodable as a `T::AccountId`. Traps otherwise.
+	// - dest_len: length of the address buffer.
+	// - value_ptr: a pointer to the buffer with value, how much of the asset to transfer.
+	//   Should be decodable as an `AssetBalanceOf<T>`. Traps otherwise.
+	// - value_len: length of the value buffer.
+	ext_asset_transfer(
+		ctx,
+		asset_id_ptr: u32,
+		asset_id_len: u32,
+		dest_ptr: u32,
+		dest_len: u32,
+		value_ptr: u32,
+		value_len: u32
+	) -> u32 => {
+		let asset_id: AssetIdOf<<E as Ext>::T> =
+			read_sandbox_memory_as(ctx, asset_id_ptr, asset_id_len)?;
+		let dest: <<E as Ext>::T as system::Trait>::AccountId =
+			read_sandbox_memory_as(ctx, dest_ptr, dest_len)?;
+		let value: AssetBalanceOf<<E as Ext>::T> =
+			read_sandbox_memory_as(ctx, value_ptr, value_len)?;
+
+		charge_gas(ctx.gas_meter, ctx.schedule, RuntimeToken::AssetTransfer)?;
+
+		match ctx.ext.asset_transfer(&asset_id, &dest, value) {
+			Ok(()) => Ok(0),
+			Err(_) => Ok(1),
+		}
+	},
+
 	// Stores the random number for the current block for the given subject into the scratch
 	// buffer.
 	//
@@ -644,6 +740,36 @@ define_env!(Env, <E: Ext>,
 		Ok(())
 	},
 
+	// Replace the code of the current contract with the code of the given hash.
+	//
+	// The storage, balance and rent allowance of the contract are kept, and so is its address.
+	// The currently executing code runs to completion; the new code is executed from the next
+	// call into the contract on. The replacement is reverted if this call fails.
+	//
+	// Returns 0 on success. If no code with the given hash has been stored, then this returns 1
+	// and no changes are made.
+	//
+	// Besides a base cost, gas is charged in proportion to the length of the new code, as it may
+	// have to be re-instrumented. If that runs out of gas, the call traps and the replacement is
+	// reverted.
+	//
+	// `code_hash_ptr`, `code_hash_len` - the pointer and the length of a buffer that encodes
+	// the hash of the new code.
+	ext_set_code_hash(ctx, code_hash_ptr: u32, code_hash_len: u32) -> u32 => {
+		let code_hash: CodeHash<<E as Ext>::T> =
+			read_sandbox_memory_as(ctx, code_hash_ptr, code_hash_len)?;
+
+		charge_gas(ctx.gas_meter, ctx.schedule, RuntimeToken::SetCodeHash)?;
+
+		match ctx.ext.set_code_hash(code_hash) {
+			Ok(code_len) => {
+				charge_gas(ctx.gas_meter, ctx.schedule, RuntimeToken::SetCodeHashLoad(code_len))?;
+				Ok(0)
+			},
+			Err(_) => Ok(1),
+		}
+	},
+
 	// Record a request to restore the caller contract to the specified contract.
 	//
 	// At the finalization stage, i.e. when all changes from the extrinsic that invoked this
@@ -303,7 +303,7 @@ mod tests {
 	};
 	use support::{
 		impl_outer_event, impl_outer_origin, parameter_types, impl_outer_dispatch,
-		additional_traits::{DelegatedDispatchVerifier},
+		additional_traits::{DelegatedDispatchVerifier, DispatchArg},
 		traits::{Currency, LockIdentifier, LockableCurrency, Time, WithdrawReasons, WithdrawReason},
 	};
 	use system::Call as SystemCall;
@@ -337,6 +337,7 @@ mod tests {
 			_doughnut: &T::Doughnut,
 			_module: &str,
 			_method: &str,
+			_args: &[DispatchArg],
 		) -> Result<(), &'static str> {
 			Ok(())
 		}
@@ -381,6 +382,10 @@ mod tests {
 		type Call = <Self as system::Trait>::Call;
 		type Doughnut = <Self as system::Trait>::Doughnut;
 		type TimestampProvider = TimestampProvider;
+		type RevocationRegistry = ();
+		type UsageTracker = ();
+		type CallSpend = ();
+		type MaxChainLength = ();
 	}
 	parameter_types! {
 		pub const ExistentialDeposit: u64 = 0;
@@ -17,20 +17,23 @@
 //!
 #![cfg(test)]
 use balances::Call as BalancesCall;
-use codec::Encode;
+use codec::{Decode, Encode};
 use keyring::AccountKeyring;
 use primitives::{sr25519::{self}, H256, Blake2Hasher};
-use prml_doughnut::{DoughnutRuntime, PlugDoughnut};
+use prml_doughnut::{DoughnutRuntime, PlugDomain, PlugDoughnut, UsageLimits};
 use runtime_io::with_externalities;
 use sr_primitives::{
-	ApplyError, DispatchError, DoughnutV0,
+	ApplyError, ApplyResult, DispatchError, DoughnutV0,
 	generic::{self, Era}, Perbill, testing::{Block, Digest, Header},
 	traits::{IdentityLookup, Header as HeaderT, BlakeTwo256, ValidateUnsigned, Verify, ConvertInto, DoughnutApi},
 	transaction_validity::{InvalidTransaction, TransactionValidity, TransactionValidityError, UnknownTransaction},
 };
 use support::{
 	impl_outer_event, impl_outer_origin, parameter_types, impl_outer_dispatch,
-	additional_traits::{DelegatedDispatchVerifier},
+	additional_traits::{
+		DelegatedCallSpend, DelegatedDispatchVerifier, DispatchArg, DoughnutRevocationRegistry, DoughnutUsageTracker,
+		VerifyDelegatedDispatch,
+	},
 	traits::{Currency, Time},
 };
 
@@ -63,14 +66,41 @@ impl<T: system::Trait> DelegatedDispatchVerifier<T::Doughnut> for MockDelegatedD
 	fn verify_dispatch(
 		doughnut: &T::Doughnut,
 		_module: &str,
-		_method: &str,
+		method: &str,
+		args: &[DispatchArg],
 	) -> Result<(), &'static str> {
 		// Check the "test" domain has a byte set to `1` for Ok, fail otherwise
 		let verify = doughnut.get_domain(Self::DOMAIN).unwrap()[0];
-		if verify == 1 {
-			Ok(())
-		} else {
-			Err("dispatch unverified")
+		if verify != 1 {
+			return Err("dispatch unverified");
+		}
+		// Transfers are limited to 100 units
+		if method == "transfer" {
+			let value = args.iter().find(|arg| arg.name == "value" && arg.ty == "T::Balance").ok_or("value arg missing")?;
+			if u64::decode(&mut &value.value[..]).map_err(|_| "value arg malformed")? > 100 {
+				return Err("transfer value exceeds limit");
+			}
+		}
+		Ok(())
+	}
+}
+
+// Doughnuts issued to Dave are considered revoked
+pub struct MockRevocationRegistry;
+impl DoughnutRevocationRegistry<AccountId> for MockRevocationRegistry {
+	fn is_revoked<Doughnut: DoughnutApi<PublicKey=AccountId> + Encode>(doughnut: &Doughnut) -> bool {
+		let dave: AccountId = AccountKeyring::Dave.into();
+		doughnut.holder() == dave
+	}
+}
+
+// Balances transfers spend asset id `0`, the spend of other calls is not accounted
+pub struct MockCallSpend;
+impl DelegatedCallSpend<Call> for MockCallSpend {
+	fn spend(call: &Call) -> Option<Vec<(u64, u128)>> {
+		match call {
+			Call::Balances(BalancesCall::transfer(_, value)) => Some(vec![(0, u128::from(*value))]),
+			_ => None,
 		}
 	}
 }
@@ -120,7 +150,12 @@ impl DoughnutRuntime for Runtime {
 	type Call = <Self as system::Trait>::Call;
 	type Doughnut = <Self as system::Trait>::Doughnut;
 	type TimestampProvider = TimestampProvider;
+	type RevocationRegistry = MockRevocationRegistry;
+	type UsageTracker = prml_doughnut_usage::Module<Runtime>;
+	type CallSpend = MockCallSpend;
+	type MaxChainLength = ();
 }
+impl prml_doughnut_usage::Trait for Runtime {}
 parameter_types! {
 	pub const ExistentialDeposit: u64 = 0;
 	pub const TransferFee: u64 = 0;
@@ -224,6 +259,36 @@ fn make_doughnut(issuer: AccountId, holder: AccountId, not_before: Option<u32>,
 	doughnut
 }
 
+/// Create a valid `DoughnutV0` given an `issuer` and `holder` with `limits` on its use
+fn make_limited_doughnut(issuer: AccountId, holder: AccountId, limits: UsageLimits) -> DoughnutV0 {
+	let issuer_key = AccountKeyring::from_public(&issuer).unwrap();
+	let mut doughnut = make_doughnut(issuer, holder, None, None, true);
+	doughnut.domains.push(("plug".to_string(), PlugDomain::new().limits(limits).encode()));
+	doughnut.signature = issuer_key.sign(&doughnut.payload()).into();
+	doughnut
+}
+
+/// Create a valid `DoughnutV0` given an `issuer` and `holder` where the issuer pays transaction fees
+fn make_sponsored_doughnut(issuer: AccountId, holder: AccountId) -> DoughnutV0 {
+	let issuer_key = AccountKeyring::from_public(&issuer).unwrap();
+	let mut doughnut = make_doughnut(issuer, holder, None, None, true);
+	doughnut.domains.push(("plug".to_string(), PlugDomain::new().issuer_pays_fees().encode()));
+	doughnut.signature = issuer_key.sign(&doughnut.payload()).into();
+	doughnut
+}
+
+/// Apply a balances transfer from `holder` under `doughnut` authority
+fn apply_delegated_transfer(holder: &AccountId, nonce: Index, doughnut: &PlugDoughnut<DoughnutV0, Runtime>, value: u64) -> ApplyResult {
+	let xt = CheckedExtrinsic {
+		signed: Some((
+			holder.clone(),
+			signed_extra(nonce, 0, Some(doughnut.clone())),
+		)),
+		function: Call::Balances(BalancesCall::transfer(AccountKeyring::Charlie.into(), value)),
+	};
+	Executive::apply_extrinsic(sign_extrinsic(xt))
+}
+
 // TODO: These tests are very repitious, could be DRYed up with macros
 #[test]
 fn delegated_dispatch_works() {
@@ -422,6 +487,50 @@ fn delegated_dispatch_fails_when_doughnut_is_premature() {
 	});
 }
 
+#[test]
+fn delegated_dispatch_fails_when_doughnut_is_revoked() {
+	let issuer_alice: AccountId = AccountKeyring::Alice.into();
+	let holder_dave: AccountId = AccountKeyring::Dave.into();
+	let receiver_charlie: AccountId = AccountKeyring::Charlie.into();
+
+	let mut t = system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	balances::GenesisConfig::<Runtime> {
+		balances: vec![(issuer_alice.clone(), 10_011), (holder_dave.clone(), 10_011)],
+		vesting: vec![],
+	}.assimilate_storage(&mut t).unwrap();
+
+	let doughnut = PlugDoughnut::<DoughnutV0, Runtime>::new(
+		make_doughnut(
+			issuer_alice.clone(),
+			holder_dave.clone(),
+			None,
+			None,
+			true,
+		)
+	);
+
+	let mut t = runtime_io::TestExternalities::<Blake2Hasher>::new(t);
+	with_externalities(&mut t, || {
+		let xt = CheckedExtrinsic {
+			signed: Some((
+				holder_dave.clone(),
+				signed_extra(0, 0, Some(doughnut)),
+			)),
+			function: Call::Balances(BalancesCall::transfer(receiver_charlie.clone().into(), 69)),
+		};
+		let uxt = sign_extrinsic(xt);
+		Executive::initialize_block(&Header::new(
+			1,
+			H256::default(),
+			H256::default(),
+			[69u8; 32].into(),
+			Digest::default(),
+		));
+		let r = Executive::apply_extrinsic(uxt);
+		assert_eq!(r, Err(ApplyError::Validity(TransactionValidityError::Invalid(InvalidTransaction::Custom(172)))));
+	});
+}
+
 #[test]
 fn delegated_dispatch_fails_when_doughnut_domain_permission_is_unverified() {
 	let issuer_alice: AccountId = AccountKeyring::Alice.into();
@@ -464,4 +573,244 @@ fn delegated_dispatch_fails_when_doughnut_domain_permission_is_unverified() {
 		let r = Executive::apply_extrinsic(uxt);
 		assert_eq!(r, Ok(Err(DispatchError { module: Some(1), error: 0, message: Some("dispatch unverified") })));
 	});
-}
\ No newline at end of file
+}
+
+#[test]
+fn delegated_dispatch_verifier_receives_call_arguments() {
+	let issuer_alice: AccountId = AccountKeyring::Alice.into();
+	let holder_bob: AccountId = AccountKeyring::Bob.into();
+	let receiver_charlie: AccountId = AccountKeyring::Charlie.into();
+
+	let mut t = system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	balances::GenesisConfig::<Runtime> {
+		balances: vec![(issuer_alice.clone(), 10_011), (holder_bob.clone(), 10_011)],
+		vesting: vec![],
+	}.assimilate_storage(&mut t).unwrap();
+
+	let doughnut = PlugDoughnut::<DoughnutV0, Runtime>::new(
+		make_doughnut(
+			issuer_alice.clone(),
+			holder_bob.clone(),
+			None,
+			None,
+			true,
+		)
+	);
+
+	let mut t = runtime_io::TestExternalities::<Blake2Hasher>::new(t);
+	with_externalities(&mut t, || {
+		let xt = CheckedExtrinsic {
+			signed: Some((
+				holder_bob.clone(),
+				signed_extra(0, 0, Some(doughnut)),
+			)),
+			// The mock verifier limits transfers to 100 units
+			function: Call::Balances(BalancesCall::transfer(receiver_charlie.clone().into(), 101)),
+		};
+		let uxt = sign_extrinsic(xt);
+		Executive::initialize_block(&Header::new(
+			1,
+			H256::default(),
+			H256::default(),
+			[69u8; 32].into(),
+			Digest::default(),
+		));
+		let r = Executive::apply_extrinsic(uxt);
+		assert_eq!(r, Ok(Err(DispatchError { module: Some(1), error: 0, message: Some("transfer value exceeds limit") })));
+		assert_eq!(<balances::Module<Runtime>>::total_balance(&receiver_charlie), 0);
+	});
+}
+
+#[test]
+fn call_verifies_delegated_dispatch_without_dispatching() {
+	let issuer_alice: AccountId = AccountKeyring::Alice.into();
+	let holder_bob: AccountId = AccountKeyring::Bob.into();
+	let receiver_charlie: AccountId = AccountKeyring::Charlie.into();
+
+	let doughnut = PlugDoughnut::<DoughnutV0, Runtime>::new(
+		make_doughnut(issuer_alice.clone(), holder_bob.clone(), None, None, true)
+	);
+	let unverified_doughnut = PlugDoughnut::<DoughnutV0, Runtime>::new(
+		make_doughnut(issuer_alice.clone(), holder_bob.clone(), None, None, false)
+	);
+
+	let call = Call::Balances(BalancesCall::transfer(receiver_charlie.clone(), 100));
+	assert_eq!(call.verify_delegated_dispatch(&doughnut), Ok(()));
+	assert_eq!(call.verify_delegated_dispatch(&unverified_doughnut), Err("dispatch unverified"));
+	// The call arguments are passed to the verifier as they are on dispatch
+	let call = Call::Balances(BalancesCall::transfer(receiver_charlie.clone(), 101));
+	assert_eq!(call.verify_delegated_dispatch(&doughnut), Err("transfer value exceeds limit"));
+}
+
+#[test]
+fn delegated_dispatch_fails_when_doughnut_max_uses_is_exceeded() {
+	let issuer_alice: AccountId = AccountKeyring::Alice.into();
+	let holder_bob: AccountId = AccountKeyring::Bob.into();
+
+	let mut t = system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	balances::GenesisConfig::<Runtime> {
+		balances: vec![(issuer_alice.clone(), 10_011), (holder_bob.clone(), 10_011)],
+		vesting: vec![],
+	}.assimilate_storage(&mut t).unwrap();
+
+	let doughnut = PlugDoughnut::<DoughnutV0, Runtime>::new(
+		make_limited_doughnut(issuer_alice.clone(), holder_bob.clone(), UsageLimits::new().max_uses(1))
+	);
+
+	let mut t = runtime_io::TestExternalities::<Blake2Hasher>::new(t);
+	with_externalities(&mut t, || {
+		Executive::initialize_block(&Header::new(
+			1,
+			H256::default(),
+			H256::default(),
+			[69u8; 32].into(),
+			Digest::default(),
+		));
+		assert_eq!(apply_delegated_transfer(&holder_bob, 0, &doughnut, 10), Ok(Ok(())));
+		assert_eq!(
+			apply_delegated_transfer(&holder_bob, 1, &doughnut, 10),
+			Err(ApplyError::Validity(TransactionValidityError::Invalid(InvalidTransaction::Custom(173)))),
+		);
+	});
+}
+
+#[test]
+fn delegated_dispatch_fails_when_doughnut_max_spend_is_exceeded() {
+	let issuer_alice: AccountId = AccountKeyring::Alice.into();
+	let holder_bob: AccountId = AccountKeyring::Bob.into();
+
+	let mut t = system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	balances::GenesisConfig::<Runtime> {
+		balances: vec![(issuer_alice.clone(), 10_011), (holder_bob.clone(), 10_011)],
+		vesting: vec![],
+	}.assimilate_storage(&mut t).unwrap();
+
+	let doughnut = PlugDoughnut::<DoughnutV0, Runtime>::new(
+		make_limited_doughnut(issuer_alice.clone(), holder_bob.clone(), UsageLimits::new().max_spend(0, 100))
+	);
+
+	let mut t = runtime_io::TestExternalities::<Blake2Hasher>::new(t);
+	with_externalities(&mut t, || {
+		Executive::initialize_block(&Header::new(
+			1,
+			H256::default(),
+			H256::default(),
+			[69u8; 32].into(),
+			Digest::default(),
+		));
+		assert_eq!(apply_delegated_transfer(&holder_bob, 0, &doughnut, 60), Ok(Ok(())));
+		assert_eq!(
+			apply_delegated_transfer(&holder_bob, 1, &doughnut, 60),
+			Err(ApplyError::Validity(TransactionValidityError::Invalid(InvalidTransaction::Custom(174)))),
+		);
+		assert_eq!(apply_delegated_transfer(&holder_bob, 1, &doughnut, 40), Ok(Ok(())));
+		assert_eq!(<balances::Module<Runtime>>::total_balance(&issuer_alice), 10_011 - 100);
+	});
+}
+
+#[test]
+fn delegated_dispatch_fails_when_doughnut_max_spend_is_set_and_call_spend_is_not_accounted() {
+	let issuer_alice: AccountId = AccountKeyring::Alice.into();
+	let holder_bob: AccountId = AccountKeyring::Bob.into();
+
+	let mut t = system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	balances::GenesisConfig::<Runtime> {
+		balances: vec![(issuer_alice.clone(), 10_011), (holder_bob.clone(), 10_011)],
+		vesting: vec![],
+	}.assimilate_storage(&mut t).unwrap();
+
+	let doughnut = PlugDoughnut::<DoughnutV0, Runtime>::new(
+		make_limited_doughnut(issuer_alice.clone(), holder_bob.clone(), UsageLimits::new().max_spend(0, 100))
+	);
+
+	let mut t = runtime_io::TestExternalities::<Blake2Hasher>::new(t);
+	with_externalities(&mut t, || {
+		Executive::initialize_block(&Header::new(
+			1,
+			H256::default(),
+			H256::default(),
+			[69u8; 32].into(),
+			Digest::default(),
+		));
+		let xt = CheckedExtrinsic {
+			signed: Some((holder_bob.clone(), signed_extra(0, 0, Some(doughnut.clone())))),
+			function: Call::Balances(BalancesCall::force_transfer(issuer_alice.clone(), holder_bob.clone(), 60)),
+		};
+		assert_eq!(
+			Executive::apply_extrinsic(sign_extrinsic(xt)),
+			Err(ApplyError::Validity(TransactionValidityError::Invalid(InvalidTransaction::Custom(177)))),
+		);
+		assert_eq!(apply_delegated_transfer(&holder_bob, 0, &doughnut, 60), Ok(Ok(())));
+	});
+}
+
+#[test]
+fn failed_delegated_dispatch_does_not_count_against_doughnut_limits() {
+	let issuer_alice: AccountId = AccountKeyring::Alice.into();
+	let holder_bob: AccountId = AccountKeyring::Bob.into();
+
+	let mut t = system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	balances::GenesisConfig::<Runtime> {
+		balances: vec![(issuer_alice.clone(), 10_011), (holder_bob.clone(), 10_011)],
+		vesting: vec![],
+	}.assimilate_storage(&mut t).unwrap();
+
+	let doughnut = PlugDoughnut::<DoughnutV0, Runtime>::new(
+		make_limited_doughnut(issuer_alice.clone(), holder_bob.clone(), UsageLimits::new().max_uses(1).max_spend(0, 200))
+	);
+
+	let mut t = runtime_io::TestExternalities::<Blake2Hasher>::new(t);
+	with_externalities(&mut t, || {
+		Executive::initialize_block(&Header::new(
+			1,
+			H256::default(),
+			H256::default(),
+			[69u8; 32].into(),
+			Digest::default(),
+		));
+		// The mock verifier limits transfers to 100 units, so this dispatch fails
+		assert_eq!(
+			apply_delegated_transfer(&holder_bob, 0, &doughnut, 101),
+			Ok(Err(DispatchError { module: Some(1), error: 0, message: Some("transfer value exceeds limit") })),
+		);
+		assert_eq!(<prml_doughnut_usage::Module<Runtime> as DoughnutUsageTracker>::uses(&doughnut), 0);
+		assert_eq!(<prml_doughnut_usage::Module<Runtime> as DoughnutUsageTracker>::spent(&doughnut, 0), 0);
+		assert_eq!(apply_delegated_transfer(&holder_bob, 1, &doughnut, 100), Ok(Ok(())));
+		assert_eq!(<prml_doughnut_usage::Module<Runtime> as DoughnutUsageTracker>::uses(&doughnut), 1);
+		assert_eq!(<prml_doughnut_usage::Module<Runtime> as DoughnutUsageTracker>::spent(&doughnut, 0), 100);
+	});
+}
+
+#[test]
+fn delegated_dispatch_fees_are_paid_by_issuer_when_sponsored() {
+	let issuer_alice: AccountId = AccountKeyring::Alice.into();
+	let holder_bob: AccountId = AccountKeyring::Bob.into();
+	let receiver_charlie: AccountId = AccountKeyring::Charlie.into();
+
+	let mut t = system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	balances::GenesisConfig::<Runtime> {
+		balances: vec![(issuer_alice.clone(), 10_011), (holder_bob.clone(), 10_011)],
+		vesting: vec![],
+	}.assimilate_storage(&mut t).unwrap();
+
+	let doughnut = PlugDoughnut::<DoughnutV0, Runtime>::new(
+		make_sponsored_doughnut(issuer_alice.clone(), holder_bob.clone())
+	);
+
+	let mut t = runtime_io::TestExternalities::<Blake2Hasher>::new(t);
+	with_externalities(&mut t, || {
+		Executive::initialize_block(&Header::new(
+			1,
+			H256::default(),
+			H256::default(),
+			[69u8; 32].into(),
+			Digest::default(),
+		));
+		assert_eq!(apply_delegated_transfer(&holder_bob, 0, &doughnut, 69), Ok(Ok(())));
+		assert_eq!(<balances::Module<Runtime>>::total_balance(&issuer_alice), 10_011 - 69 - 10 - 1024); // fees deducted
+		assert_eq!(<balances::Module<Runtime>>::total_balance(&holder_bob), 10_011); // no fees
+		assert_eq!(<balances::Module<Runtime>>::total_balance(&receiver_charlie), 69);
+		// The holder's nonce is still used
+		assert_eq!(<system::Module<Runtime>>::account_nonce(&holder_bob), 1);
+	});
+}
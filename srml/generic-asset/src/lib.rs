@@ -33,6 +33,13 @@
 //! - Slashing an account balance.
 //! - Managing total issuance.
 //! - Setting and managing locks.
+//! - Registering asset metadata.
+//! - Approving other accounts to transfer some of an account's balance.
+//! - Reaping accounts whose balance of an asset falls below its existential deposit.
+//! - Freezing assets and accounts, and restricting who may receive an asset.
+//! - Making several transfers atomically in a single batch.
+//! - Vesting an account's balance of an asset over time.
+//! - Auditing the total issuance of an asset against the balances of its holders.
 //!
 //! ### Terminology
 //!
@@ -41,6 +48,10 @@
 //! - **Permissions:** A set of rules for a kind of asset, defining the allowed operations to the asset, and which
 //! accounts are allowed to possess it.
 //! - **Total Issuance:** The total number of units in existence in a system.
+//! - **Allowance:** An amount of an account's balance of an asset that another account, the spender, has been approved
+//! to transfer on its behalf. Transfers by the spender are subject to the same locks as transfers by the owner.
+//! - **Existential Deposit:** The minimum balance an account may hold of an asset, taken from the asset's metadata.
+//! Assets without an existential deposit never have their accounts reaped.
 //! - **Free Balance:** The portion of a balance that is not reserved. The free balance is the only balance that matters
 //! for most operations. When this balance falls below the existential deposit, most functionality of the account is
 //! removed. When both it and the reserved balance are deleted, then the account is said to be dead.
@@ -52,8 +63,17 @@
 //! (i.e. a difference between total issuance and account balances). Functions that result in an imbalance will
 //! return an object of the `Imbalance` trait that can be managed within your runtime logic. (If an imbalance is
 //! simply dropped, it should automatically maintain any book-keeping such as total issuance.)
-//! - **Lock:** A freeze on a specified amount of an account's free balance until a specified block number. Multiple
-//! locks always operate over the same funds, so they "overlay" rather than "stack".
+//! - **Lock:** A freeze on a specified amount of an account's free balance of an asset kind until a specified block
+//! number. Multiple locks on the same asset always operate over the same funds, so they "overlay" rather than
+//! "stack". Locks on one asset never restrict withdrawals of another.
+//! - **Vesting:** A lock on an account's balance of an asset, which is reduced linearly over a number of blocks. The
+//! amount that has vested is released by calling `vest`.
+//! - **Freeze:** A suspension of all withdrawals of an asset, or of one account's balance of an asset. Frozen balances
+//! cannot be transferred or withdrawn, e.g. to pay fees. An asset may also be restricted, so only the accounts on its
+//! allow-list may receive it. Independently, its senders may be restricted, so it may only be sent by origins accepted
+//! by the `RestrictedTransferOrigin`, e.g. accounts holding a KYC attestation.
+//! - **Asset Info:** Metadata describing how an asset is presented to users: its symbol, number of decimal places,
+//! display name and an optional existential deposit.
 //!
 //! ### Implementations
 //!
@@ -71,28 +91,73 @@
 //! - `StakingAssetCurrency`: Currency for staking.
 //! - `SpendingAssetCurrency`: Currency for payments such as transfer fee, gas fee.
 //!
+//! The `Module` itself implements the following traits, which take the asset as an argument so that modules can
+//! handle any number of assets.
+//!
+//! - `MultiCurrency`: Functions for dealing with a fungible multi-asset system.
+//! - `MultiReservableCurrency`: Functions for dealing with assets that can be reserved from an account.
+//! - `MultiLockableCurrency`: Functions for dealing with accounts that allow liquidity restrictions on an asset.
+//!
 //! ## Interface
 //!
 //! ### Dispatchable Functions
 //!
 //! - `create`: Create a new kind of asset.
 //! - `transfer`: Transfer some liquid free balance to another account.
+//! - `batch_transfer`: Transfer some liquid free balance of any assets to several accounts. Either all of the
+//! transfers are made or none are.
 //! - `update_permission`: Updates permission for a given `asset_id` and an account. The origin of this call
 //! must have update permissions.
 //! - `mint`: Mint an asset, increases its total issuance. The origin of this call must have mint permissions.
 //! - `burn`: Burn an asset, decreases its total issuance. The origin of this call must have burn permissions.
 //! - `create_reserved`: Create a new kind of reserved asset. The origin of this call must be root.
+//! - `update_asset_info`: Updates the metadata for a given `asset_id`. The origin of this call must have update
+//! permissions.
+//! - `approve`: Approve a spender to transfer up to an amount of the origin's balance of an asset.
+//! - `transfer_from`: Transfer some liquid free balance of another account, consuming the origin's allowance.
+//! - `update_freeze_permission`: Updates who may freeze a given `asset_id` and restrict its transfers. The origin of
+//! this call must have update permissions.
+//! - `freeze_asset`: Freezes or thaws all withdrawals of a given `asset_id`. The origin of this call must have freeze
+//! permissions.
+//! - `freeze_account`: Freezes or thaws withdrawals of an account's balance of a given `asset_id`. The origin of this
+//! call must have freeze permissions.
+//! - `restrict_transfers`: Restricts a given `asset_id` to be received only by accounts on its allow-list. The origin
+//! of this call must have freeze permissions.
+//! - `restrict_senders`: Restricts a given `asset_id` to be sent only by origins accepted by the
+//! `RestrictedTransferOrigin`. The origin of this call must have freeze permissions.
+//! - `set_allowed`: Adds or removes an account from the allow-list of a given `asset_id`. The origin of this call must
+//! have freeze permissions.
+//! - `vest`: Releases the origin's balance of an asset which has vested.
+//! - `set_vesting_schedule`: Sets the vesting schedule of an account's balance of an asset. The origin of this call
+//! must be root.
+//! - `backfill_holders`: Indexes a page of the accounts with a balance of an asset which predate the index of its
+//! holders, so their balances are audited. The origin of this call must be root.
 //!
 //! ### Public Functions
 //!
 //! - `total_balance`: Get an account's total balance of an asset kind.
 //! - `free_balance`: Get an account's free balance of an asset kind.
 //! - `reserved_balance`: Get an account's reserved balance of an asset kind.
+//! - `locks`: Get the liquidity locks on an account's balance of an asset kind.
+//! - `existential_deposit`: Get the existential deposit of an asset kind.
+//! - `allowance`: Get the amount of an account's balance of an asset kind that a spender may transfer.
+//! - `can_receive`: Check if an account is allowed to receive an asset kind.
+//! - `vesting_balance`: Get the amount of an account's balance of an asset kind that is still being vested.
+//! - `holders`: Get the accounts which have a balance of an asset kind.
+//! - `audit_issuance`: Get the total issuance of an asset kind alongside the sums of its free and reserved balances.
+//! - `issuance_discrepancies`: Get the audits of all asset kinds whose total issuance does not match their balances.
+//! - `try_state`: Check the total issuance of every asset kind matches its balances, for use in tests.
+//! - `registered_assets`: Get the ids and metadata of all assets with registered metadata.
 //! - `create_asset`: Creates an asset.
 //! - `make_transfer`: Transfer some liquid free balance from one account to another.
 //! This will not emit the `Transferred` event.
 //! - `make_transfer_with_event`: Transfer some liquid free balance from one account to another.
 //! This will emit the `Transferred` event.
+//! - `withdraw_and_burn`: Withdraw some liquid free balance from an account and burn it, decreasing the total
+//! issuance of the asset.
+//! - `withdraw_into_imbalance`: Withdraw some liquid free balance from an account, returning an imbalance of
+//! the withdrawn funds.
+//! - `resolve_imbalance`: Credit the funds of an imbalance to the free balance of an account.
 //! - `reserve`: Moves an amount from free balance to reserved balance.
 //! - `unreserve`: Move up to an amount from reserved balance to free balance. This function cannot fail.
 //! - `slash`: Deduct up to an amount from the combined balance of `who`, preferring to deduct from the
@@ -101,7 +166,7 @@
 //! - `slash_reserved`: Deduct up to an amount from reserved balance of an account. This function cannot fail.
 //! - `repatriate_reserved`: Move up to an amount from reserved balance of an account to free balance of another
 //! account.
-//! - `check_permission`: Check permission to perform burn, mint or update.
+//! - `check_permission`: Check permission to perform burn, mint, update or freeze.
 //! - `ensure_can_withdraw`: Check if the account is able to make a withdrawal of the given amount
 //!	for the given reason.
 //!
@@ -152,28 +217,34 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Decode, Encode, HasCompact, Input, Output, Error};
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
 
 use sr_primitives::traits::{
-	CheckedAdd, CheckedSub, MaybeSerializeDebug, Member, One, Saturating, SimpleArithmetic, Zero, Bounded
+	CheckedAdd, CheckedMul, CheckedSub, MaybeSerializeDebug, Member, One, Saturating, SimpleArithmetic, Zero, Bounded,
+	UniqueSaturatedFrom, UniqueSaturatedInto, EnsureOrigin,
 };
+use sr_primitives::weights::{ClassifyDispatch, DispatchClass, SimpleDispatchInfo, WeighData, Weight};
 
 use rstd::prelude::*;
 use rstd::{cmp, result};
+use rstd::collections::btree_map::BTreeMap;
 use support::dispatch::Result;
 use support::{
 	decl_event, decl_module, decl_storage, ensure,
 	traits::{
-		Currency, ExistenceRequirement, Imbalance, LockIdentifier, LockableCurrency, ReservableCurrency,
+		Currency, ExistenceRequirement, Get, Imbalance, LockIdentifier, LockableCurrency, MultiCurrency,
+		MultiLockableCurrency, MultiReservableCurrency, OnFreeBalanceZero, OnUnbalanced, ReservableCurrency,
 		SignedImbalance, UpdateBalanceOutcome, WithdrawReason, WithdrawReasons,
 	},
-	Parameter, StorageMap,
+	Parameter, StorageLinkedMap, StorageMap,
 };
-use system::{ensure_signed, ensure_root};
+use system::{ensure_signed, ensure_root, OnNewAccount};
 
 mod mock;
 mod tests;
 
-pub use self::imbalances::{NegativeImbalance, PositiveImbalance};
+pub use self::imbalances::{DustImbalance, NegativeImbalance, PositiveImbalance};
 
 pub trait Trait: system::Trait {
 	type Balance: Parameter
@@ -184,6 +255,69 @@ pub trait Trait: system::Trait {
 		+ MaybeSerializeDebug;
 	type AssetId: Parameter + Member + SimpleArithmetic + Default + Copy;
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// Handler for the unbalanced reduction when removing the dust of an account's balance of an asset.
+	type DustRemoval: OnUnbalanced<DustImbalance<Self>>;
+
+	/// A function that is invoked when the free balance of an account's asset has been reaped.
+	type OnFreeBalanceZero: OnAssetFreeBalanceZero<Self::AssetId, Self::AccountId>;
+
+	/// Handler for when an account first receives a balance of an asset.
+	type OnNewAccount: OnNewAssetAccount<Self::AssetId, Self::AccountId>;
+
+	/// The origin which may send a restricted asset, returning the sending account.
+	type RestrictedTransferOrigin: EnsureOrigin<Self::Origin, Success = Self::AccountId>;
+
+	/// The maximum length of an asset's symbol, in bytes.
+	type MaxSymbolLength: Get<u32>;
+
+	/// The maximum length of an asset's name, in bytes.
+	type MaxNameLength: Get<u32>;
+}
+
+/// Handler for when the free balance of an account's asset has fallen below the asset's existential
+/// deposit and been reaped.
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+pub trait OnAssetFreeBalanceZero<AssetId, AccountId> {
+	/// The free balance of `asset_id` under `who` was reaped.
+	fn on_free_balance_zero(asset_id: &AssetId, who: &AccountId);
+}
+
+/// Handler for when an account first receives a balance of an asset.
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+pub trait OnNewAssetAccount<AssetId, AccountId> {
+	/// `who` now holds a balance of `asset_id`.
+	fn on_new_account(asset_id: &AssetId, who: &AccountId);
+}
+
+/// Forwards the account callbacks of the asset given by `U` to `H`, a handler for a single currency
+/// such as staking, session or indices. Callbacks for other assets are ignored.
+pub struct ForAsset<U, H>(rstd::marker::PhantomData<(U, H)>);
+
+impl<U, H, AccountId> OnAssetFreeBalanceZero<U::AssetId, AccountId> for ForAsset<U, H>
+where
+	U: AssetIdProvider,
+	U::AssetId: PartialEq,
+	H: OnFreeBalanceZero<AccountId>,
+{
+	fn on_free_balance_zero(asset_id: &U::AssetId, who: &AccountId) {
+		if *asset_id == U::asset_id() {
+			H::on_free_balance_zero(who);
+		}
+	}
+}
+
+impl<U, H, AccountId> OnNewAssetAccount<U::AssetId, AccountId> for ForAsset<U, H>
+where
+	U: AssetIdProvider,
+	U::AssetId: PartialEq,
+	H: OnNewAccount<AccountId>,
+{
+	fn on_new_account(asset_id: &U::AssetId, who: &AccountId) {
+		if *asset_id == U::asset_id() {
+			H::on_new_account(who);
+		}
+	}
 }
 
 pub trait Subtrait: system::Trait {
@@ -212,6 +346,20 @@ pub struct AssetOptions<Balance: HasCompact, AccountId> {
 	pub permissions: PermissionLatest<AccountId>,
 }
 
+/// Asset metadata, describing how an asset is presented to users.
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[derive(Clone, Encode, Decode, PartialEq, Eq, Default)]
+pub struct AssetInfo<Balance> {
+	/// The ticker symbol of the asset.
+	pub symbol: Vec<u8>,
+	/// The number of decimal places used to display an amount of the asset.
+	pub decimal_places: u8,
+	/// The display name of the asset.
+	pub name: Vec<u8>,
+	/// The minimum balance an account should hold of the asset, if any.
+	pub existential_deposit: Option<Balance>,
+}
+
 /// Owner of an asset.
 #[cfg_attr(feature = "std", derive(Debug))]
 #[derive(Clone, Encode, Decode, PartialEq, Eq)]
@@ -262,6 +410,8 @@ pub enum PermissionType {
 	Mint,
 	/// Permission to update asset
 	Update,
+	/// Permission to freeze asset and restrict its transfers
+	Freeze,
 }
 
 /// Alias to latest asset permissions
@@ -324,9 +474,16 @@ impl<AccountId> Into<PermissionVersions<AccountId>> for PermissionLatest<Account
 
 decl_module! {
 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// The maximum length of an asset's symbol, in bytes.
+		const MaxSymbolLength: u32 = T::MaxSymbolLength::get();
+
+		/// The maximum length of an asset's name, in bytes.
+		const MaxNameLength: u32 = T::MaxNameLength::get();
+
 		fn deposit_event() = default;
 
 		/// Create a new kind of asset.
+		#[weight = SimpleDispatchInfo::FixedNormal(CREATING_CALL_WEIGHT)]
 		fn create(origin, options: AssetOptions<T::Balance, T::AccountId>) -> Result {
 			let origin = ensure_signed(origin)?;
 			let id = Self::next_asset_id();
@@ -339,6 +496,7 @@ decl_module! {
 			<NextAssetId<T>>::put(next_id);
 			<TotalIssuance<T>>::insert(id, &options.initial_issuance);
 			<FreeBalance<T>>::insert(&id, &origin, &options.initial_issuance);
+			Self::add_holder(&id, &origin);
 			<Permissions<T>>::insert(&id, permissions);
 
 			Self::deposit_event(RawEvent::Created(id, origin, options));
@@ -347,12 +505,30 @@ decl_module! {
 		}
 
 		/// Transfer some liquid free balance to another account.
+		#[weight = SimpleDispatchInfo::FixedNormal(CREATING_CALL_WEIGHT)]
 		pub fn transfer(origin, #[compact] asset_id: T::AssetId, to: T::AccountId, #[compact] amount: T::Balance) {
-			let origin = ensure_signed(origin)?;
+			let origin = Self::ensure_sender(origin, Self::is_sender_restricted(&asset_id))?;
 			ensure!(!amount.is_zero(), "cannot transfer zero amount");
 			Self::make_transfer_with_event(&asset_id, &origin, &to, amount)?;
 		}
 
+		/// Transfer some liquid free balance of any assets to several accounts, given as
+		/// `(asset_id, to, amount)`.
+		///
+		/// Either all of the transfers are made, emitting a `Transferred` event each, or none are.
+		/// At most `MAX_BATCH_TRANSFERS` transfers may be made in a batch.
+		#[weight = BatchTransferWeight(CREATING_CALL_WEIGHT)]
+		pub fn batch_transfer(origin, transfers: Vec<(T::AssetId, T::AccountId, T::Balance)>) {
+			let restricted = transfers.iter().any(|(asset_id, _, _)| Self::is_sender_restricted(asset_id));
+			let origin = Self::ensure_sender(origin, restricted)?;
+			Self::ensure_can_batch_transfer(&origin, &transfers)?;
+
+			// Every transfer is checked as `make_transfer` would, so none can fail from here on.
+			for (asset_id, to, amount) in transfers {
+				Self::make_transfer_with_event(&asset_id, &origin, &to, amount)?;
+			}
+		}
+
 		/// Updates permission for a given `asset_id` and an account.
 		///
 		/// The `origin` must have `update` permission.
@@ -378,15 +554,18 @@ decl_module! {
 
 		/// Mints an asset, increases its total issuance.
 		/// The origin must have `mint` permissions.
+		#[weight = SimpleDispatchInfo::FixedNormal(CREATING_CALL_WEIGHT)]
 		fn mint(origin, #[compact] asset_id: T::AssetId, to: T::AccountId, amount: T::Balance) -> Result {
 			let origin = ensure_signed(origin)?;
 			if Self::check_permission(&asset_id, &origin, &PermissionType::Mint) {
+				ensure!(Self::can_receive(&asset_id, &to), "destination is not allowed to receive asset");
 				let original_free_balance = Self::free_balance(&asset_id, &to);
 				let current_total_issuance = <TotalIssuance<T>>::get(asset_id);
 				let new_total_issuance = current_total_issuance.checked_add(&amount)
 					.ok_or_else(|| "total_issuance got overflow after minting.")?;
 				let value = original_free_balance.checked_add(&amount)
 					.ok_or_else(|| "free balance got overflow after minting.")?;
+				ensure!(value >= Self::existential_deposit(&asset_id), "value too low to create account");
 
 				<TotalIssuance<T>>::insert(asset_id, new_total_issuance);
 				Self::set_free_balance(&asset_id, &to, value);
@@ -413,6 +592,10 @@ decl_module! {
 					.ok_or_else(|| "total_issuance got underflow after burning")?;
 				let value = original_free_balance.checked_sub(&amount)
 					.ok_or_else(|| "free_balance got underflow after burning")?;
+				ensure!(
+					value.is_zero() || value >= Self::existential_deposit(&asset_id),
+					"burn would leave less than the existential deposit"
+				);
 
 				<TotalIssuance<T>>::insert(asset_id, new_total_issuance);
 
@@ -428,10 +611,376 @@ decl_module! {
 
 		/// Can be used to create reserved tokens.
 		/// Requires Root call.
+		#[weight = SimpleDispatchInfo::FixedOperational(CREATING_CALL_WEIGHT)]
 		fn create_reserved(origin, asset_id: T::AssetId, options: AssetOptions<T::Balance, T::AccountId>) -> Result {
 			ensure_root(origin)?;
 			Self::create_asset(Some(asset_id), None, options)
 		}
+
+		/// Approve `spender` to transfer up to `amount` of the origin's balance of `asset_id`.
+		///
+		/// This replaces any existing allowance of `spender`, an `amount` of zero revokes it. No
+		/// allowance may be granted while the asset or the origin's balance of it is frozen, or by
+		/// an origin which may not send it, but allowances may always be revoked.
+		pub fn approve(
+			origin,
+			#[compact] asset_id: T::AssetId,
+			spender: T::AccountId,
+			#[compact] amount: T::Balance
+		) {
+			let origin = ensure_signed(origin)?;
+
+			if amount.is_zero() {
+				<Allowances<T>>::remove(asset_id, (origin.clone(), spender.clone()));
+			} else {
+				ensure!(!Self::is_asset_frozen(&asset_id), "asset is frozen");
+				ensure!(!Self::is_account_frozen(&asset_id, &origin), "account is frozen");
+				if Self::is_sender_restricted(&asset_id) {
+					Self::ensure_sender(system::RawOrigin::Signed(origin.clone()).into(), true)?;
+				}
+				<Allowances<T>>::insert(asset_id, (origin.clone(), spender.clone()), amount);
+			}
+
+			Self::deposit_event(RawEvent::Approved(asset_id, origin, spender, amount));
+		}
+
+		/// Transfer some liquid free balance of `from` to another account, consuming the
+		/// origin's allowance.
+		///
+		/// Assets with restricted senders may only be transferred if both the origin and `from` are
+		/// accepted by `RestrictedTransferOrigin`.
+		#[weight = SimpleDispatchInfo::FixedNormal(CREATING_CALL_WEIGHT)]
+		pub fn transfer_from(
+			origin,
+			#[compact] asset_id: T::AssetId,
+			from: T::AccountId,
+			to: T::AccountId,
+			#[compact] amount: T::Balance
+		) {
+			let restricted = Self::is_sender_restricted(&asset_id);
+			let origin = Self::ensure_sender(origin, restricted)?;
+			if restricted {
+				// The owner of the funds must be allowed to send them too.
+				Self::ensure_sender(system::RawOrigin::Signed(from.clone()).into(), true)
+					.map_err(|_| "owner is not allowed to send restricted asset")?;
+			}
+			ensure!(!amount.is_zero(), "cannot transfer zero amount");
+
+			let new_allowance = Self::allowance(&asset_id, &from, &origin)
+				.checked_sub(&amount)
+				.ok_or_else(|| "amount exceeds allowance")?;
+			Self::make_transfer_with_event(&asset_id, &from, &to, amount)?;
+
+			if new_allowance.is_zero() {
+				<Allowances<T>>::remove(asset_id, (from, origin));
+			} else {
+				<Allowances<T>>::insert(asset_id, (from, origin), new_allowance);
+			}
+		}
+
+		/// Updates the metadata for a given `asset_id`.
+		///
+		/// The `origin` must have `update` permission. The symbol and name may be no longer than
+		/// `MaxSymbolLength` and `MaxNameLength`.
+		///
+		/// The existential deposit may only be raised while none of the asset has been issued, as
+		/// raising it would otherwise strand balances below it. It may always be lowered.
+		fn update_asset_info(origin, #[compact] asset_id: T::AssetId, info: AssetInfo<T::Balance>) -> Result {
+			let origin = ensure_signed(origin)?;
+			ensure!(info.symbol.len() <= T::MaxSymbolLength::get() as usize, "asset symbol is too long");
+			ensure!(info.name.len() <= T::MaxNameLength::get() as usize, "asset name is too long");
+			ensure!(
+				info.existential_deposit.unwrap_or_else(Zero::zero) <= Self::existential_deposit(&asset_id)
+					|| Self::total_issuance(&asset_id).is_zero(),
+				"existential deposit can not be raised once the asset has been issued"
+			);
+
+			if Self::check_permission(&asset_id, &origin, &PermissionType::Update) {
+				<AssetMeta<T>>::insert(asset_id, &info);
+
+				Self::deposit_event(RawEvent::AssetInfoUpdated(asset_id, info));
+
+				Ok(())
+			} else {
+				Err("Origin does not have enough permission to update asset info.")
+			}
+		}
+
+		/// Updates who has permission to freeze a given `asset_id` and restrict its transfers.
+		///
+		/// The `origin` must have `update` permission. The freeze permission is kept apart from the
+		/// asset's other permissions, so their encoding is unchanged.
+		fn update_freeze_permission(origin, #[compact] asset_id: T::AssetId, freeze: Owner<T::AccountId>) -> Result {
+			let origin = ensure_signed(origin)?;
+
+			if Self::check_permission(&asset_id, &origin, &PermissionType::Update) {
+				<FreezePermission<T>>::insert(asset_id, &freeze);
+
+				Self::deposit_event(RawEvent::FreezePermissionUpdated(asset_id, freeze));
+
+				Ok(())
+			} else {
+				Err("Origin does not have enough permission to update permissions.")
+			}
+		}
+
+		/// Freezes or thaws all withdrawals of a given `asset_id`.
+		fn freeze_asset(origin, #[compact] asset_id: T::AssetId, frozen: bool) -> Result {
+			let origin = ensure_signed(origin)?;
+
+			if Self::check_permission(&asset_id, &origin, &PermissionType::Freeze) {
+				if frozen {
+					<FrozenAssets<T>>::insert(asset_id, true);
+				} else {
+					<FrozenAssets<T>>::remove(asset_id);
+				}
+
+				Self::deposit_event(RawEvent::AssetFrozen(asset_id, frozen));
+
+				Ok(())
+			} else {
+				Err("Origin does not have enough permission to freeze asset.")
+			}
+		}
+
+		/// Freezes or thaws withdrawals of `who`'s balance of a given `asset_id`.
+		fn freeze_account(origin, #[compact] asset_id: T::AssetId, who: T::AccountId, frozen: bool) -> Result {
+			let origin = ensure_signed(origin)?;
+
+			if Self::check_permission(&asset_id, &origin, &PermissionType::Freeze) {
+				if frozen {
+					<FrozenAccounts<T>>::insert(&asset_id, &who, true);
+				} else {
+					<FrozenAccounts<T>>::remove(&asset_id, &who);
+				}
+
+				Self::deposit_event(RawEvent::AccountFrozen(asset_id, who, frozen));
+
+				Ok(())
+			} else {
+				Err("Origin does not have enough permission to freeze asset.")
+			}
+		}
+
+		/// Restricts a given `asset_id` to be received only by the accounts on its allow-list, or
+		/// lifts the restriction.
+		fn restrict_transfers(origin, #[compact] asset_id: T::AssetId, restricted: bool) -> Result {
+			let origin = ensure_signed(origin)?;
+
+			if Self::check_permission(&asset_id, &origin, &PermissionType::Freeze) {
+				if restricted {
+					<Restricted<T>>::insert(asset_id, true);
+				} else {
+					<Restricted<T>>::remove(asset_id);
+				}
+
+				Self::deposit_event(RawEvent::TransfersRestricted(asset_id, restricted));
+
+				Ok(())
+			} else {
+				Err("Origin does not have enough permission to freeze asset.")
+			}
+		}
+
+		/// Restricts a given `asset_id` to be sent only by origins accepted by `RestrictedTransferOrigin`,
+		/// or lifts the restriction.
+		///
+		/// This is independent of the allow-list of accounts which may receive the asset.
+		fn restrict_senders(origin, #[compact] asset_id: T::AssetId, restricted: bool) -> Result {
+			let origin = ensure_signed(origin)?;
+
+			if Self::check_permission(&asset_id, &origin, &PermissionType::Freeze) {
+				if restricted {
+					<SenderRestricted<T>>::insert(asset_id, true);
+				} else {
+					<SenderRestricted<T>>::remove(asset_id);
+				}
+
+				Self::deposit_event(RawEvent::SendersRestricted(asset_id, restricted));
+
+				Ok(())
+			} else {
+				Err("Origin does not have enough permission to freeze asset.")
+			}
+		}
+
+		/// Adds `who` to, or removes it from, the allow-list of a given `asset_id`.
+		fn set_allowed(origin, #[compact] asset_id: T::AssetId, who: T::AccountId, allowed: bool) -> Result {
+			let origin = ensure_signed(origin)?;
+
+			if Self::check_permission(&asset_id, &origin, &PermissionType::Freeze) {
+				if allowed {
+					<AllowList<T>>::insert(&asset_id, &who, true);
+				} else {
+					<AllowList<T>>::remove(&asset_id, &who);
+				}
+
+				Self::deposit_event(RawEvent::AllowListUpdated(asset_id, who, allowed));
+
+				Ok(())
+			} else {
+				Err("Origin does not have enough permission to freeze asset.")
+			}
+		}
+
+		/// Release the origin's balance of `asset_id` which has vested.
+		pub fn vest(origin, #[compact] asset_id: T::AssetId) {
+			let origin = ensure_signed(origin)?;
+			ensure!(<Vesting<T>>::exists(&asset_id, &origin), "account has no vesting schedule");
+
+			let locked = Self::update_vesting_lock(&asset_id, &origin);
+			Self::deposit_event(RawEvent::Vested(asset_id, origin, locked));
+		}
+
+		/// Sets the vesting schedule of `who`'s balance of `asset_id`, locking the amount which has
+		/// not vested yet.
+		///
+		/// The `origin` must be root. The schedule must vest a non-zero amount per block and may
+		/// not lock more than `who`'s free balance of `asset_id`.
+		pub fn set_vesting_schedule(
+			origin,
+			#[compact] asset_id: T::AssetId,
+			who: T::AccountId,
+			schedule: VestingSchedule<T::Balance, T::BlockNumber>
+		) {
+			ensure_root(origin)?;
+			ensure!(!schedule.per_block.is_zero(), "vesting schedule must vest a non-zero amount per block");
+			ensure!(
+				schedule.locked <= Self::free_balance(&asset_id, &who),
+				"vesting schedule locks more than the free balance"
+			);
+
+			<Vesting<T>>::insert(&asset_id, &who, schedule);
+			Self::update_vesting_lock(&asset_id, &who);
+
+			Self::deposit_event(RawEvent::VestingScheduleUpdated(asset_id, who));
+		}
+
+		/// Index the given accounts as holders of `asset_id` if they have a balance of it, so that
+		/// balances created before the `Holders` index was introduced are audited.
+		///
+		/// The accounts are given in pages of at most `MAX_BACKFILL_HOLDERS`. The `cursor` must be
+		/// the number of accounts given for `asset_id` so far, as kept in `BackfillCursor`, so that
+		/// no page is applied twice or out of order.
+		///
+		/// The `origin` must be root.
+		#[weight = BackfillHoldersWeight(CREATING_CALL_WEIGHT)]
+		pub fn backfill_holders(
+			origin,
+			#[compact] asset_id: T::AssetId,
+			#[compact] cursor: u32,
+			accounts: Vec<T::AccountId>
+		) {
+			ensure_root(origin)?;
+			ensure!(accounts.len() <= MAX_BACKFILL_HOLDERS, "too many accounts to backfill");
+			ensure!(cursor == Self::backfill_cursor(&asset_id), "backfill cursor does not match");
+
+			<BackfillCursor<T>>::insert(asset_id, cursor.saturating_add(accounts.len() as u32));
+			for who in accounts {
+				if !Self::total_balance(&asset_id, &who).is_zero() {
+					Self::add_holder(&asset_id, &who);
+				}
+			}
+		}
+	}
+}
+
+/// The neighbours of an account in the list of the holders of an asset.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Encode, Decode, PartialEq, Eq, Default)]
+pub struct HolderLinkage<AccountId> {
+	/// The previous holder of the asset, if any.
+	pub previous: Option<AccountId>,
+	/// The next holder of the asset, if any.
+	pub next: Option<AccountId>,
+}
+
+/// The total issuance of an asset alongside the sums of the balances of its holders.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Encode, Decode, PartialEq, Eq, Default)]
+pub struct IssuanceAudit<Balance> {
+	/// The recorded total issuance of the asset.
+	pub total_issuance: Balance,
+	/// The sum of the free balances of the asset.
+	pub free: Balance,
+	/// The sum of the reserved balances of the asset.
+	pub reserved: Balance,
+}
+
+impl<Balance: SimpleArithmetic + Copy> IssuanceAudit<Balance> {
+	/// Whether the total issuance equals the sum of the free and reserved balances.
+	pub fn is_consistent(&self) -> bool {
+		self.free.checked_add(&self.reserved) == Some(self.total_issuance)
+	}
+}
+
+const VESTING_ID: LockIdentifier = *b"vesting ";
+
+/// The maximum number of transfers in a `batch_transfer`.
+pub const MAX_BATCH_TRANSFERS: usize = 1_000;
+
+/// The maximum number of accounts in a page of `backfill_holders`.
+pub const MAX_BACKFILL_HOLDERS: usize = 1_000;
+
+/// The weight of a call which may give an account its first balance of an asset: the default
+/// weight of a call, and as much again for indexing the account in `Holders`.
+pub const CREATING_CALL_WEIGHT: Weight = 20_000;
+
+/// Struct to encode the vesting schedule of an account's balance of an asset.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct VestingSchedule<Balance, BlockNumber> {
+	/// Locked amount when the schedule was set.
+	pub locked: Balance,
+	/// Amount that gets unlocked every block after `starting_block`.
+	pub per_block: Balance,
+	/// Starting block for unlocking(vesting).
+	pub starting_block: BlockNumber,
+}
+
+impl<Balance: SimpleArithmetic + Copy, BlockNumber: SimpleArithmetic + Copy> VestingSchedule<Balance, BlockNumber> {
+	/// Amount locked at block `n`.
+	pub fn locked_at(&self, n: BlockNumber) -> Balance {
+		// Number of blocks that count toward vesting
+		// Saturating to 0 when n < starting_block
+		let vested_block_count: u64 = n.saturating_sub(self.starting_block).unique_saturated_into();
+		let vested_block_count = Balance::unique_saturated_from(vested_block_count);
+		// Return amount that is still locked in vesting
+		if let Some(x) = vested_block_count.checked_mul(&self.per_block) {
+			self.locked.max(x) - x
+		} else {
+			Zero::zero()
+		}
+	}
+}
+
+/// The weight of a `batch_transfer`, a fixed weight for each transfer in the batch.
+pub struct BatchTransferWeight(pub Weight);
+
+impl<'a, T> WeighData<(&'a Vec<T>,)> for BatchTransferWeight {
+	fn weigh_data(&self, (transfers,): (&'a Vec<T>,)) -> Weight {
+		self.0.saturating_mul(transfers.len() as Weight)
+	}
+}
+
+impl<T> ClassifyDispatch<T> for BatchTransferWeight {
+	fn classify_dispatch(&self, _: T) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+/// The weight of a `backfill_holders`, a fixed weight for each account in the page.
+pub struct BackfillHoldersWeight(pub Weight);
+
+impl<'a, AssetId, AccountId> WeighData<(&'a AssetId, &'a u32, &'a Vec<AccountId>)> for BackfillHoldersWeight {
+	fn weigh_data(&self, (_, _, accounts): (&'a AssetId, &'a u32, &'a Vec<AccountId>)) -> Weight {
+		self.0.saturating_mul(accounts.len() as Weight)
+	}
+}
+
+impl<T> ClassifyDispatch<T> for BackfillHoldersWeight {
+	fn classify_dispatch(&self, _: T) -> DispatchClass {
+		DispatchClass::Operational
 	}
 }
 
@@ -458,32 +1007,108 @@ decl_storage! {
 		/// The reserved balance of a given asset under an account.
 		pub ReservedBalance: double_map T::AssetId, twox_128(T::AccountId) => T::Balance;
 
+		/// The accounts which have a balance of a given asset, so that balances may be audited,
+		/// linked in a list per asset.
+		///
+		/// NOTE: Balances created before this index was introduced are only included once indexed
+		/// with `backfill_holders`, or the account is next created, i.e. after being reaped.
+		pub Holders: double_map T::AssetId, twox_128(T::AccountId) => Option<HolderLinkage<T::AccountId>>;
+
+		/// The first holder in the list of the holders of a given asset.
+		pub HoldersHead: map T::AssetId => Option<T::AccountId>;
+
+		/// The asset kinds with at least one holder.
+		pub HolderAssets: linked_map T::AssetId => ();
+
+		/// The number of accounts given to `backfill_holders` for a given asset so far.
+		pub BackfillCursor get(backfill_cursor): map T::AssetId => u32;
+
 		/// Next available ID for user-created asset.
 		pub NextAssetId get(next_asset_id) config(): T::AssetId;
 
 		/// Permission options for a given asset.
 		pub Permissions get(get_permission): map T::AssetId => PermissionVersions<T::AccountId>;
 
-		/// Any liquidity locks on some account balances.
-		pub Locks get(locks): map T::AccountId => Vec<BalanceLock<T::Balance, T::BlockNumber>>;
+		/// Deprecated: liquidity locks from before locks were kept per asset kind.
+		///
+		/// These only ever restricted the staking asset, and are read as locks on it until they are
+		/// moved to `AssetLocks` under the staking asset, the next time a lock of the account is set,
+		/// extended or removed.
+		pub Locks: map T::AccountId => Vec<BalanceLock<T::Balance, T::BlockNumber>>;
+
+		/// Any liquidity locks on a given asset under an account.
+		pub AssetLocks: double_map T::AssetId, twox_128(T::AccountId) => Vec<BalanceLock<T::Balance, T::BlockNumber>>;
 
 		/// The identity of the asset which is the one that is designated for the chain's staking system.
 		pub StakingAssetId get(staking_asset_id) config(): T::AssetId;
 
 		/// The identity of the asset which is the one that is designated for paying the chain's transaction fee.
 		pub SpendingAssetId get(spending_asset_id) config(): T::AssetId;
+
+		/// The amount of an owner's balance of a given asset that a spender may transfer, keyed by
+		/// `(owner, spender)`.
+		pub Allowances: double_map T::AssetId, twox_128((T::AccountId, T::AccountId)) => T::Balance;
+
+		/// The vesting schedule of an account's balance of a given asset.
+		pub Vesting get(vesting): double_map T::AssetId, twox_128(T::AccountId)
+			=> Option<VestingSchedule<T::Balance, T::BlockNumber>>;
+
+		/// Who has permission to freeze a given asset and restrict its transfers.
+		pub FreezePermission get(freeze_permission): map T::AssetId => Owner<T::AccountId>;
+
+		/// Whether all withdrawals of a given asset are frozen.
+		pub FrozenAssets get(is_asset_frozen): map T::AssetId => bool;
+
+		/// Whether withdrawals of an account's balance of a given asset are frozen.
+		pub FrozenAccounts get(is_account_frozen): double_map T::AssetId, twox_128(T::AccountId) => bool;
+
+		/// Whether a given asset may only be received by the accounts on its allow-list.
+		pub Restricted get(is_restricted): map T::AssetId => bool;
+
+		/// The accounts allowed to receive a given restricted asset.
+		pub AllowList get(is_allowed): double_map T::AssetId, twox_128(T::AccountId) => bool;
+
+		/// Whether a given asset may only be sent by origins accepted by `RestrictedTransferOrigin`.
+		pub SenderRestricted get(is_sender_restricted): map T::AssetId => bool;
+
+		/// Metadata of a given asset.
+		pub AssetMeta get(asset_meta) config(): linked_map T::AssetId => AssetInfo<T::Balance>;
 	}
 	add_extra_genesis {
 		config(assets): Vec<T::AssetId>;
 		config(initial_balance): T::Balance;
 		config(endowed_accounts): Vec<T::AccountId>;
+		config(vesting): Vec<(T::AssetId, T::AccountId, T::BlockNumber, T::BlockNumber, T::Balance)>;
 
 		build(|config: &GenesisConfig<T>| {
 			config.assets.iter().for_each(|asset_id| {
 				config.endowed_accounts.iter().for_each(|account_id| {
 					<FreeBalance<T>>::insert(asset_id, account_id, &config.initial_balance);
+					<Module<T>>::add_holder(asset_id, account_id);
 				});
 			});
+
+			// Generate initial vesting configuration
+			// * asset_id - Asset kind which is vesting
+			// * who - Account which we are generating vesting configuration for
+			// * begin - Block when the account will start to vest
+			// * length - Number of blocks from `begin` until fully vested
+			// * liquid - Number of units which can be spent before vesting begins
+			config.vesting.iter().for_each(|&(asset_id, ref who, begin, length, liquid)| {
+				// Total genesis balance minus `liquid` equals funds locked for vesting
+				let locked = <FreeBalance<T>>::get(&asset_id, who).saturating_sub(liquid);
+				let length: u64 = length.unique_saturated_into();
+				let length = T::Balance::unique_saturated_from(length);
+				// Number of units unlocked per block after `begin`
+				let per_block = locked / length.max(One::one());
+
+				<Vesting<T>>::insert(&asset_id, who, VestingSchedule {
+					locked,
+					per_block,
+					starting_block: begin,
+				});
+				<Module<T>>::update_vesting_lock(&asset_id, who);
+			});
 		});
 	}
 }
@@ -493,7 +1118,8 @@ decl_event!(
 		<T as system::Trait>::AccountId,
 		<T as Trait>::Balance,
 		<T as Trait>::AssetId,
-		AssetOptions = AssetOptions<<T as Trait>::Balance, <T as system::Trait>::AccountId>
+		AssetOptions = AssetOptions<<T as Trait>::Balance, <T as system::Trait>::AccountId>,
+		AssetInfo = AssetInfo<<T as Trait>::Balance>
 	{
 		/// Asset created (asset_id, creator, asset_options).
 		Created(AssetId, AccountId, AssetOptions),
@@ -505,6 +1131,30 @@ decl_event!(
 		Minted(AssetId, AccountId, Balance),
 		/// Asset burned (asset_id, account, amount).
 		Burned(AssetId, AccountId, Balance),
+		/// Asset metadata updated (asset_id, new_info).
+		AssetInfoUpdated(AssetId, AssetInfo),
+		/// A new account was created with a balance of an asset (asset_id, account, free_balance).
+		NewAccount(AssetId, AccountId, Balance),
+		/// An account's balance of an asset was reaped (asset_id, account).
+		ReapedAccount(AssetId, AccountId),
+		/// An account's vesting schedule of an asset was set (asset_id, account).
+		VestingScheduleUpdated(AssetId, AccountId),
+		/// An account's vested balance of an asset was released (asset_id, account, amount_still_locked).
+		Vested(AssetId, AccountId, Balance),
+		/// A spender was approved to transfer some of an owner's asset (asset_id, owner, spender, amount).
+		Approved(AssetId, AccountId, AccountId, Balance),
+		/// Asset freeze permission updated (asset_id, new_freeze_permission).
+		FreezePermissionUpdated(AssetId, Owner<AccountId>),
+		/// Withdrawals of an asset were frozen or thawed (asset_id, frozen).
+		AssetFrozen(AssetId, bool),
+		/// Withdrawals of an account's balance of an asset were frozen or thawed (asset_id, account, frozen).
+		AccountFrozen(AssetId, AccountId, bool),
+		/// An asset was restricted to its allow-list, or the restriction lifted (asset_id, restricted).
+		TransfersRestricted(AssetId, bool),
+		/// An account was added to or removed from an asset's allow-list (asset_id, account, allowed).
+		AllowListUpdated(AssetId, AccountId, bool),
+		/// An asset was restricted to attested senders, or the restriction lifted (asset_id, restricted).
+		SendersRestricted(AssetId, bool),
 	}
 );
 
@@ -526,6 +1176,116 @@ impl<T: Trait> Module<T> {
 		<ReservedBalance<T>>::get(asset_id, who)
 	}
 
+	/// Get the liquidity locks on an account's balance of an asset kind.
+	pub fn locks(asset_id: &T::AssetId, who: &T::AccountId) -> Vec<BalanceLock<T::Balance, T::BlockNumber>> {
+		let mut locks = <AssetLocks<T>>::get(asset_id, who);
+		if asset_id == &Self::staking_asset_id() {
+			locks.extend(<Locks<T>>::get(who));
+		}
+		locks
+	}
+
+	/// Get the amount of `owner`'s balance of an asset kind that `spender` may transfer.
+	pub fn allowance(asset_id: &T::AssetId, owner: &T::AccountId, spender: &T::AccountId) -> T::Balance {
+		<Allowances<T>>::get(asset_id, (owner.clone(), spender.clone()))
+	}
+
+	/// Get the amount of `who`'s balance of an asset kind that is still being vested and cannot be
+	/// transferred.
+	pub fn vesting_balance(asset_id: &T::AssetId, who: &T::AccountId) -> T::Balance {
+		if let Some(v) = Self::vesting(asset_id, who) {
+			Self::free_balance(asset_id, who)
+				.min(v.locked_at(<system::Module<T>>::block_number()))
+		} else {
+			Zero::zero()
+		}
+	}
+
+	/// Get the accounts which have a balance of an asset kind.
+	///
+	/// NOTE: This iterates over the holders of the asset kind, it should not be used on chain.
+	pub fn holders(asset_id: &T::AssetId) -> Vec<T::AccountId> {
+		let mut holders = Vec::new();
+		let mut next = <HoldersHead<T>>::get(asset_id);
+		while let Some(who) = next {
+			next = <Holders<T>>::get(asset_id, &who).and_then(|linkage| linkage.next);
+			holders.push(who);
+		}
+		holders
+	}
+
+	/// Get the total issuance of an asset kind alongside the sums of the free and reserved
+	/// balances of its holders.
+	///
+	/// NOTE: This iterates over the holders of the asset kind, it should not be used on chain.
+	pub fn audit_issuance(asset_id: &T::AssetId) -> IssuanceAudit<T::Balance> {
+		Self::holders(asset_id).into_iter().fold(
+			IssuanceAudit { total_issuance: Self::total_issuance(asset_id), ..Default::default() },
+			|mut audit, who| {
+				audit.free = audit.free.saturating_add(Self::free_balance(asset_id, &who));
+				audit.reserved = audit.reserved.saturating_add(Self::reserved_balance(asset_id, &who));
+				audit
+			},
+		)
+	}
+
+	/// Get the audits of the staking and spending assets, and every asset kind with holders, whose
+	/// total issuance does not match the balances of its holders.
+	///
+	/// NOTE: This iterates over the holders of every asset kind, it should not be used on chain.
+	pub fn issuance_discrepancies() -> Vec<(T::AssetId, IssuanceAudit<T::Balance>)> {
+		let mut asset_ids = vec![Self::staking_asset_id(), Self::spending_asset_id()];
+		for (asset_id, _) in <HolderAssets<T>>::enumerate() {
+			if !asset_ids.contains(&asset_id) {
+				asset_ids.push(asset_id);
+			}
+		}
+
+		asset_ids
+			.into_iter()
+			.map(|asset_id| (asset_id, Self::audit_issuance(&asset_id)))
+			.filter(|(_, audit)| !audit.is_consistent())
+			.collect()
+	}
+
+	/// Return `Ok` iff the total issuance of every asset kind matches the balances of its holders.
+	///
+	/// Meant to be called at the end of tests, to catch accounting drift.
+	pub fn try_state() -> Result {
+		if Self::issuance_discrepancies().is_empty() {
+			Ok(())
+		} else {
+			Err("total issuance does not match the sum of balances")
+		}
+	}
+
+	/// Ensure `origin` may send assets, checking it against `RestrictedTransferOrigin` if the
+	/// senders of any of them are restricted. Returns the sending account.
+	fn ensure_sender(origin: T::Origin, restricted: bool) -> result::Result<T::AccountId, &'static str> {
+		if restricted {
+			T::RestrictedTransferOrigin::ensure_origin(origin)
+				.map_err(|_| "origin is not allowed to send restricted asset")
+		} else {
+			ensure_signed(origin).map_err(Into::into)
+		}
+	}
+
+	/// Check if `who` is allowed to receive an asset kind, which is always the case unless the asset
+	/// is restricted to its allow-list.
+	pub fn can_receive(asset_id: &T::AssetId, who: &T::AccountId) -> bool {
+		!Self::is_restricted(asset_id) || Self::is_allowed(asset_id, who)
+	}
+
+	/// Get the existential deposit of an asset kind, zero if it has none.
+	pub fn existential_deposit(asset_id: &T::AssetId) -> T::Balance {
+		Self::asset_meta(asset_id).existential_deposit.unwrap_or_else(Zero::zero)
+	}
+
+	/// Get the ids and metadata of all assets with registered metadata.
+	pub fn registered_assets() -> Vec<(T::AssetId, AssetInfo<T::Balance>)> {
+		<AssetMeta<T>>::enumerate().collect()
+	}
+
 	/// Creates an asset.
 	///
 	/// # Arguments
@@ -557,6 +1317,7 @@ impl<T: Trait> Module<T> {
 
 		<TotalIssuance<T>>::insert(asset_id, &options.initial_issuance);
 		<FreeBalance<T>>::insert(&asset_id, &account_id, &options.initial_issuance);
+		Self::add_holder(&asset_id, &account_id);
 		<Permissions<T>>::insert(&asset_id, permissions);
 
 		Self::deposit_event(RawEvent::Created(asset_id, account_id, options));
@@ -567,14 +1328,81 @@ impl<T: Trait> Module<T> {
 	/// Transfer some liquid free balance from one account to another.
 	/// This will not emit the `Transferred` event.
 	pub fn make_transfer(asset_id: &T::AssetId, from: &T::AccountId, to: &T::AccountId, amount: T::Balance) -> Result {
-		let new_balance = Self::free_balance(asset_id, from)
+		let new_balances = Self::checked_transfer(asset_id, from, to, amount, Self::free_balance)?;
+		if let Some((new_balance, new_to_balance)) = new_balances {
+			Self::set_free_balance(asset_id, from, new_balance);
+			Self::set_free_balance(asset_id, to, new_to_balance);
+		}
+
+		Ok(())
+	}
+
+	/// Return the free balances of `from` and `to` after a transfer of `amount` between them, given
+	/// their current free balances are read with `free_balance`.
+	///
+	/// `Ok(None)` if `from` and `to` are the same account, so no balance changes.
+	/// `Err(...)` with the reason the transfer can not be made otherwise.
+	fn checked_transfer(
+		asset_id: &T::AssetId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: T::Balance,
+		free_balance: impl Fn(&T::AssetId, &T::AccountId) -> T::Balance,
+	) -> result::Result<Option<(T::Balance, T::Balance)>, &'static str> {
+		let new_balance = free_balance(asset_id, from)
 			.checked_sub(&amount)
 			.ok_or_else(|| "balance too low to send amount")?;
 		Self::ensure_can_withdraw(asset_id, from, amount, WithdrawReason::Transfer, new_balance)?;
 
-		if from != to {
-			<FreeBalance<T>>::mutate(asset_id, from, |balance| *balance -= amount);
-			<FreeBalance<T>>::mutate(asset_id, to, |balance| *balance += amount);
+		if from == to {
+			return Ok(None);
+		}
+
+		ensure!(Self::can_receive(asset_id, to), "destination is not allowed to receive asset");
+		let to_balance = free_balance(asset_id, to);
+		if to_balance.is_zero() && amount < Self::existential_deposit(asset_id) {
+			return Err("value too low to create account");
+		}
+		let new_to_balance = to_balance
+			.checked_add(&amount)
+			.ok_or_else(|| "destination balance too high to receive value")?;
+
+		Ok(Some((new_balance, new_to_balance)))
+	}
+
+	/// Return `Ok` iff every transfer of a batch from `from` would succeed, given the transfers
+	/// before it in the batch had been made.
+	///
+	/// `Err(...)` with the reason the first failing transfer would fail otherwise.
+	fn ensure_can_batch_transfer(
+		from: &T::AccountId,
+		transfers: &[(T::AssetId, T::AccountId, T::Balance)],
+	) -> Result {
+		ensure!(transfers.len() <= MAX_BATCH_TRANSFERS, "too many transfers in batch");
+
+		// The free balances the batch has touched so far, as they would be after its transfers.
+		let mut balances: BTreeMap<(T::AssetId, T::AccountId), T::Balance> = BTreeMap::new();
+
+		for (asset_id, to, amount) in transfers {
+			ensure!(!amount.is_zero(), "cannot transfer zero amount");
+
+			let free_balance = |asset_id: &T::AssetId, who: &T::AccountId| {
+				balances
+					.get(&(*asset_id, who.clone()))
+					.cloned()
+					.unwrap_or_else(|| Self::free_balance(asset_id, who))
+			};
+			let new_balances = Self::checked_transfer(asset_id, from, to, *amount, free_balance)?;
+			if let Some((new_balance, new_to_balance)) = new_balances {
+				// The sender is reaped, as `set_free_balance` would do, if left with too little.
+				let new_balance = if new_balance < Self::existential_deposit(asset_id) {
+					Zero::zero()
+				} else {
+					new_balance
+				};
+				balances.insert((*asset_id, from.clone()), new_balance);
+				balances.insert((*asset_id, to.clone()), new_to_balance);
+			}
 		}
 
 		Ok(())
@@ -597,6 +1425,43 @@ impl<T: Trait> Module<T> {
 		Ok(())
 	}
 
+	/// Withdraw `amount` of `asset_id` from the free balance of `who` and burn it, decreasing the
+	/// total issuance of the asset.
+	pub fn withdraw_and_burn(
+		asset_id: &T::AssetId,
+		who: &T::AccountId,
+		amount: T::Balance,
+		reason: WithdrawReason,
+	) -> Result {
+		Self::withdraw_into_imbalance(asset_id, who, amount, reason).map(|_| ())
+	}
+
+	/// Withdraw `amount` of `asset_id` from the free balance of `who`, returning the imbalance of
+	/// the withdrawn funds. The total issuance of the asset is decreased when it is dropped.
+	///
+	/// Used to take payments, such as transaction fees, in an asset chosen at runtime.
+	pub fn withdraw_into_imbalance(
+		asset_id: &T::AssetId,
+		who: &T::AccountId,
+		amount: T::Balance,
+		reason: WithdrawReason,
+	) -> result::Result<DustImbalance<T>, &'static str> {
+		let new_balance = Self::free_balance(asset_id, who)
+			.checked_sub(&amount)
+			.ok_or_else(|| "account has too few funds")?;
+		Self::ensure_can_withdraw(asset_id, who, amount, reason, new_balance)?;
+
+		Self::set_free_balance(asset_id, who, new_balance);
+
+		Ok(DustImbalance::new(*asset_id, amount))
+	}
+
+	/// Credit the funds of `imbalance` to the free balance of `who`, squaring up the total issuance.
+	pub fn resolve_imbalance(who: &T::AccountId, imbalance: DustImbalance<T>) {
+		let (asset_id, amount) = imbalance.into_parts();
+		Self::set_free_balance(&asset_id, who, Self::free_balance(&asset_id, who).saturating_add(amount));
+	}
+
 	/// Move `amount` from free balance to reserved balance.
 	///
 	/// If the free balance is lower than `amount`, then no funds will be moved and an `Err` will
@@ -692,7 +1557,7 @@ impl<T: Trait> Module<T> {
 		amount - slash
 	}
 
-	/// Check permission to perform burn, mint or update.
+	/// Check permission to perform burn, mint, update or freeze.
 	///
 	/// # Arguments
 	/// * `asset_id`:  A `T::AssetId` type that contains the `asset_id`, which has the permission embedded.
@@ -700,6 +1565,10 @@ impl<T: Trait> Module<T> {
 	/// * `what`: The permission to check.
 	///
 	pub fn check_permission(asset_id: &T::AssetId, who: &T::AccountId, what: &PermissionType) -> bool {
+		if let PermissionType::Freeze = what {
+			return Self::freeze_permission(asset_id) == Owner::Address(who.clone());
+		}
+
 		let permission_versions: PermissionVersions<T::AccountId> = Self::get_permission(asset_id);
 		let permission = permission_versions.into();
 
@@ -740,16 +1609,15 @@ impl<T: Trait> Module<T> {
 		reason: WithdrawReason,
 		new_balance: T::Balance,
 	) -> Result {
-		if asset_id != &Self::staking_asset_id() {
-			return Ok(());
-		}
+		ensure!(!Self::is_asset_frozen(asset_id), "asset is frozen");
+		ensure!(!Self::is_account_frozen(asset_id, who), "account is frozen");
 
-		let locks = Self::locks(who);
+		let locks = Self::locks(asset_id, who);
 		if locks.is_empty() {
 			return Ok(());
 		}
 		let now = <system::Module<T>>::block_number();
-		if Self::locks(who)
+		if locks
 			.into_iter()
 			.all(|l| now >= l.until || new_balance >= l.amount || !l.reasons.contains(reason))
 		{
@@ -761,25 +1629,196 @@ impl<T: Trait> Module<T> {
 
 	// PRIVATE MUTABLES
 
+	/// Set the reserved balance of an account's asset to some new value. Will enforce the
+	/// existential deposit of the asset, reaping the reserved balance if it falls below it.
+	///
 	/// NOTE: LOW-LEVEL: This will not attempt to maintain total issuance. It is expected that
 	/// the caller will do this.
-	fn set_reserved_balance(asset_id: &T::AssetId, who: &T::AccountId, balance: T::Balance) {
-		<ReservedBalance<T>>::insert(asset_id, who, &balance);
+	fn set_reserved_balance(asset_id: &T::AssetId, who: &T::AccountId, balance: T::Balance) -> UpdateBalanceOutcome {
+		if balance < Self::existential_deposit(asset_id) {
+			if Self::reserved_balance(asset_id, who).is_zero() {
+				// There is no reserved balance to reap, only the dust to remove.
+				Self::remove_dust(asset_id, balance);
+				return UpdateBalanceOutcome::AccountKilled;
+			}
+			<ReservedBalance<T>>::insert(asset_id, who, &balance);
+			Self::on_reserved_too_low(asset_id, who);
+			UpdateBalanceOutcome::AccountKilled
+		} else {
+			<ReservedBalance<T>>::insert(asset_id, who, &balance);
+			UpdateBalanceOutcome::Updated
+		}
 	}
 
+	/// Set the free balance of an account's asset to some new value. Will enforce the existential
+	/// deposit of the asset, reaping the free balance if it falls below it. Only a previously
+	/// non-zero free balance is reaped, a balance below the existential deposit is otherwise
+	/// just removed as dust.
+	///
 	/// NOTE: LOW-LEVEL: This will not attempt to maintain total issuance. It is expected that
 	/// the caller will do this.
-	fn set_free_balance(asset_id: &T::AssetId, who: &T::AccountId, balance: T::Balance) {
-		<FreeBalance<T>>::insert(asset_id, who, &balance);
+	fn set_free_balance(asset_id: &T::AssetId, who: &T::AccountId, balance: T::Balance) -> UpdateBalanceOutcome {
+		if balance < Self::existential_deposit(asset_id) {
+			if Self::free_balance(asset_id, who).is_zero() {
+				Self::remove_dust(asset_id, balance);
+				return UpdateBalanceOutcome::AccountKilled;
+			}
+			<FreeBalance<T>>::insert(asset_id, who, &balance);
+			Self::on_free_too_low(asset_id, who);
+			UpdateBalanceOutcome::AccountKilled
+		} else {
+			if !<FreeBalance<T>>::exists(asset_id, who) {
+				Self::add_holder(asset_id, who);
+				if !balance.is_zero() {
+					Self::new_account(asset_id, who, balance);
+				}
+			}
+			<FreeBalance<T>>::insert(asset_id, who, &balance);
+			UpdateBalanceOutcome::Updated
+		}
+	}
+
+	/// Remove an amount of an asset too low to be kept by any account.
+	fn remove_dust(asset_id: &T::AssetId, dust: T::Balance) {
+		if !dust.is_zero() {
+			T::DustRemoval::on_unbalanced(DustImbalance::new(*asset_id, dust));
+		}
+	}
+
+	/// Register a new account's balance of an asset.
+	fn new_account(asset_id: &T::AssetId, who: &T::AccountId, balance: T::Balance) {
+		T::OnNewAccount::on_new_account(asset_id, who);
+		Self::deposit_event(RawEvent::NewAccount(*asset_id, who.clone(), balance));
+	}
+
+	/// Unregister an account's balance of an asset.
+	///
+	/// This just removes the asset's balances for the account, the account may still hold others.
+	fn reap_account(asset_id: &T::AssetId, who: &T::AccountId) {
+		Self::remove_holder(asset_id, who);
+		Self::deposit_event(RawEvent::ReapedAccount(*asset_id, who.clone()));
+	}
+
+	/// Add `who` to the list of the holders of an asset kind, if not already in it.
+	fn add_holder(asset_id: &T::AssetId, who: &T::AccountId) {
+		if <Holders<T>>::exists(asset_id, who) {
+			return;
+		}
+
+		let head = <HoldersHead<T>>::get(asset_id);
+		if let Some(ref head) = head {
+			<Holders<T>>::mutate(asset_id, head, |linkage| {
+				if let Some(linkage) = linkage {
+					linkage.previous = Some(who.clone());
+				}
+			});
+		}
+		<Holders<T>>::insert(asset_id, who, HolderLinkage { previous: None, next: head });
+		<HoldersHead<T>>::insert(asset_id, who);
+		<HolderAssets<T>>::insert(asset_id, ());
+	}
+
+	/// Remove `who` from the list of the holders of an asset kind, if in it.
+	fn remove_holder(asset_id: &T::AssetId, who: &T::AccountId) {
+		let linkage = match <Holders<T>>::take(asset_id, who) {
+			Some(linkage) => linkage,
+			None => return,
+		};
+
+		if let Some(ref next) = linkage.next {
+			<Holders<T>>::mutate(asset_id, next, |next_linkage| {
+				if let Some(next_linkage) = next_linkage {
+					next_linkage.previous = linkage.previous.clone();
+				}
+			});
+		}
+		match linkage.previous {
+			Some(ref previous) => <Holders<T>>::mutate(asset_id, previous, |previous_linkage| {
+				if let Some(previous_linkage) = previous_linkage {
+					previous_linkage.next = linkage.next.clone();
+				}
+			}),
+			None => match linkage.next {
+				Some(ref next) => <HoldersHead<T>>::insert(asset_id, next),
+				None => {
+					<HoldersHead<T>>::remove(asset_id);
+					<HolderAssets<T>>::remove(asset_id);
+				}
+			},
+		}
+	}
+
+	/// Account's free balance of an asset has dropped below its existential deposit. Kill its
+	/// free side and the account's balance of the asset completely if its reserved size is zero.
+	fn on_free_too_low(asset_id: &T::AssetId, who: &T::AccountId) {
+		let dust = <FreeBalance<T>>::take(asset_id, who);
+		<AssetLocks<T>>::remove(asset_id, who);
+		<Vesting<T>>::remove(asset_id, who);
+		if asset_id == &Self::staking_asset_id() {
+			<Locks<T>>::remove(who);
+		}
+
+		Self::remove_dust(asset_id, dust);
+
+		T::OnFreeBalanceZero::on_free_balance_zero(asset_id, who);
+
+		if Self::reserved_balance(asset_id, who).is_zero() {
+			Self::reap_account(asset_id, who);
+		}
+	}
+
+	/// Account's reserved balance of an asset has dropped below its existential deposit. Kill its
+	/// reserved side and the account's balance of the asset completely if its free side is zero.
+	fn on_reserved_too_low(asset_id: &T::AssetId, who: &T::AccountId) {
+		let dust = <ReservedBalance<T>>::take(asset_id, who);
+		Self::remove_dust(asset_id, dust);
+
+		if Self::free_balance(asset_id, who).is_zero() {
+			Self::reap_account(asset_id, who);
+		}
+	}
+
+	/// Lock the amount of `who`'s balance of an asset kind which has not vested yet, removing its
+	/// vesting schedule once fully vested. Returns the amount still locked.
+	fn update_vesting_lock(asset_id: &T::AssetId, who: &T::AccountId) -> T::Balance {
+		let now = <system::Module<T>>::block_number();
+		let locked = Self::vesting(asset_id, who).map_or_else(Zero::zero, |v| v.locked_at(now));
+
+		if locked.is_zero() {
+			<Vesting<T>>::remove(asset_id, who);
+			Self::remove_lock(asset_id, VESTING_ID, who);
+		} else {
+			Self::set_lock(
+				asset_id,
+				VESTING_ID,
+				who,
+				locked,
+				T::BlockNumber::max_value(),
+				WithdrawReasons::except(WithdrawReason::TransactionPayment),
+			);
+		}
+
+		locked
+	}
+
+	/// Move the deprecated `Locks` of `who`, if any, to the staking asset before its locks on
+	/// `asset_id` are changed.
+	fn migrate_locks(asset_id: &T::AssetId, who: &T::AccountId) {
+		if asset_id == &Self::staking_asset_id() && <Locks<T>>::exists(who) {
+			let legacy = <Locks<T>>::take(who);
+			<AssetLocks<T>>::mutate(asset_id, who, |locks| locks.extend(legacy));
+		}
 	}
 
 	fn set_lock(
+		asset_id: &T::AssetId,
 		id: LockIdentifier,
 		who: &T::AccountId,
 		amount: T::Balance,
 		until: T::BlockNumber,
 		reasons: WithdrawReasons,
 	) {
+		Self::migrate_locks(asset_id, who);
 		let now = <system::Module<T>>::block_number();
 		let mut new_lock = Some(BalanceLock {
 			id,
@@ -787,7 +1826,7 @@ impl<T: Trait> Module<T> {
 			until,
 			reasons,
 		});
-		let mut locks = <Module<T>>::locks(who)
+		let mut locks = <AssetLocks<T>>::get(asset_id, who)
 			.into_iter()
 			.filter_map(|l| {
 				if l.id == id {
@@ -802,16 +1841,18 @@ impl<T: Trait> Module<T> {
 		if let Some(lock) = new_lock {
 			locks.push(lock)
 		}
-		<Locks<T>>::insert(who, locks);
+		<AssetLocks<T>>::insert(asset_id, who, locks);
 	}
 
 	fn extend_lock(
+		asset_id: &T::AssetId,
 		id: LockIdentifier,
 		who: &T::AccountId,
 		amount: T::Balance,
 		until: T::BlockNumber,
 		reasons: WithdrawReasons,
 	) {
+		Self::migrate_locks(asset_id, who);
 		let now = <system::Module<T>>::block_number();
 		let mut new_lock = Some(BalanceLock {
 			id,
@@ -819,7 +1860,7 @@ impl<T: Trait> Module<T> {
 			until,
 			reasons,
 		});
-		let mut locks = <Module<T>>::locks(who)
+		let mut locks = <AssetLocks<T>>::get(asset_id, who)
 			.into_iter()
 			.filter_map(|l| {
 				if l.id == id {
@@ -839,16 +1880,17 @@ impl<T: Trait> Module<T> {
 		if let Some(lock) = new_lock {
 			locks.push(lock)
 		}
-		<Locks<T>>::insert(who, locks);
+		<AssetLocks<T>>::insert(asset_id, who, locks);
 	}
 
-	fn remove_lock(id: LockIdentifier, who: &T::AccountId) {
+	fn remove_lock(asset_id: &T::AssetId, id: LockIdentifier, who: &T::AccountId) {
+		Self::migrate_locks(asset_id, who);
 		let now = <system::Module<T>>::block_number();
-		let locks = <Module<T>>::locks(who)
+		let locks = <AssetLocks<T>>::get(asset_id, who)
 			.into_iter()
 			.filter_map(|l| if l.until > now && l.id != id { Some(l) } else { None })
 			.collect::<Vec<_>>();
-		<Locks<T>>::insert(who, locks);
+		<AssetLocks<T>>::insert(asset_id, who, locks);
 	}
 }
 
@@ -897,6 +1939,45 @@ mod imbalances {
 		}
 	}
 
+	/// Opaque, move-only struct with private fields that serves as a token denoting that
+	/// funds of an asset chosen at runtime have been destroyed without any equal and opposite
+	/// accounting, such as the dust of a reaped account.
+	#[must_use]
+	pub struct DustImbalance<T: Subtrait>(T::AssetId, T::Balance);
+	impl<T: Subtrait> DustImbalance<T> {
+		pub fn new(asset_id: T::AssetId, amount: T::Balance) -> Self {
+			DustImbalance(asset_id, amount)
+		}
+
+		/// The asset of the destroyed funds.
+		pub fn asset_id(&self) -> T::AssetId {
+			self.0
+		}
+
+		/// The amount of the destroyed funds.
+		pub fn peek(&self) -> T::Balance {
+			self.1
+		}
+
+		/// Split into two imbalances of the same asset, the first of `amount` and the second of
+		/// the remainder. The first is of the whole amount if `amount` exceeds it.
+		pub fn split(self, amount: T::Balance) -> (Self, Self) {
+			let first = self.1.min(amount);
+			let second = self.1 - first;
+			let asset_id = self.0;
+			mem::forget(self);
+			(DustImbalance(asset_id, first), DustImbalance(asset_id, second))
+		}
+
+		/// Consume the imbalance without squaring up the total issuance, returning the asset
+		/// and amount of the destroyed funds so that they may be credited elsewhere.
+		pub(super) fn into_parts(self) -> (T::AssetId, T::Balance) {
+			let parts = (self.0, self.1);
+			mem::forget(self);
+			parts
+		}
+	}
+
 	impl<T, U> Imbalance<T::Balance> for PositiveImbalance<T, U>
 	where
 		T: Subtrait,
@@ -1016,6 +2097,13 @@ mod imbalances {
 			<super::TotalIssuance<super::ElevatedTrait<T>>>::mutate(&U::asset_id(), |v| *v = v.saturating_sub(self.0));
 		}
 	}
+
+	impl<T: Subtrait> Drop for DustImbalance<T> {
+		/// Basic drop handler will just square up the total issuance.
+		fn drop(&mut self) {
+			<super::TotalIssuance<super::ElevatedTrait<T>>>::mutate(&self.0, |v| *v = v.saturating_sub(self.1));
+		}
+	}
 }
 
 // TODO: #2052
@@ -1066,6 +2154,12 @@ impl<T: Subtrait> Trait for ElevatedTrait<T> {
 	type Balance = T::Balance;
 	type AssetId = T::AssetId;
 	type Event = ();
+	type DustRemoval = ();
+	type OnFreeBalanceZero = ();
+	type OnNewAccount = ();
+	type RestrictedTransferOrigin = system::EnsureSigned<T::AccountId, T::Doughnut>;
+	type MaxSymbolLength = ();
+	type MaxNameLength = ();
 }
 
 #[derive(Encode, Decode, Clone, PartialEq, Eq)]
@@ -1095,7 +2189,7 @@ where
 	}
 
 	fn minimum_balance() -> Self::Balance {
-		Zero::zero()
+		<Module<T>>::existential_deposit(&U::asset_id())
 	}
 
 	fn transfer(transactor: &T::AccountId, dest: &T::AccountId, value: Self::Balance) -> Result {
@@ -1115,11 +2209,14 @@ where
 		who: &T::AccountId,
 		value: Self::Balance,
 		reason: WithdrawReason,
-		_: ExistenceRequirement, // no existential deposit policy for generic asset
+		liveness: ExistenceRequirement,
 	) -> result::Result<Self::NegativeImbalance, &'static str> {
 		let new_balance = Self::free_balance(who)
 			.checked_sub(&value)
 			.ok_or_else(|| "account has too few funds")?;
+		if liveness == ExistenceRequirement::KeepAlive && new_balance < Self::minimum_balance() {
+			return Err("payment would kill account")
+		}
 		Self::ensure_can_withdraw(who, value, reason, new_balance)?;
 		<Module<T>>::set_free_balance(&U::asset_id(), who, new_balance);
 		Ok(NegativeImbalance::new(value))
@@ -1129,8 +2226,12 @@ where
 		who: &T::AccountId,
 		value: Self::Balance,
 	) -> result::Result<Self::PositiveImbalance, &'static str> {
-		// No existential deposit rule and creation fee in GA. `deposit_into_existing` is same with `deposit_creating`.
-		Ok(Self::deposit_creating(who, value))
+		// Accounts of assets without an existential deposit are never reaped, so they always exist.
+		if !Self::minimum_balance().is_zero() && Self::total_balance(who).is_zero() {
+			return Err("beneficiary account must pre-exist");
+		}
+		<Module<T>>::set_free_balance(&U::asset_id(), who, Self::free_balance(who) + value);
+		Ok(PositiveImbalance::new(value))
 	}
 
 	fn deposit_creating(who: &T::AccountId, value: Self::Balance) -> Self::PositiveImbalance {
@@ -1151,13 +2252,21 @@ where
 		UpdateBalanceOutcome,
 	) {
 		let original = <Module<T>>::free_balance(&U::asset_id(), who);
+		if balance < Self::minimum_balance() && original.is_zero() {
+			// Setting a non-existent account to less than the existential deposit is a no-op, so
+			// bypass it rather than account for the dust before its equal and opposite cause.
+			return (
+				SignedImbalance::Positive(Self::PositiveImbalance::zero()),
+				UpdateBalanceOutcome::AccountKilled,
+			)
+		}
 		let imbalance = if original <= balance {
 			SignedImbalance::Positive(PositiveImbalance::new(balance - original))
 		} else {
 			SignedImbalance::Negative(NegativeImbalance::new(original - balance))
 		};
-		<Module<T>>::set_free_balance(&U::asset_id(), who, balance);
-		(imbalance, UpdateBalanceOutcome::Updated)
+		let outcome = <Module<T>>::set_free_balance(&U::asset_id(), who, balance);
+		(imbalance, outcome)
 	}
 
 	fn can_slash(who: &T::AccountId, value: Self::Balance) -> bool {
@@ -1254,10 +2363,11 @@ impl<T: Trait> AssetIdProvider for SpendingAssetIdProvider<T> {
 	}
 }
 
-impl<T> LockableCurrency<T::AccountId> for AssetCurrency<T, StakingAssetIdProvider<T>>
+impl<T, U> LockableCurrency<T::AccountId> for AssetCurrency<T, U>
 where
 	T: Trait,
 	T::Balance: MaybeSerializeDebug,
+	U: AssetIdProvider<AssetId = T::AssetId>,
 {
 	type Moment = T::BlockNumber;
 
@@ -1268,7 +2378,7 @@ where
 		until: T::BlockNumber,
 		reasons: WithdrawReasons,
 	) {
-		<Module<T>>::set_lock(id, who, amount, until, reasons)
+		<Module<T>>::set_lock(&U::asset_id(), id, who, amount, until, reasons)
 	}
 
 	fn extend_lock(
@@ -1278,11 +2388,182 @@ where
 		until: T::BlockNumber,
 		reasons: WithdrawReasons,
 	) {
-		<Module<T>>::extend_lock(id, who, amount, until, reasons)
+		<Module<T>>::extend_lock(&U::asset_id(), id, who, amount, until, reasons)
 	}
 
 	fn remove_lock(id: LockIdentifier, who: &T::AccountId) {
-		<Module<T>>::remove_lock(id, who)
+		<Module<T>>::remove_lock(&U::asset_id(), id, who)
+	}
+}
+
+impl<T: Trait> MultiCurrency<T::AccountId> for Module<T> {
+	type AssetId = T::AssetId;
+	type Balance = T::Balance;
+
+	fn total_balance(asset_id: &T::AssetId, who: &T::AccountId) -> T::Balance {
+		Self::free_balance(asset_id, who) + Self::reserved_balance(asset_id, who)
+	}
+
+	fn can_slash(asset_id: &T::AssetId, who: &T::AccountId, value: T::Balance) -> bool {
+		Self::free_balance(asset_id, who) >= value
+	}
+
+	fn total_issuance(asset_id: &T::AssetId) -> T::Balance {
+		<TotalIssuance<T>>::get(asset_id)
+	}
+
+	fn minimum_balance(asset_id: &T::AssetId) -> T::Balance {
+		Self::existential_deposit(asset_id)
+	}
+
+	fn free_balance(asset_id: &T::AssetId, who: &T::AccountId) -> T::Balance {
+		<FreeBalance<T>>::get(asset_id, who)
+	}
+
+	fn ensure_can_withdraw(
+		asset_id: &T::AssetId,
+		who: &T::AccountId,
+		amount: T::Balance,
+		reason: WithdrawReason,
+		new_balance: T::Balance,
+	) -> Result {
+		<Module<T>>::ensure_can_withdraw(asset_id, who, amount, reason, new_balance)
+	}
+
+	fn ensure_can_transfer(asset_id: &T::AssetId, source: &T::AccountId, dest: &T::AccountId) -> Result {
+		if Self::is_sender_restricted(asset_id) {
+			Self::ensure_sender(system::RawOrigin::Signed(source.clone()).into(), true)?;
+		}
+		ensure!(Self::can_receive(asset_id, dest), "destination is not allowed to receive asset");
+		Ok(())
+	}
+
+	fn transfer(asset_id: &T::AssetId, source: &T::AccountId, dest: &T::AccountId, value: T::Balance) -> Result {
+		Self::make_transfer(asset_id, source, dest, value)
+	}
+
+	fn deposit(asset_id: &T::AssetId, who: &T::AccountId, value: T::Balance) -> Result {
+		if value.is_zero() {
+			return Ok(());
+		}
+		let balance = <FreeBalance<T>>::get(asset_id, who);
+		if balance.is_zero() && value < Self::existential_deposit(asset_id) {
+			return Err("value too low to create account");
+		}
+		let new_total_issuance = <TotalIssuance<T>>::get(asset_id)
+			.checked_add(&value)
+			.ok_or_else(|| "total_issuance got overflow after deposit.")?;
+		let new_balance = balance
+			.checked_add(&value)
+			.ok_or_else(|| "free balance got overflow after deposit.")?;
+
+		<TotalIssuance<T>>::insert(asset_id, new_total_issuance);
+		Self::set_free_balance(asset_id, who, new_balance);
+
+		Ok(())
+	}
+
+	fn withdraw(
+		asset_id: &T::AssetId,
+		who: &T::AccountId,
+		value: T::Balance,
+		reason: WithdrawReason,
+		liveness: ExistenceRequirement,
+	) -> Result {
+		let new_balance = <FreeBalance<T>>::get(asset_id, who)
+			.checked_sub(&value)
+			.ok_or_else(|| "account has too few funds")?;
+		if liveness == ExistenceRequirement::KeepAlive && new_balance < Self::existential_deposit(asset_id) {
+			return Err("payment would kill account")
+		}
+		Self::withdraw_and_burn(asset_id, who, value, reason)
+	}
+
+	fn slash(asset_id: &T::AssetId, who: &T::AccountId, value: T::Balance) -> T::Balance {
+		let remaining = <Module<T>>::slash(asset_id, who, value).unwrap_or_else(Zero::zero);
+		<TotalIssuance<T>>::mutate(asset_id, |issued| *issued = issued.saturating_sub(value - remaining));
+		remaining
+	}
+
+	fn make_free_balance_be(asset_id: &T::AssetId, who: &T::AccountId, balance: T::Balance) -> UpdateBalanceOutcome {
+		let original = Self::free_balance(asset_id, who);
+		if balance < Self::existential_deposit(asset_id) && original.is_zero() {
+			// Setting a non-existent account to less than the existential deposit is a no-op.
+			return UpdateBalanceOutcome::AccountKilled;
+		}
+		if original <= balance {
+			<TotalIssuance<T>>::mutate(asset_id, |issued| *issued = issued.saturating_add(balance - original));
+		} else {
+			<TotalIssuance<T>>::mutate(asset_id, |issued| *issued = issued.saturating_sub(original - balance));
+		}
+		Self::set_free_balance(asset_id, who, balance)
+	}
+}
+
+impl<T: Trait> MultiReservableCurrency<T::AccountId> for Module<T> {
+	fn can_reserve(asset_id: &T::AssetId, who: &T::AccountId, value: T::Balance) -> bool {
+		<FreeBalance<T>>::get(asset_id, who)
+			.checked_sub(&value)
+			.map_or(false, |new_balance|
+				Self::ensure_can_withdraw(asset_id, who, value, WithdrawReason::Reserve, new_balance).is_ok()
+			)
+	}
+
+	fn slash_reserved(asset_id: &T::AssetId, who: &T::AccountId, value: T::Balance) -> T::Balance {
+		let remaining = <Module<T>>::slash_reserved(asset_id, who, value).unwrap_or_else(Zero::zero);
+		<TotalIssuance<T>>::mutate(asset_id, |issued| *issued = issued.saturating_sub(value - remaining));
+		remaining
+	}
+
+	fn reserved_balance(asset_id: &T::AssetId, who: &T::AccountId) -> T::Balance {
+		<ReservedBalance<T>>::get(asset_id, who)
+	}
+
+	fn reserve(asset_id: &T::AssetId, who: &T::AccountId, value: T::Balance) -> Result {
+		<Module<T>>::reserve(asset_id, who, value)
+	}
+
+	fn unreserve(asset_id: &T::AssetId, who: &T::AccountId, value: T::Balance) -> T::Balance {
+		<Module<T>>::unreserve(asset_id, who, value)
+	}
+
+	fn repatriate_reserved(
+		asset_id: &T::AssetId,
+		slashed: &T::AccountId,
+		beneficiary: &T::AccountId,
+		value: T::Balance,
+	) -> result::Result<T::Balance, &'static str> {
+		Ok(<Module<T>>::repatriate_reserved(asset_id, slashed, beneficiary, value))
+	}
+}
+
+impl<T: Trait> MultiLockableCurrency<T::AccountId> for Module<T> {
+	type Moment = T::BlockNumber;
+
+	fn set_lock(
+		asset_id: &T::AssetId,
+		id: LockIdentifier,
+		who: &T::AccountId,
+		amount: T::Balance,
+		until: T::BlockNumber,
+		reasons: WithdrawReasons,
+	) {
+		<Module<T>>::set_lock(asset_id, id, who, amount, until, reasons)
+	}
+
+	fn extend_lock(
+		asset_id: &T::AssetId,
+		id: LockIdentifier,
+		who: &T::AccountId,
+		amount: T::Balance,
+		until: T::BlockNumber,
+		reasons: WithdrawReasons,
+	) {
+		<Module<T>>::extend_lock(asset_id, id, who, amount, until, reasons)
+	}
+
+	fn remove_lock(asset_id: &T::AssetId, id: LockIdentifier, who: &T::AccountId) {
+		<Module<T>>::remove_lock(asset_id, id, who)
 	}
 }
 
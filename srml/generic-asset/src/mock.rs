@@ -26,7 +26,8 @@ use sr_primitives::{
 	traits::{BlakeTwo256, IdentityLookup},
 };
 use primitives::{Blake2Hasher, H256};
-use support::{parameter_types, impl_outer_event, impl_outer_origin};
+use support::{parameter_types, impl_outer_event, impl_outer_origin, traits::Contains};
+use std::cell::RefCell;
 
 use super::*;
 
@@ -44,6 +45,8 @@ parameter_types! {
 	pub const MaximumBlockWeight: u32 = 1024;
 	pub const MaximumBlockLength: u32 = 2 * 1024;
 	pub const AvailableBlockRatio: Perbill = Perbill::one();
+	pub const MaxSymbolLength: u32 = 8;
+	pub const MaxNameLength: u32 = 32;
 }
 impl system::Trait for Test {
 	type Origin = Origin;
@@ -66,10 +69,42 @@ impl system::Trait for Test {
 	type DelegatedDispatchVerifier = ();
 }
 
+thread_local! {
+	pub static REAPED: RefCell<Vec<(u32, u64)>> = RefCell::new(vec![]);
+	pub static CREATED: RefCell<Vec<(u32, u64)>> = RefCell::new(vec![]);
+}
+
+/// Records the account callbacks of the module.
+pub struct TestCallbacks;
+impl OnAssetFreeBalanceZero<u32, u64> for TestCallbacks {
+	fn on_free_balance_zero(asset_id: &u32, who: &u64) {
+		REAPED.with(|r| r.borrow_mut().push((*asset_id, *who)));
+	}
+}
+impl OnNewAssetAccount<u32, u64> for TestCallbacks {
+	fn on_new_account(asset_id: &u32, who: &u64) {
+		CREATED.with(|r| r.borrow_mut().push((*asset_id, *who)));
+	}
+}
+
+/// The accounts which may send restricted assets.
+pub struct Verified;
+impl Contains<u64> for Verified {
+	fn contains(who: &u64) -> bool {
+		*who == 1 || *who == 2
+	}
+}
+
 impl Trait for Test {
 	type Balance = u64;
 	type AssetId = u32;
 	type Event = TestEvent;
+	type DustRemoval = ();
+	type OnFreeBalanceZero = TestCallbacks;
+	type OnNewAccount = TestCallbacks;
+	type RestrictedTransferOrigin = system::EnsureSignedBy<Verified, u64, ()>;
+	type MaxSymbolLength = MaxSymbolLength;
+	type MaxNameLength = MaxNameLength;
 }
 
 mod generic_asset {
@@ -91,6 +126,8 @@ pub struct ExtBuilder {
 	next_asset_id: u32,
 	accounts: Vec<u64>,
 	initial_balance: u64,
+	asset_meta: Vec<(u32, AssetInfo<u64>)>,
+	vesting: Vec<(u32, u64, u64, u64, u64)>,
 }
 
 // Returns default values for genesis config
@@ -101,6 +138,8 @@ impl Default for ExtBuilder {
 			next_asset_id: 1000,
 			accounts: vec![0],
 			initial_balance: 0,
+			asset_meta: vec![],
+			vesting: vec![],
 		}
 	}
 }
@@ -119,8 +158,22 @@ impl ExtBuilder {
 		self
 	}
 
+	// Registers asset metadata in genesis config
+	pub fn asset_info(mut self, asset_id: u32, info: AssetInfo<u64>) -> Self {
+		self.asset_meta.push((asset_id, info));
+		self
+	}
+
+	// Adds a vesting schedule of `(asset_id, who, begin, length, liquid)` to genesis config
+	pub fn vesting(mut self, vesting: (u32, u64, u64, u64, u64)) -> Self {
+		self.vesting.push(vesting);
+		self
+	}
+
 	// builds genesis config
 	pub fn build(self) -> runtime_io::TestExternalities<Blake2Hasher> {
+		REAPED.with(|r| r.borrow_mut().clear());
+		CREATED.with(|r| r.borrow_mut().clear());
 		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
 
 		GenesisConfig::<Test> {
@@ -130,6 +183,8 @@ impl ExtBuilder {
 				next_asset_id: self.next_asset_id,
 				staking_asset_id: 16000,
 				spending_asset_id: 16001,
+				asset_meta: self.asset_meta,
+				vesting: self.vesting,
 			}
 			.assimilate_storage(&mut t).unwrap();
 
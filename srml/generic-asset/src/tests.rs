@@ -21,9 +21,9 @@
 #![cfg(test)]
 
 use super::*;
-use crate::mock::{new_test_ext, ExtBuilder, GenericAsset, Origin, System, Test, TestEvent};
+use crate::mock::{new_test_ext, ExtBuilder, GenericAsset, Origin, System, Test, TestEvent, CREATED, REAPED};
 use runtime_io::with_externalities;
-use support::{assert_noop, assert_ok};
+use support::{assert_noop, assert_ok, dispatch::GetDispatchInfo};
 
 #[test]
 fn issuing_asset_units_to_issuer_should_work() {
@@ -1286,3 +1286,1249 @@ fn burn_should_raise_event() {
 		},
 	);
 }
+
+#[test]
+fn withdraw_and_burn_should_decrease_free_balance_and_total_issuance() {
+	let asset_id = 16001;
+	let who = 1;
+
+	with_externalities(&mut ExtBuilder::default().free_balance((asset_id, who, 100)).build(), || {
+		assert_ok!(GenericAsset::withdraw_and_burn(&asset_id, &who, 40, WithdrawReason::TransactionPayment));
+
+		assert_eq!(GenericAsset::free_balance(&asset_id, &who), 60);
+		assert_eq!(GenericAsset::total_issuance(&asset_id), 60);
+	});
+}
+
+#[test]
+fn withdraw_and_burn_should_fail_with_insufficient_funds() {
+	let asset_id = 16001;
+	let who = 1;
+
+	with_externalities(&mut ExtBuilder::default().free_balance((asset_id, who, 100)).build(), || {
+		assert_noop!(
+			GenericAsset::withdraw_and_burn(&asset_id, &who, 101, WithdrawReason::TransactionPayment),
+			"account has too few funds"
+		);
+		assert_eq!(GenericAsset::total_issuance(&asset_id), 100);
+	});
+}
+
+#[test]
+fn withdraw_into_imbalance_should_decrease_total_issuance_when_dropped() {
+	let asset_id = 16001;
+	let who = 1;
+
+	with_externalities(&mut ExtBuilder::default().free_balance((asset_id, who, 100)).build(), || {
+		let imbalance = GenericAsset::withdraw_into_imbalance(&asset_id, &who, 40, WithdrawReason::TransactionPayment)
+			.unwrap();
+		assert_eq!(imbalance.asset_id(), asset_id);
+		assert_eq!(imbalance.peek(), 40);
+		assert_eq!(GenericAsset::free_balance(&asset_id, &who), 60);
+		assert_eq!(GenericAsset::total_issuance(&asset_id), 100);
+
+		drop(imbalance);
+		assert_eq!(GenericAsset::total_issuance(&asset_id), 60);
+	});
+}
+
+#[test]
+fn resolve_imbalance_should_credit_account_and_keep_total_issuance() {
+	let asset_id = 16001;
+	let who = 1;
+
+	with_externalities(&mut ExtBuilder::default().free_balance((asset_id, who, 100)).build(), || {
+		let imbalance = GenericAsset::withdraw_into_imbalance(&asset_id, &who, 40, WithdrawReason::TransactionPayment)
+			.unwrap();
+		let (first, second) = imbalance.split(30);
+		assert_eq!((first.peek(), second.peek()), (30, 10));
+		GenericAsset::resolve_imbalance(&2, first);
+		GenericAsset::resolve_imbalance(&3, second);
+
+		assert_eq!(GenericAsset::free_balance(&asset_id, &who), 60);
+		assert_eq!(GenericAsset::free_balance(&asset_id, &2), 30);
+		assert_eq!(GenericAsset::free_balance(&asset_id, &3), 10);
+		assert_eq!(GenericAsset::total_issuance(&asset_id), 100);
+	});
+}
+
+#[test]
+fn update_asset_info_should_work_with_update_permission() {
+	let asset_id = 1000;
+	let origin = 1;
+	let permissions = PermissionLatest {
+		update: Owner::Address(origin),
+		mint: Owner::None,
+		burn: Owner::None,
+	};
+	let info = AssetInfo {
+		symbol: b"PLUG".to_vec(),
+		decimal_places: 4,
+		name: b"Plug Token".to_vec(),
+		existential_deposit: Some(5),
+	};
+
+	with_externalities(&mut ExtBuilder::default().next_asset_id(asset_id).build(), || {
+		assert_ok!(GenericAsset::create(
+			Origin::signed(origin),
+			AssetOptions {
+				initial_issuance: 0,
+				permissions,
+			}
+		));
+
+		assert_ok!(GenericAsset::update_asset_info(Origin::signed(origin), asset_id, info.clone()));
+
+		assert_eq!(GenericAsset::asset_meta(asset_id), info);
+		assert_eq!(GenericAsset::registered_assets(), vec![(asset_id, info.clone())]);
+		assert!(System::events().iter().any(|record| record.event
+			== TestEvent::generic_asset(RawEvent::AssetInfoUpdated(asset_id, info.clone()))));
+	});
+}
+
+#[test]
+fn update_asset_info_should_fail_without_update_permission() {
+	let asset_id = 1000;
+	let origin = 1;
+	let permissions = PermissionLatest {
+		update: Owner::Address(origin),
+		mint: Owner::Address(origin),
+		burn: Owner::Address(origin),
+	};
+	let info = AssetInfo {
+		symbol: b"PLUG".to_vec(),
+		decimal_places: 4,
+		name: b"Plug Token".to_vec(),
+		existential_deposit: None,
+	};
+
+	with_externalities(&mut ExtBuilder::default().next_asset_id(asset_id).build(), || {
+		assert_ok!(GenericAsset::create(
+			Origin::signed(origin),
+			AssetOptions {
+				initial_issuance: 0,
+				permissions,
+			}
+		));
+
+		assert_noop!(
+			GenericAsset::update_asset_info(Origin::signed(2), asset_id, info),
+			"Origin does not have enough permission to update asset info."
+		);
+		assert!(GenericAsset::registered_assets().is_empty());
+	});
+}
+
+#[test]
+fn update_asset_info_should_fail_with_too_long_symbol_or_name() {
+	let asset_id = 1000;
+	let origin = 1;
+	let permissions = PermissionLatest {
+		update: Owner::Address(origin),
+		mint: Owner::None,
+		burn: Owner::None,
+	};
+	let info = AssetInfo {
+		symbol: b"PLUG".to_vec(),
+		decimal_places: 4,
+		name: b"Plug Token".to_vec(),
+		existential_deposit: None,
+	};
+
+	with_externalities(&mut ExtBuilder::default().next_asset_id(asset_id).build(), || {
+		assert_ok!(GenericAsset::create(
+			Origin::signed(origin),
+			AssetOptions {
+				initial_issuance: 0,
+				permissions,
+			}
+		));
+
+		// The mock allows symbols of up to 8 bytes and names of up to 32 bytes
+		assert_noop!(
+			GenericAsset::update_asset_info(
+				Origin::signed(origin),
+				asset_id,
+				AssetInfo { symbol: vec![b'P'; 9], ..info.clone() },
+			),
+			"asset symbol is too long"
+		);
+		assert_noop!(
+			GenericAsset::update_asset_info(
+				Origin::signed(origin),
+				asset_id,
+				AssetInfo { name: vec![b'P'; 33], ..info.clone() },
+			),
+			"asset name is too long"
+		);
+		assert_ok!(GenericAsset::update_asset_info(
+			Origin::signed(origin),
+			asset_id,
+			AssetInfo { symbol: vec![b'P'; 8], name: vec![b'P'; 32], ..info },
+		));
+	});
+}
+
+#[test]
+fn update_asset_info_should_not_raise_existential_deposit_once_issued() {
+	let asset_id = 1000;
+	let origin = 1;
+	let permissions = PermissionLatest {
+		update: Owner::Address(origin),
+		mint: Owner::Address(origin),
+		burn: Owner::None,
+	};
+
+	with_externalities(&mut ExtBuilder::default().next_asset_id(asset_id).build(), || {
+		assert_ok!(GenericAsset::create(
+			Origin::signed(origin),
+			AssetOptions {
+				initial_issuance: 0,
+				permissions,
+			}
+		));
+
+		// nothing has been issued, so the existential deposit may be raised
+		assert_ok!(GenericAsset::update_asset_info(
+			Origin::signed(origin),
+			asset_id,
+			info_with_existential_deposit(10)
+		));
+		assert_ok!(GenericAsset::mint(Origin::signed(origin), asset_id, 2, 100));
+
+		assert_noop!(
+			GenericAsset::update_asset_info(Origin::signed(origin), asset_id, info_with_existential_deposit(20)),
+			"existential deposit can not be raised once the asset has been issued"
+		);
+		assert_eq!(GenericAsset::existential_deposit(&asset_id), 10);
+
+		assert_ok!(GenericAsset::update_asset_info(
+			Origin::signed(origin),
+			asset_id,
+			info_with_existential_deposit(10)
+		));
+		assert_ok!(GenericAsset::update_asset_info(
+			Origin::signed(origin),
+			asset_id,
+			info_with_existential_deposit(5)
+		));
+		assert_eq!(GenericAsset::existential_deposit(&asset_id), 5);
+		assert_eq!(GenericAsset::free_balance(&asset_id, &2), 100);
+	});
+}
+
+#[test]
+fn asset_info_should_be_registered_at_genesis() {
+	let staking_info = AssetInfo {
+		symbol: b"STK".to_vec(),
+		decimal_places: 18,
+		name: b"Staking".to_vec(),
+		existential_deposit: Some(1),
+	};
+	let spending_info = AssetInfo {
+		symbol: b"SPD".to_vec(),
+		decimal_places: 18,
+		name: b"Spending".to_vec(),
+		existential_deposit: None,
+	};
+
+	with_externalities(
+		&mut ExtBuilder::default()
+			.asset_info(16000, staking_info.clone())
+			.asset_info(16001, spending_info.clone())
+			.build(),
+		|| {
+			assert_eq!(GenericAsset::asset_meta(16000), staking_info);
+			assert_eq!(GenericAsset::asset_meta(16001), spending_info);
+			assert_eq!(GenericAsset::asset_meta(16002), AssetInfo::default());
+
+			let mut registered = GenericAsset::registered_assets();
+			registered.sort_by_key(|(asset_id, _)| *asset_id);
+			assert_eq!(registered, vec![(16000, staking_info.clone()), (16001, spending_info.clone())]);
+		},
+	);
+}
+
+const ID_1: LockIdentifier = *b"1       ";
+const ID_2: LockIdentifier = *b"2       ";
+
+fn endow(who: u64, balance: u64) {
+	GenericAsset::set_free_balance(&16000, &who, balance);
+	GenericAsset::set_free_balance(&16001, &who, balance);
+}
+
+#[test]
+fn staking_asset_lock_should_not_restrict_spending_asset() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		endow(1, 100);
+		StakingAssetCurrency::<Test>::set_lock(ID_1, &1, 90, u64::max_value(), WithdrawReasons::all());
+
+		assert_noop!(
+			GenericAsset::make_transfer(&16000, &1, &2, 20),
+			"account liquidity restrictions prevent withdrawal"
+		);
+		assert_ok!(GenericAsset::make_transfer(&16001, &1, &2, 20));
+		assert!(GenericAsset::locks(&16001, &1).is_empty());
+	});
+}
+
+#[test]
+fn spending_asset_lock_should_not_restrict_staking_asset() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		endow(1, 100);
+		SpendingAssetCurrency::<Test>::set_lock(ID_1, &1, 90, u64::max_value(), WithdrawReasons::all());
+
+		assert_noop!(
+			GenericAsset::make_transfer(&16001, &1, &2, 20),
+			"account liquidity restrictions prevent withdrawal"
+		);
+		assert_ok!(GenericAsset::make_transfer(&16000, &1, &2, 20));
+
+		SpendingAssetCurrency::<Test>::remove_lock(ID_1, &1);
+		assert_ok!(GenericAsset::make_transfer(&16001, &1, &2, 20));
+	});
+}
+
+#[test]
+fn legacy_locks_should_restrict_staking_asset_until_migrated() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		endow(1, 100);
+		let legacy_lock = BalanceLock {
+			id: ID_1,
+			amount: 90,
+			until: u64::max_value(),
+			reasons: WithdrawReasons::all(),
+		};
+		<Locks<Test>>::insert(1, vec![legacy_lock.clone()]);
+
+		assert_eq!(GenericAsset::locks(&16000, &1), vec![legacy_lock.clone()]);
+		assert_noop!(
+			GenericAsset::make_transfer(&16000, &1, &2, 20),
+			"account liquidity restrictions prevent withdrawal"
+		);
+		assert_ok!(GenericAsset::make_transfer(&16001, &1, &2, 20));
+
+		// locks on the spending asset leave the legacy locks in place
+		SpendingAssetCurrency::<Test>::set_lock(ID_2, &1, 10, u64::max_value(), WithdrawReasons::all());
+		assert!(<Locks<Test>>::exists(1));
+
+		// setting another lock moves the legacy locks to the staking asset
+		StakingAssetCurrency::<Test>::set_lock(ID_2, &1, 10, u64::max_value(), WithdrawReasons::all());
+		assert!(!<Locks<Test>>::exists(1));
+		assert_eq!(GenericAsset::locks(&16000, &1).len(), 2);
+		assert!(GenericAsset::locks(&16000, &1).contains(&legacy_lock));
+		assert_noop!(
+			GenericAsset::make_transfer(&16000, &1, &2, 20),
+			"account liquidity restrictions prevent withdrawal"
+		);
+
+		StakingAssetCurrency::<Test>::remove_lock(ID_1, &1);
+		assert_ok!(GenericAsset::make_transfer(&16000, &1, &2, 20));
+	});
+}
+
+fn info_with_existential_deposit(existential_deposit: u64) -> AssetInfo<u64> {
+	AssetInfo {
+		existential_deposit: Some(existential_deposit),
+		..Default::default()
+	}
+}
+
+#[test]
+fn transfer_leaving_dust_should_reap_sender() {
+	with_externalities(
+		&mut ExtBuilder::default()
+			.free_balance((16000, 1, 100))
+			.asset_info(16000, info_with_existential_deposit(10))
+			.build(),
+		|| {
+			assert_ok!(GenericAsset::make_transfer_with_event(&16000, &1, &2, 95));
+
+			assert_eq!(GenericAsset::free_balance(&16000, &1), 0);
+			assert!(!<FreeBalance<Test>>::exists(&16000, &1));
+			assert_eq!(GenericAsset::free_balance(&16000, &2), 95);
+			// the dust is burned
+			assert_eq!(GenericAsset::total_issuance(&16000), 95);
+
+			REAPED.with(|r| assert_eq!(*r.borrow(), vec![(16000, 1)]));
+			CREATED.with(|r| assert_eq!(*r.borrow(), vec![(16000, 2)]));
+			assert!(System::events().iter().any(|record| record.event
+				== TestEvent::generic_asset(RawEvent::ReapedAccount(16000, 1))));
+			assert!(System::events().iter().any(|record| record.event
+				== TestEvent::generic_asset(RawEvent::NewAccount(16000, 2, 95))));
+		},
+	);
+}
+
+#[test]
+fn transfer_below_existential_deposit_should_not_create_account() {
+	with_externalities(
+		&mut ExtBuilder::default()
+			.free_balance((16000, 1, 100))
+			.asset_info(16000, info_with_existential_deposit(10))
+			.build(),
+		|| {
+			assert_noop!(
+				GenericAsset::make_transfer(&16000, &1, &2, 9),
+				"value too low to create account"
+			);
+			assert_ok!(GenericAsset::make_transfer(&16000, &1, &2, 10));
+			// an existing account can receive less than the existential deposit
+			assert_ok!(GenericAsset::make_transfer(&16000, &1, &2, 1));
+			assert_eq!(GenericAsset::free_balance(&16000, &2), 11);
+		},
+	);
+}
+
+#[test]
+fn account_which_never_existed_should_not_be_reaped() {
+	with_externalities(
+		&mut ExtBuilder::default()
+			.free_balance((16000, 1, 100))
+			.asset_info(16000, info_with_existential_deposit(10))
+			.build(),
+		|| {
+			assert_eq!(GenericAsset::slash(&16000, &2, 5), Some(5));
+			assert_eq!(GenericAsset::slash_reserved(&16000, &2, 5), Some(5));
+
+			REAPED.with(|r| assert!(r.borrow().is_empty()));
+			assert!(!System::events().iter().any(|record| record.event
+				== TestEvent::generic_asset(RawEvent::ReapedAccount(16000, 2))));
+			assert_eq!(GenericAsset::total_issuance(&16000), 100);
+		},
+	);
+}
+
+#[test]
+fn mint_and_burn_should_respect_existential_deposit() {
+	with_externalities(&mut ExtBuilder::default().next_asset_id(1000).build(), || {
+		assert_ok!(GenericAsset::create(
+			Origin::signed(1),
+			AssetOptions {
+				initial_issuance: 0,
+				permissions: PermissionLatest {
+					update: Owner::Address(1),
+					mint: Owner::Address(1),
+					burn: Owner::Address(1),
+				},
+			}
+		));
+		assert_ok!(GenericAsset::update_asset_info(Origin::signed(1), 1000, info_with_existential_deposit(10)));
+
+		assert_noop!(GenericAsset::mint(Origin::signed(1), 1000, 2, 5), "value too low to create account");
+		assert_ok!(GenericAsset::mint(Origin::signed(1), 1000, 2, 15));
+
+		assert_noop!(
+			GenericAsset::burn(Origin::signed(1), 1000, 2, 10),
+			"burn would leave less than the existential deposit"
+		);
+		assert_ok!(GenericAsset::burn(Origin::signed(1), 1000, 2, 15));
+		assert_eq!(GenericAsset::total_issuance(&1000), 0);
+		REAPED.with(|r| assert_eq!(*r.borrow(), vec![(1000, 2)]));
+	});
+}
+
+#[test]
+fn asset_without_existential_deposit_should_not_reap_accounts() {
+	with_externalities(&mut ExtBuilder::default().free_balance((16000, 1, 100)).build(), || {
+		assert_ok!(GenericAsset::make_transfer(&16000, &1, &2, 100));
+
+		assert!(<FreeBalance<Test>>::exists(&16000, &1));
+		assert_eq!(GenericAsset::total_issuance(&16000), 100);
+		REAPED.with(|r| assert!(r.borrow().is_empty()));
+	});
+}
+
+#[test]
+fn reserved_dust_should_be_removed() {
+	with_externalities(
+		&mut ExtBuilder::default()
+			.free_balance((16000, 1, 100))
+			.asset_info(16000, info_with_existential_deposit(10))
+			.build(),
+		|| {
+			assert_ok!(GenericAsset::reserve(&16000, &1, 50));
+			assert_eq!(GenericAsset::unreserve(&16000, &1, 45), 0);
+
+			assert_eq!(GenericAsset::reserved_balance(&16000, &1), 0);
+			assert!(!<ReservedBalance<Test>>::exists(&16000, &1));
+			assert_eq!(GenericAsset::free_balance(&16000, &1), 95);
+			assert_eq!(GenericAsset::total_issuance(&16000), 95);
+			// the account still holds a free balance
+			REAPED.with(|r| assert!(r.borrow().is_empty()));
+		},
+	);
+}
+
+#[test]
+fn asset_currency_should_respect_existential_deposit() {
+	with_externalities(
+		&mut ExtBuilder::default()
+			.free_balance((16000, 1, 100))
+			.asset_info(16000, info_with_existential_deposit(10))
+			.build(),
+		|| {
+			assert_eq!(StakingAssetCurrency::<Test>::minimum_balance(), 10);
+			assert_eq!(SpendingAssetCurrency::<Test>::minimum_balance(), 0);
+
+			assert_noop!(
+				StakingAssetCurrency::<Test>::withdraw(&1, 95, WithdrawReason::Fee, ExistenceRequirement::KeepAlive),
+				"payment would kill account"
+			);
+			assert_noop!(
+				StakingAssetCurrency::<Test>::deposit_into_existing(&2, 50),
+				"beneficiary account must pre-exist"
+			);
+
+			match StakingAssetCurrency::<Test>::make_free_balance_be(&2, 5) {
+				(SignedImbalance::Positive(imbalance), UpdateBalanceOutcome::AccountKilled) =>
+					assert_eq!(imbalance.peek(), 0),
+				_ => panic!("setting a new account below the existential deposit should be a no-op"),
+			}
+			assert!(!<FreeBalance<Test>>::exists(&16000, &2));
+
+			let _ = StakingAssetCurrency::<Test>::withdraw(
+				&1, 95, WithdrawReason::Fee, ExistenceRequirement::AllowDeath
+			).unwrap();
+			assert_eq!(GenericAsset::total_issuance(&16000), 0);
+			REAPED.with(|r| assert_eq!(*r.borrow(), vec![(16000, 1)]));
+		},
+	);
+}
+
+#[test]
+fn for_asset_should_only_forward_callbacks_of_its_asset() {
+	thread_local! {
+		static KILLED: std::cell::RefCell<Vec<u64>> = std::cell::RefCell::new(vec![]);
+	}
+	struct Handler;
+	impl OnFreeBalanceZero<u64> for Handler {
+		fn on_free_balance_zero(who: &u64) {
+			KILLED.with(|k| k.borrow_mut().push(*who));
+		}
+	}
+	type StakingHandler = ForAsset<StakingAssetIdProvider<Test>, Handler>;
+
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		<StakingHandler as OnAssetFreeBalanceZero<u32, u64>>::on_free_balance_zero(&16001, &1);
+		<StakingHandler as OnAssetFreeBalanceZero<u32, u64>>::on_free_balance_zero(&16000, &2);
+
+		KILLED.with(|k| assert_eq!(*k.borrow(), vec![2]));
+	});
+}
+
+/// Moves the whole free balance of `asset_id` from `from` to `to`, written against `MultiCurrency` only.
+fn sweep<C: MultiCurrency<u64>>(asset_id: &C::AssetId, from: &u64, to: &u64) -> Result {
+	C::transfer(asset_id, from, to, C::free_balance(asset_id, from))
+}
+
+#[test]
+fn multi_currency_should_work_over_any_asset() {
+	with_externalities(&mut ExtBuilder::default().free_balance((16000, 1, 100)).build(), || {
+		assert_ok!(<GenericAsset as MultiCurrency<_>>::deposit(&16001, &1, 50));
+		assert_eq!(<GenericAsset as MultiCurrency<_>>::total_issuance(&16001), 50);
+
+		assert_ok!(sweep::<GenericAsset>(&16000, &1, &2));
+		assert_ok!(sweep::<GenericAsset>(&16001, &1, &2));
+		assert_eq!(<GenericAsset as MultiCurrency<_>>::total_balance(&16000, &2), 100);
+		assert_eq!(<GenericAsset as MultiCurrency<_>>::total_balance(&16001, &2), 50);
+
+		assert_ok!(<GenericAsset as MultiCurrency<_>>::withdraw(
+			&16001, &2, 20, WithdrawReason::Fee, ExistenceRequirement::AllowDeath
+		));
+		assert_eq!(<GenericAsset as MultiCurrency<_>>::free_balance(&16001, &2), 30);
+		assert_eq!(<GenericAsset as MultiCurrency<_>>::total_issuance(&16001), 30);
+
+		assert_eq!(<GenericAsset as MultiCurrency<_>>::slash(&16000, &2, 120), 20);
+		assert_eq!(<GenericAsset as MultiCurrency<_>>::total_issuance(&16000), 0);
+	});
+}
+
+#[test]
+fn multi_currency_should_respect_existential_deposit() {
+	with_externalities(
+		&mut ExtBuilder::default()
+			.free_balance((16000, 1, 100))
+			.asset_info(16000, info_with_existential_deposit(10))
+			.build(),
+		|| {
+			assert_eq!(<GenericAsset as MultiCurrency<_>>::minimum_balance(&16000), 10);
+			assert_noop!(
+				<GenericAsset as MultiCurrency<_>>::deposit(&16000, &2, 5),
+				"value too low to create account"
+			);
+			assert_noop!(
+				<GenericAsset as MultiCurrency<_>>::withdraw(
+					&16000, &1, 95, WithdrawReason::Fee, ExistenceRequirement::KeepAlive
+				),
+				"payment would kill account"
+			);
+		},
+	);
+}
+
+#[test]
+fn multi_currency_make_free_balance_be_should_adjust_issuance() {
+	with_externalities(
+		&mut ExtBuilder::default()
+			.free_balance((16000, 1, 100))
+			.asset_info(16000, info_with_existential_deposit(10))
+			.build(),
+		|| {
+			<GenericAsset as MultiCurrency<_>>::make_free_balance_be(&16000, &1, 60);
+			<GenericAsset as MultiCurrency<_>>::make_free_balance_be(&16000, &2, 40);
+			assert_eq!(GenericAsset::free_balance(&16000, &1), 60);
+			assert_eq!(GenericAsset::free_balance(&16000, &2), 40);
+			assert_eq!(GenericAsset::total_issuance(&16000), 100);
+
+			// setting a new account below the existential deposit does nothing
+			<GenericAsset as MultiCurrency<_>>::make_free_balance_be(&16000, &3, 5);
+			assert_eq!(GenericAsset::free_balance(&16000, &3), 0);
+			assert_eq!(GenericAsset::total_issuance(&16000), 100);
+
+			// and an existing account is reaped, its dust removed from the issuance
+			<GenericAsset as MultiCurrency<_>>::make_free_balance_be(&16000, &2, 5);
+			assert_eq!(GenericAsset::free_balance(&16000, &2), 0);
+			assert_eq!(GenericAsset::total_issuance(&16000), 60);
+			assert_ok!(GenericAsset::try_state());
+		},
+	);
+}
+
+#[test]
+fn multi_reservable_currency_should_work() {
+	with_externalities(&mut ExtBuilder::default().free_balance((16000, 1, 100)).build(), || {
+		assert!(<GenericAsset as MultiReservableCurrency<_>>::can_reserve(&16000, &1, 100));
+		assert!(!<GenericAsset as MultiReservableCurrency<_>>::can_reserve(&16001, &1, 1));
+
+		assert_ok!(<GenericAsset as MultiReservableCurrency<_>>::reserve(&16000, &1, 60));
+		assert_eq!(<GenericAsset as MultiReservableCurrency<_>>::reserved_balance(&16000, &1), 60);
+
+		assert_eq!(<GenericAsset as MultiReservableCurrency<_>>::repatriate_reserved(&16000, &1, &2, 20), Ok(0));
+		assert_eq!(<GenericAsset as MultiCurrency<_>>::free_balance(&16000, &2), 20);
+
+		assert_eq!(<GenericAsset as MultiReservableCurrency<_>>::slash_reserved(&16000, &1, 50), 10);
+		assert_eq!(<GenericAsset as MultiReservableCurrency<_>>::reserved_balance(&16000, &1), 0);
+		assert_eq!(<GenericAsset as MultiCurrency<_>>::total_issuance(&16000), 60);
+	});
+}
+
+#[test]
+fn multi_lockable_currency_should_lock_a_single_asset() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		endow(1, 100);
+		<GenericAsset as MultiLockableCurrency<_>>::set_lock(
+			&16001, ID_1, &1, 90, u64::max_value(), WithdrawReasons::all()
+		);
+
+		assert_noop!(
+			<GenericAsset as MultiCurrency<_>>::transfer(&16001, &1, &2, 20),
+			"account liquidity restrictions prevent withdrawal"
+		);
+		assert_ok!(<GenericAsset as MultiCurrency<_>>::transfer(&16000, &1, &2, 20));
+
+		<GenericAsset as MultiLockableCurrency<_>>::remove_lock(&16001, ID_1, &1);
+		assert_ok!(<GenericAsset as MultiCurrency<_>>::transfer(&16001, &1, &2, 20));
+	});
+}
+
+#[test]
+fn transfer_from_should_consume_allowance() {
+	with_externalities(&mut ExtBuilder::default().free_balance((16000, 1, 100)).build(), || {
+		assert_ok!(GenericAsset::approve(Origin::signed(1), 16000, 2, 50));
+		assert_eq!(GenericAsset::allowance(&16000, &1, &2), 50);
+		assert_eq!(GenericAsset::allowance(&16001, &1, &2), 0);
+
+		assert_ok!(GenericAsset::transfer_from(Origin::signed(2), 16000, 1, 3, 30));
+		assert_eq!(GenericAsset::free_balance(&16000, &1), 70);
+		assert_eq!(GenericAsset::free_balance(&16000, &3), 30);
+		assert_eq!(GenericAsset::allowance(&16000, &1, &2), 20);
+
+		assert_noop!(
+			GenericAsset::transfer_from(Origin::signed(2), 16000, 1, 3, 30),
+			"amount exceeds allowance"
+		);
+		assert_noop!(
+			GenericAsset::transfer_from(Origin::signed(3), 16000, 1, 3, 10),
+			"amount exceeds allowance"
+		);
+
+		assert_ok!(GenericAsset::transfer_from(Origin::signed(2), 16000, 1, 2, 20));
+		assert_eq!(GenericAsset::allowance(&16000, &1, &2), 0);
+		assert!(!<Allowances<Test>>::exists(&16000, &(1u64, 2u64)));
+	});
+}
+
+#[test]
+fn approve_should_replace_and_revoke_allowance() {
+	with_externalities(&mut ExtBuilder::default().free_balance((16000, 1, 100)).build(), || {
+		assert_ok!(GenericAsset::approve(Origin::signed(1), 16000, 2, 50));
+		assert_ok!(GenericAsset::approve(Origin::signed(1), 16000, 2, 10));
+		assert_eq!(GenericAsset::allowance(&16000, &1, &2), 10);
+
+		assert_ok!(GenericAsset::approve(Origin::signed(1), 16000, 2, 0));
+		assert_eq!(GenericAsset::allowance(&16000, &1, &2), 0);
+		assert_noop!(
+			GenericAsset::transfer_from(Origin::signed(2), 16000, 1, 2, 1),
+			"amount exceeds allowance"
+		);
+	});
+}
+
+#[test]
+fn transfer_from_should_respect_locks_without_consuming_allowance() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		endow(1, 100);
+		assert_ok!(GenericAsset::approve(Origin::signed(1), 16001, 2, 50));
+		SpendingAssetCurrency::<Test>::set_lock(ID_1, &1, 90, u64::max_value(), WithdrawReasons::all());
+
+		assert_noop!(
+			GenericAsset::transfer_from(Origin::signed(2), 16001, 1, 2, 20),
+			"account liquidity restrictions prevent withdrawal"
+		);
+		assert_eq!(GenericAsset::allowance(&16001, &1, &2), 50);
+
+		assert_ok!(GenericAsset::transfer_from(Origin::signed(2), 16001, 1, 2, 10));
+		assert_eq!(GenericAsset::allowance(&16001, &1, &2), 40);
+	});
+}
+
+#[test]
+fn v1_permissions_should_keep_their_encoding() {
+	let v1 = PermissionsV1 {
+		update: Owner::Address(1u64),
+		mint: Owner::Address(2),
+		burn: Owner::None,
+	};
+	let encoded = PermissionVersions::V1(v1.clone()).encode();
+	assert_eq!(encoded[0], 0);
+	assert_eq!(&encoded[1..], &v1.encode()[..]);
+
+	let decoded = PermissionVersions::<u64>::decode(&mut &encoded[..]).unwrap();
+	assert_eq!(decoded, PermissionVersions::V1(v1.clone()));
+	let latest: PermissionLatest<u64> = decoded.into();
+	assert_eq!(latest, v1);
+}
+
+fn create_freezable_asset(owner: u64, initial_issuance: u64) -> u32 {
+	let asset_id = GenericAsset::next_asset_id();
+	assert_ok!(GenericAsset::create(
+		Origin::signed(owner),
+		AssetOptions {
+			initial_issuance,
+			permissions: PermissionLatest {
+				update: Owner::Address(owner),
+				mint: Owner::Address(owner),
+				burn: Owner::Address(owner),
+			},
+		}
+	));
+	assert_ok!(GenericAsset::update_freeze_permission(
+		Origin::signed(owner),
+		asset_id,
+		Owner::Address(owner)
+	));
+	asset_id
+}
+
+#[test]
+fn update_freeze_permission_should_require_update_permission() {
+	with_externalities(&mut ExtBuilder::default().next_asset_id(1000).build(), || {
+		let asset_id = create_freezable_asset(1, 100);
+		assert!(GenericAsset::check_permission(&asset_id, &1, &PermissionType::Freeze));
+
+		assert_noop!(
+			GenericAsset::update_freeze_permission(Origin::signed(2), asset_id, Owner::Address(2)),
+			"Origin does not have enough permission to update permissions."
+		);
+
+		// updating the other permissions leaves the freeze permission in place
+		assert_ok!(GenericAsset::update_permission(
+			Origin::signed(1),
+			asset_id,
+			PermissionLatest {
+				update: Owner::Address(1),
+				mint: Owner::None,
+				burn: Owner::None,
+			}
+		));
+		assert!(GenericAsset::check_permission(&asset_id, &1, &PermissionType::Freeze));
+
+		assert_ok!(GenericAsset::update_freeze_permission(Origin::signed(1), asset_id, Owner::Address(2)));
+		assert!(!GenericAsset::check_permission(&asset_id, &1, &PermissionType::Freeze));
+		assert_ok!(GenericAsset::freeze_asset(Origin::signed(2), asset_id, true));
+		assert!(GenericAsset::is_asset_frozen(asset_id));
+	});
+}
+
+#[test]
+fn freeze_asset_should_block_withdrawals_until_thawed() {
+	with_externalities(&mut ExtBuilder::default().next_asset_id(1000).build(), || {
+		let asset_id = create_freezable_asset(1, 100);
+		assert_ok!(GenericAsset::transfer(Origin::signed(1), asset_id, 2, 50));
+
+		assert_noop!(
+			GenericAsset::freeze_asset(Origin::signed(2), asset_id, true),
+			"Origin does not have enough permission to freeze asset."
+		);
+		assert_ok!(GenericAsset::freeze_asset(Origin::signed(1), asset_id, true));
+		assert!(GenericAsset::is_asset_frozen(asset_id));
+
+		assert_noop!(GenericAsset::transfer(Origin::signed(2), asset_id, 1, 10), "asset is frozen");
+		assert_noop!(GenericAsset::transfer(Origin::signed(1), asset_id, 2, 10), "asset is frozen");
+
+		assert_ok!(GenericAsset::freeze_asset(Origin::signed(1), asset_id, false));
+		assert_ok!(GenericAsset::transfer(Origin::signed(2), asset_id, 1, 10));
+	});
+}
+
+#[test]
+fn freeze_account_should_only_block_that_account() {
+	with_externalities(&mut ExtBuilder::default().next_asset_id(1000).build(), || {
+		let asset_id = create_freezable_asset(1, 100);
+		assert_ok!(GenericAsset::transfer(Origin::signed(1), asset_id, 2, 50));
+		assert_ok!(GenericAsset::approve(Origin::signed(2), asset_id, 3, 10));
+		assert_ok!(GenericAsset::freeze_account(Origin::signed(1), asset_id, 2, true));
+
+		assert_noop!(GenericAsset::transfer(Origin::signed(2), asset_id, 1, 10), "account is frozen");
+		assert_ok!(GenericAsset::transfer(Origin::signed(1), asset_id, 2, 10));
+		assert_eq!(GenericAsset::free_balance(&asset_id, &2), 60);
+
+		// a frozen account may not grant new allowances, but its existing ones can not be spent either
+		assert_noop!(GenericAsset::approve(Origin::signed(2), asset_id, 4, 10), "account is frozen");
+		assert_noop!(
+			GenericAsset::transfer_from(Origin::signed(3), asset_id, 2, 3, 10),
+			"account is frozen"
+		);
+
+		assert_ok!(GenericAsset::freeze_account(Origin::signed(1), asset_id, 2, false));
+		assert_ok!(GenericAsset::transfer_from(Origin::signed(3), asset_id, 2, 3, 10));
+	});
+}
+
+#[test]
+fn approve_should_fail_while_asset_is_frozen_but_allow_revoking() {
+	with_externalities(&mut ExtBuilder::default().next_asset_id(1000).build(), || {
+		let asset_id = create_freezable_asset(1, 100);
+		assert_ok!(GenericAsset::approve(Origin::signed(1), asset_id, 2, 10));
+		assert_ok!(GenericAsset::freeze_asset(Origin::signed(1), asset_id, true));
+
+		assert_noop!(GenericAsset::approve(Origin::signed(1), asset_id, 2, 20), "asset is frozen");
+		assert_ok!(GenericAsset::approve(Origin::signed(1), asset_id, 2, 0));
+		assert_eq!(GenericAsset::allowance(&asset_id, &1, &2), 0);
+	});
+}
+
+#[test]
+fn restricted_asset_should_only_be_received_by_allowed_accounts() {
+	with_externalities(&mut ExtBuilder::default().next_asset_id(1000).build(), || {
+		let asset_id = create_freezable_asset(1, 100);
+		assert_ok!(GenericAsset::transfer(Origin::signed(1), asset_id, 3, 20));
+		assert_ok!(GenericAsset::restrict_transfers(Origin::signed(1), asset_id, true));
+		assert_ok!(GenericAsset::set_allowed(Origin::signed(1), asset_id, 2, true));
+		assert!(!GenericAsset::can_receive(&asset_id, &3));
+
+		assert_ok!(GenericAsset::transfer(Origin::signed(1), asset_id, 2, 50));
+		assert_noop!(
+			GenericAsset::transfer(Origin::signed(2), asset_id, 3, 10),
+			"destination is not allowed to receive asset"
+		);
+		assert_noop!(
+			GenericAsset::set_allowed(Origin::signed(2), asset_id, 3, true),
+			"Origin does not have enough permission to freeze asset."
+		);
+
+		// senders are not restricted, so account 3 may send to the allow-list without attestation
+		assert!(!GenericAsset::is_sender_restricted(&asset_id));
+		assert_ok!(GenericAsset::transfer(Origin::signed(3), asset_id, 2, 10));
+		assert_eq!(GenericAsset::free_balance(&asset_id, &2), 60);
+
+		// minting is restricted to the allow-list too
+		assert_noop!(
+			GenericAsset::mint(Origin::signed(1), asset_id, 3, 10),
+			"destination is not allowed to receive asset"
+		);
+		assert_ok!(GenericAsset::mint(Origin::signed(1), asset_id, 2, 10));
+
+		assert_ok!(GenericAsset::restrict_transfers(Origin::signed(1), asset_id, false));
+		assert_ok!(GenericAsset::transfer(Origin::signed(2), asset_id, 3, 10));
+	});
+}
+
+#[test]
+fn restricted_asset_should_only_be_sent_by_restricted_transfer_origin() {
+	with_externalities(&mut ExtBuilder::default().next_asset_id(1000).build(), || {
+		let asset_id = create_freezable_asset(1, 100);
+		assert_ok!(GenericAsset::transfer(Origin::signed(1), asset_id, 3, 50));
+		assert_ok!(GenericAsset::restrict_senders(Origin::signed(1), asset_id, true));
+		assert_noop!(
+			GenericAsset::restrict_senders(Origin::signed(2), asset_id, false),
+			"Origin does not have enough permission to freeze asset."
+		);
+
+		// account 3 holds the asset, but is not accepted by the mock's `RestrictedTransferOrigin`
+		assert_noop!(
+			GenericAsset::transfer(Origin::signed(3), asset_id, 2, 10),
+			"origin is not allowed to send restricted asset"
+		);
+		assert_noop!(
+			GenericAsset::batch_transfer(Origin::signed(3), vec![(asset_id, 2, 10)]),
+			"origin is not allowed to send restricted asset"
+		);
+		assert_ok!(GenericAsset::approve(Origin::signed(1), asset_id, 3, 10));
+		assert_noop!(
+			GenericAsset::transfer_from(Origin::signed(3), asset_id, 1, 2, 10),
+			"origin is not allowed to send restricted asset"
+		);
+
+		// account 3 may not approve an accepted spender to send its restricted funds
+		assert_noop!(
+			GenericAsset::approve(Origin::signed(3), asset_id, 2, 10),
+			"origin is not allowed to send restricted asset"
+		);
+
+		// nor may an allowance granted before the restriction be spent
+		assert_ok!(GenericAsset::restrict_senders(Origin::signed(1), asset_id, false));
+		assert_ok!(GenericAsset::approve(Origin::signed(3), asset_id, 2, 10));
+		assert_ok!(GenericAsset::restrict_senders(Origin::signed(1), asset_id, true));
+		assert_noop!(
+			GenericAsset::transfer_from(Origin::signed(2), asset_id, 3, 2, 10),
+			"owner is not allowed to send restricted asset"
+		);
+
+		// receivers are not restricted, so accepted senders may send to any account
+		assert!(!GenericAsset::is_restricted(&asset_id));
+		assert_ok!(GenericAsset::transfer(Origin::signed(1), asset_id, 2, 10));
+		assert_ok!(GenericAsset::transfer(Origin::signed(1), asset_id, 3, 10));
+		assert_ok!(GenericAsset::approve(Origin::signed(1), asset_id, 2, 10));
+		assert_ok!(GenericAsset::transfer_from(Origin::signed(2), asset_id, 1, 2, 10));
+		assert_eq!(GenericAsset::free_balance(&asset_id, &2), 20);
+
+		assert_ok!(GenericAsset::restrict_senders(Origin::signed(1), asset_id, false));
+		assert_ok!(GenericAsset::transfer(Origin::signed(3), asset_id, 2, 10));
+	});
+}
+
+#[test]
+fn restricted_asset_should_apply_sender_and_receiver_restrictions_together() {
+	with_externalities(&mut ExtBuilder::default().next_asset_id(1000).build(), || {
+		let asset_id = create_freezable_asset(1, 100);
+		assert_ok!(GenericAsset::transfer(Origin::signed(1), asset_id, 3, 50));
+		assert_ok!(GenericAsset::restrict_transfers(Origin::signed(1), asset_id, true));
+		assert_ok!(GenericAsset::restrict_senders(Origin::signed(1), asset_id, true));
+		assert_ok!(GenericAsset::set_allowed(Origin::signed(1), asset_id, 2, true));
+
+		assert_noop!(
+			GenericAsset::transfer(Origin::signed(3), asset_id, 2, 10),
+			"origin is not allowed to send restricted asset"
+		);
+		assert_noop!(
+			GenericAsset::transfer(Origin::signed(1), asset_id, 3, 10),
+			"destination is not allowed to receive asset"
+		);
+		assert_ok!(GenericAsset::transfer(Origin::signed(1), asset_id, 2, 10));
+	});
+}
+
+#[test]
+fn batch_transfer_should_make_every_transfer() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		endow(1, 100);
+		assert_ok!(GenericAsset::batch_transfer(
+			Origin::signed(1),
+			vec![(16000, 2, 10), (16001, 3, 20), (16000, 3, 30)]
+		));
+
+		assert_eq!(GenericAsset::free_balance(&16000, &1), 60);
+		assert_eq!(GenericAsset::free_balance(&16001, &1), 80);
+		assert_eq!(GenericAsset::free_balance(&16000, &2), 10);
+		assert_eq!(GenericAsset::free_balance(&16001, &3), 20);
+		assert_eq!(GenericAsset::free_balance(&16000, &3), 30);
+		assert!(System::events().iter().any(|record| record.event
+			== TestEvent::generic_asset(RawEvent::Transferred(16001, 1, 3, 20))));
+	});
+}
+
+#[test]
+fn batch_transfer_should_make_no_transfer_if_any_fails() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		endow(1, 100);
+		assert_noop!(
+			GenericAsset::batch_transfer(Origin::signed(1), vec![(16000, 2, 60), (16001, 2, 10), (16000, 3, 60)]),
+			"balance too low to send amount"
+		);
+
+		SpendingAssetCurrency::<Test>::set_lock(ID_1, &1, 90, u64::max_value(), WithdrawReasons::all());
+		assert_noop!(
+			GenericAsset::batch_transfer(Origin::signed(1), vec![(16000, 2, 60), (16001, 2, 20)]),
+			"account liquidity restrictions prevent withdrawal"
+		);
+		assert_noop!(
+			GenericAsset::batch_transfer(Origin::signed(1), vec![(16000, 2, 60), (16000, 3, 0)]),
+			"cannot transfer zero amount"
+		);
+	});
+}
+
+#[test]
+fn batch_transfer_should_fail_if_an_earlier_transfer_reaps_the_sender() {
+	with_externalities(
+		&mut ExtBuilder::default()
+			.free_balance((16000, 1, 100))
+			.asset_info(16000, info_with_existential_deposit(10))
+			.build(),
+		|| {
+			assert_noop!(
+				GenericAsset::batch_transfer(Origin::signed(1), vec![(16000, 2, 95), (16000, 3, 5)]),
+				"balance too low to send amount"
+			);
+			assert_noop!(
+				GenericAsset::batch_transfer(Origin::signed(1), vec![(16000, 2, 5), (16000, 2, 5)]),
+				"value too low to create account"
+			);
+			assert_ok!(GenericAsset::batch_transfer(Origin::signed(1), vec![(16000, 2, 10), (16000, 2, 5)]));
+			assert_eq!(GenericAsset::free_balance(&16000, &2), 15);
+		},
+	);
+}
+
+#[test]
+fn batch_transfer_should_fail_with_too_many_transfers() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		endow(1, 100_000);
+		assert_noop!(
+			GenericAsset::batch_transfer(Origin::signed(1), vec![(16000, 2, 1); MAX_BATCH_TRANSFERS + 1]),
+			"too many transfers in batch"
+		);
+		assert_ok!(GenericAsset::batch_transfer(Origin::signed(1), vec![(16000, 2, 1); MAX_BATCH_TRANSFERS]));
+		assert_eq!(GenericAsset::free_balance(&16000, &2), MAX_BATCH_TRANSFERS as u64);
+	});
+}
+
+#[test]
+fn batch_transfer_weight_should_be_proportional_to_length() {
+	let weight = |len| Call::<Test>::batch_transfer(vec![(16000, 2, 1); len]).get_dispatch_info().weight;
+	assert_eq!(weight(1), CREATING_CALL_WEIGHT);
+	assert_eq!(weight(5), 5 * CREATING_CALL_WEIGHT);
+}
+
+#[test]
+fn genesis_vesting_should_lock_until_vested() {
+	with_externalities(
+		&mut ExtBuilder::default()
+			.free_balance((16000, 1, 100))
+			.vesting((16000, 1, 10, 10, 20))
+			.build(),
+		|| {
+			System::set_block_number(1);
+			assert_eq!(GenericAsset::vesting_balance(&16000, &1), 80);
+			assert_noop!(
+				GenericAsset::transfer(Origin::signed(1), 16000, 2, 30),
+				"account liquidity restrictions prevent withdrawal"
+			);
+
+			System::set_block_number(15);
+			assert_eq!(GenericAsset::vesting_balance(&16000, &1), 40);
+			// vested balance is only released by `vest`
+			assert_noop!(
+				GenericAsset::transfer(Origin::signed(1), 16000, 2, 30),
+				"account liquidity restrictions prevent withdrawal"
+			);
+			assert_ok!(GenericAsset::vest(Origin::signed(1), 16000));
+			assert!(System::events().iter().any(|record| record.event
+				== TestEvent::generic_asset(RawEvent::Vested(16000, 1, 40))));
+			assert_ok!(GenericAsset::transfer(Origin::signed(1), 16000, 2, 60));
+
+			System::set_block_number(20);
+			assert_ok!(GenericAsset::vest(Origin::signed(1), 16000));
+			assert_eq!(GenericAsset::vesting(&16000, &1), None);
+			assert!(GenericAsset::locks(&16000, &1).is_empty());
+			assert_noop!(GenericAsset::vest(Origin::signed(1), 16000), "account has no vesting schedule");
+		},
+	);
+}
+
+#[test]
+fn set_vesting_schedule_should_lock_a_single_asset() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		endow(1, 100);
+		let schedule = VestingSchedule { locked: 50, per_block: 5, starting_block: 10 };
+
+		assert_noop!(
+			GenericAsset::set_vesting_schedule(Origin::signed(1), 16001, 1, schedule),
+			"RequireRootOrigin"
+		);
+		assert_ok!(GenericAsset::set_vesting_schedule(Origin::ROOT, 16001, 1, schedule));
+		assert_eq!(GenericAsset::vesting(&16001, &1), Some(schedule));
+
+		assert_noop!(
+			GenericAsset::transfer(Origin::signed(1), 16001, 2, 60),
+			"account liquidity restrictions prevent withdrawal"
+		);
+		assert_ok!(GenericAsset::transfer(Origin::signed(1), 16000, 2, 60));
+		// vesting does not restrict paying fees
+		assert_ok!(GenericAsset::ensure_can_withdraw(&16001, &1, 60, WithdrawReason::TransactionPayment, 40));
+	});
+}
+
+#[test]
+fn set_vesting_schedule_should_reject_invalid_schedules() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		endow(1, 100);
+
+		assert_noop!(
+			GenericAsset::set_vesting_schedule(
+				Origin::ROOT,
+				16001,
+				1,
+				VestingSchedule { locked: 50, per_block: 0, starting_block: 10 }
+			),
+			"vesting schedule must vest a non-zero amount per block"
+		);
+		assert_noop!(
+			GenericAsset::set_vesting_schedule(
+				Origin::ROOT,
+				16001,
+				1,
+				VestingSchedule { locked: 101, per_block: 5, starting_block: 10 }
+			),
+			"vesting schedule locks more than the free balance"
+		);
+		assert_ok!(GenericAsset::set_vesting_schedule(
+			Origin::ROOT,
+			16001,
+			1,
+			VestingSchedule { locked: 100, per_block: 5, starting_block: 10 }
+		));
+	});
+}
+
+#[test]
+fn audit_issuance_should_sum_holder_balances() {
+	with_externalities(&mut ExtBuilder::default().next_asset_id(1000).build(), || {
+		let asset_id = create_freezable_asset(1, 100);
+		assert_ok!(GenericAsset::transfer(Origin::signed(1), asset_id, 2, 40));
+		assert_ok!(GenericAsset::mint(Origin::signed(1), asset_id, 3, 20));
+		assert_ok!(GenericAsset::burn(Origin::signed(1), asset_id, 2, 10));
+		assert_ok!(GenericAsset::reserve(&asset_id, &1, 30));
+		<GenericAsset as MultiCurrency<_>>::slash(&asset_id, &2, 5);
+
+		assert_eq!(
+			GenericAsset::audit_issuance(&asset_id),
+			IssuanceAudit { total_issuance: 105, free: 75, reserved: 30 }
+		);
+		assert_ok!(GenericAsset::try_state());
+	});
+}
+
+#[test]
+fn audit_issuance_should_report_drift() {
+	with_externalities(&mut ExtBuilder::default().free_balance((16001, 1, 100)).build(), || {
+		assert_ok!(GenericAsset::try_state());
+
+		<FreeBalance<Test>>::insert(&16001, &1, &90);
+		assert_eq!(
+			GenericAsset::issuance_discrepancies(),
+			vec![(16001, IssuanceAudit { total_issuance: 100, free: 90, reserved: 0 })]
+		);
+		assert_noop!(GenericAsset::try_state(), "total issuance does not match the sum of balances");
+	});
+}
+
+#[test]
+fn reaped_accounts_should_not_be_audited() {
+	with_externalities(
+		&mut ExtBuilder::default()
+			.free_balance((16000, 1, 100))
+			.asset_info(16000, info_with_existential_deposit(10))
+			.build(),
+		|| {
+			assert_ok!(GenericAsset::transfer(Origin::signed(1), 16000, 2, 95));
+			assert!(!<Holders<Test>>::exists(&16000, &1));
+			assert!(<Holders<Test>>::exists(&16000, &2));
+			assert_ok!(GenericAsset::try_state());
+		},
+	);
+}
+
+#[test]
+fn holders_should_be_listed_per_asset() {
+	with_externalities(
+		&mut ExtBuilder::default()
+			.free_balance((16000, 1, 100))
+			.asset_info(16000, info_with_existential_deposit(10))
+			.build(),
+		|| {
+			assert_ok!(GenericAsset::transfer(Origin::signed(1), 16000, 2, 10));
+			assert_ok!(GenericAsset::transfer(Origin::signed(1), 16000, 3, 10));
+			assert_eq!(GenericAsset::holders(&16000), vec![3, 2, 1]);
+			assert!(GenericAsset::holders(&16001).is_empty());
+			assert!(<HolderAssets<Test>>::exists(&16000));
+
+			// reaping removes a holder from anywhere in the list
+			assert_ok!(GenericAsset::transfer(Origin::signed(2), 16000, 1, 10));
+			assert_eq!(GenericAsset::holders(&16000), vec![3, 1]);
+			assert_ok!(GenericAsset::transfer(Origin::signed(3), 16000, 1, 10));
+			assert_eq!(GenericAsset::holders(&16000), vec![1]);
+			assert_ok!(GenericAsset::transfer(Origin::signed(1), 16000, 2, 100));
+			assert_eq!(GenericAsset::holders(&16000), vec![2]);
+			assert_ok!(GenericAsset::try_state());
+		},
+	);
+}
+
+#[test]
+fn backfill_holders_should_index_balances_created_before_the_index() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		// balances written before the holders index was introduced
+		<FreeBalance<Test>>::insert(&16000, &1, &100);
+		<FreeBalance<Test>>::insert(&16000, &3, &50);
+		<TotalIssuance<Test>>::insert(&16000, &150);
+
+		assert_eq!(
+			GenericAsset::issuance_discrepancies(),
+			vec![(16000, IssuanceAudit { total_issuance: 150, free: 0, reserved: 0 })]
+		);
+
+		assert_noop!(GenericAsset::backfill_holders(Origin::signed(1), 16000, 0, vec![1, 2]), "RequireRootOrigin");
+		assert_ok!(GenericAsset::backfill_holders(Origin::ROOT, 16000, 0, vec![1, 2]));
+
+		// only accounts with a balance are indexed
+		assert_eq!(GenericAsset::holders(&16000), vec![1]);
+		assert_eq!(GenericAsset::backfill_cursor(&16000), 2);
+
+		// pages must be given in order
+		assert_noop!(
+			GenericAsset::backfill_holders(Origin::ROOT, 16000, 0, vec![3]),
+			"backfill cursor does not match"
+		);
+		assert_ok!(GenericAsset::backfill_holders(Origin::ROOT, 16000, 2, vec![3]));
+		assert_eq!(GenericAsset::holders(&16000), vec![3, 1]);
+		assert_ok!(GenericAsset::try_state());
+
+		// indexing is idempotent
+		assert_ok!(GenericAsset::backfill_holders(Origin::ROOT, 16000, 3, vec![1]));
+		assert_eq!(GenericAsset::holders(&16000), vec![3, 1]);
+	});
+}
+
+#[test]
+fn backfill_holders_should_be_bounded_and_weighed_per_account() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		assert_noop!(
+			GenericAsset::backfill_holders(Origin::ROOT, 16000, 0, vec![1; MAX_BACKFILL_HOLDERS + 1]),
+			"too many accounts to backfill"
+		);
+	});
+
+	let info = Call::<Test>::backfill_holders(16000, 0, vec![1; 5]).get_dispatch_info();
+	assert_eq!(info.weight, 5 * CREATING_CALL_WEIGHT);
+	assert_eq!(info.class, DispatchClass::Operational);
+}
+
+#[test]
+fn calls_creating_balances_should_be_weighed_for_indexing_holders() {
+	assert_eq!(Call::<Test>::transfer(16000, 2, 1).get_dispatch_info().weight, CREATING_CALL_WEIGHT);
+	assert_eq!(Call::<Test>::transfer_from(16000, 1, 2, 1).get_dispatch_info().weight, CREATING_CALL_WEIGHT);
+	assert_eq!(Call::<Test>::mint(16000, 2, 1).get_dispatch_info().weight, CREATING_CALL_WEIGHT);
+}
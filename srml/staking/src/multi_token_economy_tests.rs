@@ -97,6 +97,16 @@ impl generic_asset::Trait for Test {
 	type Balance = u64;
 	type AssetId = u32;
 	type Event = ();
+	type DustRemoval = ();
+	type OnFreeBalanceZero = ();
+	type OnNewAccount = ();
+	type RestrictedTransferOrigin = system::EnsureSigned<u64, ()>;
+	type MaxSymbolLength = MaxSymbolLength;
+	type MaxNameLength = MaxNameLength;
+}
+parameter_types! {
+	pub const MaxSymbolLength: u32 = 8;
+	pub const MaxNameLength: u32 = 32;
 }
 parameter_types! {
 	pub const Period: BlockNumber = 1;
@@ -198,6 +208,8 @@ impl ExtBuilder {
 			staking_asset_id: STAKING_ASSET_ID,
 			spending_asset_id: REWARD_ASSET_ID,
 			assets: vec![STAKING_ASSET_ID, REWARD_ASSET_ID],
+			asset_meta: vec![],
+			vesting: vec![],
 			next_asset_id: 102,
 		}.assimilate_storage(&mut storage);
 
@@ -1,8 +1,9 @@
 //! Additional traits to srml original traits. These traits are generally used
 //! to decouple `srml` modules from `prml` modules.
 
+use codec::Encode;
 use sr_primitives::traits::DoughnutApi;
-use rstd::marker::PhantomData;
+use rstd::{marker::PhantomData, prelude::*};
 
 /// Perform fee payment for an extrinsic
 pub trait ChargeExtrinsicFee<AccountId, Extrinsic> {
@@ -47,6 +48,16 @@ impl<T, U> ChargeFee<T> for DummyChargeFee<T, U> {
 	fn refund_fee(_: &T, _: Self::Amount) -> Result<(), &'static str> { Ok(()) }
 }
 
+/// An argument of a dispatched call, as passed to a `DelegatedDispatchVerifier`
+pub struct DispatchArg<'a> {
+	/// The argument name as declared by the module method
+	pub name: &'a str,
+	/// The argument type as declared by the module method e.g. `T::Balance`
+	pub ty: &'a str,
+	/// The SCALE encoded argument value
+	pub value: Vec<u8>,
+}
+
 /// A type which can verify a doughnut delegation proof in order to dispatch a module/method call
 /// into the runtime
 /// The `verify()` hook is injected into every module/method on the runtime.
@@ -54,22 +65,31 @@ impl<T, U> ChargeFee<T> for DummyChargeFee<T, U> {
 pub trait DelegatedDispatchVerifier<Doughnut> {
 	/// The doughnut permission domain it verifies
 	const DOMAIN: &'static str;
-	/// Check the doughnut authorizes a dispatched call to `module` and `method` for this domain
+	/// Check the doughnut authorizes a dispatched call to `module` and `method` with `args` for this domain
 	fn verify_dispatch(
 		doughnut: &Doughnut,
 		module: &str,
 		method: &str,
+		args: &[DispatchArg],
 	) -> Result<(), &'static str>;
 }
 
 /// A dummy implementation for when dispatch verifiaction is not needed
 impl<Doughnut> DelegatedDispatchVerifier<Doughnut> for () {
 	const DOMAIN: &'static str = "";
-	fn verify_dispatch(_: &Doughnut, _: &str, _: &str) -> Result<(), &'static str> {
+	fn verify_dispatch(_: &Doughnut, _: &str, _: &str, _: &[DispatchArg]) -> Result<(), &'static str> {
 		Ok(())
 	}
 }
 
+/// A call which can check whether a doughnut authorizes its dispatch, without dispatching it.
+/// It is implemented for module and outer `Call`s by `decl_module!` and `impl_outer_dispatch!`
+/// using the runtime's `DelegatedDispatchVerifier`.
+pub trait VerifyDelegatedDispatch<Doughnut> {
+	/// Check `doughnut` authorizes dispatch of this call
+	fn verify_delegated_dispatch(&self, doughnut: &Doughnut) -> Result<(), &'static str>;
+}
+
 /// Something which may have doughnut. Returns a ref to the doughnut, if any.
 /// It's main purpose is to allow checking if an `OuterOrigin` contains a doughnut (i.e. it is delegated).
 pub trait MaybeDoughnutRef {
@@ -83,3 +103,67 @@ impl MaybeDoughnutRef for () {
 	type Doughnut = ();
 	fn doughnut(&self) -> Option<&Self::Doughnut> { None }
 }
+
+/// A registry of doughnuts revoked by their issuers
+pub trait DoughnutRevocationRegistry<AccountId> {
+	/// Return whether `doughnut` has been revoked
+	fn is_revoked<Doughnut: DoughnutApi<PublicKey=AccountId> + Encode>(doughnut: &Doughnut) -> bool;
+}
+
+/// A dummy implementation for when doughnut revocation is not needed
+impl<AccountId> DoughnutRevocationRegistry<AccountId> for () {
+	fn is_revoked<Doughnut: DoughnutApi<PublicKey=AccountId> + Encode>(_: &Doughnut) -> bool {
+		false
+	}
+}
+
+/// Tracks the use of doughnuts so that issuer imposed usage limits may be enforced
+pub trait DoughnutUsageTracker {
+	/// Return the number of extrinsics `doughnut` has been used for
+	fn uses<Doughnut: Encode>(doughnut: &Doughnut) -> u32;
+	/// Return the total amount of `asset_id` spent using `doughnut`
+	fn spent<Doughnut: Encode>(doughnut: &Doughnut, asset_id: u64) -> u128;
+	/// Record a use of `doughnut` which spends the given `(asset_id, amount)` pairs
+	fn record_use<Doughnut: Encode>(doughnut: &Doughnut, spend: &[(u64, u128)]);
+	/// Return whether uses are tracked, so that usage limits can be enforced.
+	/// Doughnuts with usage limits must be rejected when they can not be.
+	fn enforces_limits() -> bool;
+}
+
+/// A dummy implementation for when doughnut usage is not tracked.
+/// Usage limits can not be enforced with it, so doughnuts which carry them are rejected.
+impl DoughnutUsageTracker for () {
+	fn enforces_limits() -> bool { false }
+	fn uses<Doughnut: Encode>(_: &Doughnut) -> u32 { 0 }
+	fn spent<Doughnut: Encode>(_: &Doughnut, _: u64) -> u128 { 0 }
+	fn record_use<Doughnut: Encode>(_: &Doughnut, _: &[(u64, u128)]) {}
+}
+
+/// Determines the amounts a call spends on behalf of a doughnut issuer
+pub trait DelegatedCallSpend<Call> {
+	/// Return the `(asset_id, amount)` pairs spent by `call`, or `None` if the spend of `call` is not accounted.
+	/// Doughnuts with spending limits may only be used for calls whose spend is accounted.
+	fn spend(call: &Call) -> Option<Vec<(u64, u128)>>;
+}
+
+/// A dummy implementation for when no call spends are accounted
+impl<Call> DelegatedCallSpend<Call> for () {
+	fn spend(_: &Call) -> Option<Vec<(u64, u128)>> {
+		None
+	}
+}
+
+/// A source of asset prices, used to convert an amount of one asset into an equal value of another
+/// e.g. to let transaction fees be paid in an asset of the transactor's choosing
+pub trait AssetPricing<AssetId, Balance> {
+	/// Return the amount of `to_asset` equal in value to `amount` of `from_asset`,
+	/// or `None` if the assets can not be priced
+	fn convert(from_asset: &AssetId, to_asset: &AssetId, amount: Balance) -> Option<Balance>;
+}
+
+/// A dummy implementation for when no asset prices are available
+impl<AssetId, Balance> AssetPricing<AssetId, Balance> for () {
+	fn convert(_: &AssetId, _: &AssetId, _: Balance) -> Option<Balance> {
+		None
+	}
+}
@@ -925,6 +925,61 @@ macro_rules! decl_module {
 		}
 	};
 
+	// A module without dispatchable functions, its `Call` is never constructed.
+	(@impl_verify_delegated_dispatch
+		$call_type:ident<$trait_instance:ident: $trait_name:ident$(<I>, $instance:ident: $instantiable:path)?>;
+		$system:ident;
+		{ $( $other_where_bounds:tt )* }
+		{}
+	) => {
+		impl<$trait_instance: $trait_name $(<I>, $instance: $instantiable)?, Doughnut>
+			$crate::additional_traits::VerifyDelegatedDispatch<Doughnut>
+			for $call_type<$trait_instance $(, $instance)?> where $( $other_where_bounds )*
+		{
+			fn verify_delegated_dispatch(&self, _doughnut: &Doughnut) -> $crate::dispatch::result::Result<(), &'static str> {
+				unreachable!("__PhantomItem should never be used.")
+			}
+		}
+	};
+
+	// Verify a doughnut authorizes a `Call` exactly as it would be verified on dispatch
+	(@impl_verify_delegated_dispatch
+		$call_type:ident<$trait_instance:ident: $trait_name:ident$(<I>, $instance:ident: $instantiable:path)?>;
+		$system:ident;
+		{ $( $other_where_bounds:tt )* }
+		{ $( $fn_name:ident ( $( $param_name:ident : $param:ty ),* ); )+ }
+	) => {
+		impl<$trait_instance: $trait_name $(<I>, $instance: $instantiable)?>
+			$crate::additional_traits::VerifyDelegatedDispatch<<$trait_instance as $system::Trait>::Doughnut>
+			for $call_type<$trait_instance $(, $instance)?> where $( $other_where_bounds )*
+		{
+			fn verify_delegated_dispatch(
+				&self,
+				doughnut: &<$trait_instance as $system::Trait>::Doughnut,
+			) -> $crate::dispatch::result::Result<(), &'static str> {
+				use $crate::dispatch::DelegatedDispatchVerifier;
+				match *self {
+					$(
+						$call_type::$fn_name( $( ref $param_name ),* ) =>
+							<$trait_instance as $system::Trait>::DelegatedDispatchVerifier::verify_dispatch(
+								doughnut,
+								env!("CARGO_PKG_NAME"),		// module
+								stringify!($fn_name),		// method
+								&[ $(						// args
+									$crate::additional_traits::DispatchArg {
+										name: stringify!($param_name),
+										ty: stringify!($param),
+										value: $crate::codec::Encode::encode($param_name),
+									},
+								)* ],
+							),
+					)*
+					$call_type::__PhantomItem(_, _) => unreachable!("__PhantomItem should never be used."),
+				}
+			}
+		}
+	};
+
 	// Declare a `Call` variant parameter that should be encoded `compact`.
 	(@create_call_enum
 		$( #[$attr:meta] )*
@@ -1126,16 +1181,12 @@ macro_rules! decl_module {
 						$from $(, $param_name : $param )*
 					) $( -> $result )* {
 						// Trait imports for doughnut dispatch verification
-						use $crate::additional_traits::MaybeDoughnutRef;
-						use $crate::dispatch::DelegatedDispatchVerifier;
+						use $crate::additional_traits::{MaybeDoughnutRef, VerifyDelegatedDispatch};
 						// Check whether `origin` is acting with delegated authority (i.e. doughnut attached).
 						if let Some(doughnut) = &$from.doughnut() {
-							// Ensure the doughnut authorizes the current call
-							let _ = <T as $system::Trait>::DelegatedDispatchVerifier::verify_dispatch(
-								doughnut,
-								env!("CARGO_PKG_NAME"),		// module
-								stringify!($fn_name)		// method
-							)?;
+							// Ensure the doughnut authorizes the current call, verified as its `Call` would be
+							$call_type::<$trait_instance $(, $fn_instance)?>::$fn_name( $( $param_name.clone() ),* )
+								.verify_delegated_dispatch(doughnut)?;
 						}
 						$( $impl )*
 					}
@@ -1280,6 +1331,13 @@ macro_rules! decl_module {
 				}
 			}
 		}
+		$crate::decl_module! {
+			@impl_verify_delegated_dispatch
+			$call_type<$trait_instance: $trait_name $(<I>, $instance: $instantiable)?>;
+			$system;
+			{ $( $other_where_bounds )* }
+			{ $( $fn_name ( $( $param_name : $param ),* ); )* }
+		}
 		impl<$trait_instance: $trait_name $(<I>, $instance: $instantiable)?> $crate::dispatch::Callable<$trait_instance>
 			for $mod_type<$trait_instance $(, $instance)?> where $( $other_where_bounds )*
 		{
@@ -1365,6 +1423,16 @@ macro_rules! impl_outer_dispatch {
 				}
 			}
 		}
+		impl<Doughnut> $crate::additional_traits::VerifyDelegatedDispatch<Doughnut> for $call_type
+		where
+			$( $crate::dispatch::CallableCallFor<$camelcase, $runtime>: $crate::additional_traits::VerifyDelegatedDispatch<Doughnut>, )*
+		{
+			fn verify_delegated_dispatch(&self, doughnut: &Doughnut) -> $crate::dispatch::result::Result<(), &'static str> {
+				match self {
+					$( $call_type::$camelcase(call) => call.verify_delegated_dispatch(doughnut), )*
+				}
+			}
+		}
 		$(
 			impl $crate::dispatch::IsSubType<$camelcase, $runtime> for $call_type {
 				#[allow(unreachable_patterns)]
@@ -23,7 +23,7 @@ use codec::{FullCodec, Codec, Encode, Decode};
 use primitives::u32_trait::Value as U32;
 use sr_primitives::{
 	ConsensusEngineId,
-	traits::{MaybeSerializeDebug, SimpleArithmetic, Saturating},
+	traits::{MaybeDebug, MaybeSerializeDebug, SimpleArithmetic, Saturating},
 };
 
 /// Anything that can have a `::len()` method.
@@ -595,6 +595,189 @@ pub trait LockableCurrency<AccountId>: Currency<AccountId> {
 	);
 }
 
+/// Abstraction over a fungible multi-asset system, where the asset is chosen at call time.
+///
+/// Unlike `Currency`, operations that create or destroy funds adjust the total issuance of the
+/// asset directly rather than returning an imbalance.
+pub trait MultiCurrency<AccountId> {
+	/// The identifier of an asset.
+	type AssetId: FullCodec + Copy + Eq + MaybeDebug;
+
+	/// The balance of an account.
+	type Balance: SimpleArithmetic + FullCodec + Copy + MaybeSerializeDebug + Default;
+
+	// PUBLIC IMMUTABLES
+
+	/// The combined balance of `who` in `asset_id`.
+	fn total_balance(asset_id: &Self::AssetId, who: &AccountId) -> Self::Balance;
+
+	/// Same result as `slash(asset_id, who, value)` (but without the side-effects) assuming there
+	/// are no balance changes in the meantime and only the reserved balance is not taken into account.
+	fn can_slash(asset_id: &Self::AssetId, who: &AccountId, value: Self::Balance) -> bool;
+
+	/// The total amount of issuance of `asset_id`.
+	fn total_issuance(asset_id: &Self::AssetId) -> Self::Balance;
+
+	/// The minimum balance any single account may have of `asset_id`.
+	fn minimum_balance(asset_id: &Self::AssetId) -> Self::Balance;
+
+	/// The 'free' balance of `who` in `asset_id`.
+	fn free_balance(asset_id: &Self::AssetId, who: &AccountId) -> Self::Balance;
+
+	/// Returns `Ok` iff the account is able to make a withdrawal of the given amount of `asset_id`
+	/// for the given reason. Basically, it's just a dry-run of `withdraw`.
+	///
+	/// `Err(...)` with the reason why not otherwise.
+	fn ensure_can_withdraw(
+		asset_id: &Self::AssetId,
+		who: &AccountId,
+		_amount: Self::Balance,
+		reason: WithdrawReason,
+		new_balance: Self::Balance,
+	) -> result::Result<(), &'static str>;
+
+	/// Returns `Ok` iff `source` is permitted to send `asset_id` to `dest`, e.g. both pass any
+	/// transfer restrictions of the asset. Balances are not checked.
+	///
+	/// `Err(...)` with the reason why not otherwise.
+	fn ensure_can_transfer(
+		asset_id: &Self::AssetId,
+		source: &AccountId,
+		dest: &AccountId,
+	) -> result::Result<(), &'static str>;
+
+	// PUBLIC MUTABLES (DANGEROUS)
+
+	/// Transfer some liquid free balance of `asset_id` to another account.
+	fn transfer(
+		asset_id: &Self::AssetId,
+		source: &AccountId,
+		dest: &AccountId,
+		value: Self::Balance,
+	) -> result::Result<(), &'static str>;
+
+	/// Mints `value` of `asset_id` to the free balance of `who`, increasing the total issuance
+	/// of the asset.
+	fn deposit(
+		asset_id: &Self::AssetId,
+		who: &AccountId,
+		value: Self::Balance,
+	) -> result::Result<(), &'static str>;
+
+	/// Removes `value` of `asset_id` from the free balance of `who` for `reason`, decreasing the
+	/// total issuance of the asset. If `liveness` is `KeepAlive`, then no less than the minimum
+	/// balance must be left remaining.
+	///
+	/// This checks any locks and liquidity requirements. If the removal is not possible, then it
+	/// returns `Err`.
+	fn withdraw(
+		asset_id: &Self::AssetId,
+		who: &AccountId,
+		value: Self::Balance,
+		reason: WithdrawReason,
+		liveness: ExistenceRequirement,
+	) -> result::Result<(), &'static str>;
+
+	/// Deducts up to `value` of `asset_id` from the combined balance of `who`, preferring to deduct
+	/// from the free balance, and decreases the total issuance of the asset accordingly. This
+	/// function cannot fail.
+	///
+	/// As much funds up to `value` will be deducted as possible. If this is less than `value`,
+	/// then a non-zero amount will be returned.
+	fn slash(asset_id: &Self::AssetId, who: &AccountId, value: Self::Balance) -> Self::Balance;
+
+	/// Force the free balance of `who` in `asset_id` to `balance`, adjusting the total issuance of
+	/// the asset by the difference. The minimum balance is enforced as by any other change, so an
+	/// account left with less is reaped, but no checks are made on locks. This is only for callers
+	/// which have already checked them, e.g. when committing a batch of balance changes.
+	fn make_free_balance_be(
+		asset_id: &Self::AssetId,
+		who: &AccountId,
+		balance: Self::Balance,
+	) -> UpdateBalanceOutcome;
+}
+
+/// A multi-asset system where funds can be reserved from the user.
+pub trait MultiReservableCurrency<AccountId>: MultiCurrency<AccountId> {
+	/// Same result as `reserve(asset_id, who, value)` (but without the side-effects) assuming there
+	/// are no balance changes in the meantime.
+	fn can_reserve(asset_id: &Self::AssetId, who: &AccountId, value: Self::Balance) -> bool;
+
+	/// Deducts up to `value` of `asset_id` from the reserved balance of `who`, decreasing the total
+	/// issuance of the asset accordingly. This function cannot fail.
+	///
+	/// As much funds up to `value` will be deducted as possible. If the reserve balance of `who`
+	/// is less than `value`, then a non-zero amount will be returned.
+	fn slash_reserved(asset_id: &Self::AssetId, who: &AccountId, value: Self::Balance) -> Self::Balance;
+
+	/// The amount of the balance of `asset_id` of a given account that is externally reserved; this
+	/// can still get slashed, but gets slashed last of all.
+	fn reserved_balance(asset_id: &Self::AssetId, who: &AccountId) -> Self::Balance;
+
+	/// Moves `value` of `asset_id` from free balance to reserved balance.
+	///
+	/// If the free balance is lower than `value`, then no funds will be moved and an `Err` will
+	/// be returned to notify of this. This is different behavior than `unreserve`.
+	fn reserve(asset_id: &Self::AssetId, who: &AccountId, value: Self::Balance) -> result::Result<(), &'static str>;
+
+	/// Moves up to `value` of `asset_id` from reserved balance to free balance. This function
+	/// cannot fail.
+	///
+	/// As much funds up to `value` will be moved as possible. If the reserve balance of `who`
+	/// is less than `value`, then the remaining amount will be returned.
+	fn unreserve(asset_id: &Self::AssetId, who: &AccountId, value: Self::Balance) -> Self::Balance;
+
+	/// Moves up to `value` of `asset_id` from reserved balance of account `slashed` to free balance
+	/// of account `beneficiary`.
+	///
+	/// As much funds up to `value` will be deducted as possible. If this is less than `value`,
+	/// then `Ok(non_zero)` will be returned.
+	fn repatriate_reserved(
+		asset_id: &Self::AssetId,
+		slashed: &AccountId,
+		beneficiary: &AccountId,
+		value: Self::Balance,
+	) -> result::Result<Self::Balance, &'static str>;
+}
+
+/// A multi-asset system whose accounts can have liquidity restrictions on each asset.
+pub trait MultiLockableCurrency<AccountId>: MultiCurrency<AccountId> {
+	/// The quantity used to denote time; usually just a `BlockNumber`.
+	type Moment;
+
+	/// Create a new lock on the balance of `asset_id` of account `who`.
+	///
+	/// If the lock `id` already exists for the asset, this will update it.
+	fn set_lock(
+		asset_id: &Self::AssetId,
+		id: LockIdentifier,
+		who: &AccountId,
+		amount: Self::Balance,
+		until: Self::Moment,
+		reasons: WithdrawReasons,
+	);
+
+	/// Changes a lock on the balance of `asset_id` (selected by `id`) so that it becomes less
+	/// liquid in all parameters or creates a new one if it does not exist.
+	///
+	/// See `LockableCurrency::extend_lock`.
+	fn extend_lock(
+		asset_id: &Self::AssetId,
+		id: LockIdentifier,
+		who: &AccountId,
+		amount: Self::Balance,
+		until: Self::Moment,
+		reasons: WithdrawReasons,
+	);
+
+	/// Remove an existing lock on the balance of `asset_id`.
+	fn remove_lock(
+		asset_id: &Self::AssetId,
+		id: LockIdentifier,
+		who: &AccountId,
+	);
+}
+
 bitmask! {
 	/// Reasons for moving funds out of an account.
 	#[derive(Encode, Decode)]
@@ -0,0 +1,93 @@
+// Copyright 2019 Plug New Zealand Limited
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// This module implements the following manufacturing mode:
+///
+/// # MasterToNBatch
+/// Manufacture a single batch transaction from the master account
+/// to `num` randomly created accounts, one transfer each.
+///
+///   A -> [B, C, D, ... x `num`]
+
+use std::sync::Arc;
+
+use log::info;
+use client::Client;
+use client::block_builder::api::BlockBuilder;
+use client::runtime_api::ConstructRuntimeApi;
+use primitives::{Blake2Hasher, Hasher};
+use sr_primitives::traits::{Block as BlockT, ProvideRuntimeApi, One, Zero};
+use sr_primitives::generic::BlockId;
+
+use crate::{RuntimeAdapter, create_block};
+
+pub fn next<RA, Backend, Exec, Block, RtApi>(
+	factory_state: &mut RA,
+	client: &Arc<Client<Backend, Exec, Block, RtApi>>,
+	version: u32,
+	genesis_hash: <RA::Block as BlockT>::Hash,
+	prior_block_hash: <RA::Block as BlockT>::Hash,
+	prior_block_id: BlockId<Block>,
+) -> Option<Block>
+where
+	Block: BlockT<Hash = <Blake2Hasher as Hasher>::Out>,
+	Exec: client::CallExecutor<Block, Blake2Hasher> + Send + Sync + Clone,
+	Backend: client::backend::Backend<Block, Blake2Hasher> + Send,
+	Client<Backend, Exec, Block, RtApi>: ProvideRuntimeApi,
+	<Client<Backend, Exec, Block, RtApi> as ProvideRuntimeApi>::Api: BlockBuilder<Block>,
+	RtApi: ConstructRuntimeApi<Block, Client<Backend, Exec, Block, RtApi>> + Send + Sync,
+	RA: RuntimeAdapter,
+{
+	// the whole batch goes into a single block
+	if factory_state.block_no() > Zero::zero() {
+		return None;
+	}
+
+	let from = (RA::master_account_id(), RA::master_account_secret());
+
+	let mut destinations = Vec::new();
+	let mut seed = factory_state.start_number();
+	while seed < factory_state.start_number() + factory_state.num() {
+		destinations.push((RA::gen_random_account_id(&seed), RA::minimum_balance()));
+		seed = seed + RA::Number::one();
+	}
+
+	let batch = factory_state.batch_transfer_extrinsic(
+		&from.0,
+		&from.1,
+		&destinations,
+		version,
+		&genesis_hash,
+		&prior_block_hash,
+	);
+
+	let inherents = RA::inherent_extrinsics(&factory_state);
+	let inherents = client.runtime_api().inherent_extrinsics(&prior_block_id, inherents)
+		.expect("Failed to create inherent extrinsics");
+
+	let block = create_block::<RA, _, _, _, _>(&client, batch, inherents);
+
+	factory_state.set_block_no(factory_state.block_no() + RA::Number::one());
+
+	info!(
+		"Created block {} with hash {}. Transferring {} from {} to {} accounts in a batch.",
+		factory_state.block_no(),
+		prior_block_hash,
+		RA::minimum_balance(),
+		from.0,
+		destinations.len()
+	);
+
+	Some(block)
+}
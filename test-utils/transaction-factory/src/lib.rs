@@ -42,6 +42,7 @@ use sr_primitives::traits::{
 pub use crate::modes::Mode;
 
 pub mod modes;
+mod batch_mode;
 mod complex_mode;
 mod simple_modes;
 
@@ -79,6 +80,16 @@ pub trait RuntimeAdapter {
 		prior_block_hash: &<Self::Block as BlockT>::Hash,
 	) -> <Self::Block as BlockT>::Extrinsic;
 
+	fn batch_transfer_extrinsic(
+		&self,
+		sender: &Self::AccountId,
+		key: &Self::Secret,
+		destinations: &[(Self::AccountId, Self::Balance)],
+		version: u32,
+		genesis_hash: &<Self::Block as BlockT>::Hash,
+		prior_block_hash: &<Self::Block as BlockT>::Hash,
+	) -> <Self::Block as BlockT>::Extrinsic;
+
 	fn inherent_extrinsics(&self) -> InherentData;
 
 	fn minimum_balance() -> Self::Balance;
@@ -130,6 +141,14 @@ where
 			best_hash.into(),
 			best_block_id,
 		),
+		Mode::MasterToNBatch => batch_mode::next::<RA, _, _, _, _>(
+			&mut factory_state,
+			&client,
+			version,
+			genesis_hash,
+			best_hash.into(),
+			best_block_id,
+		),
 		_ => simple_modes::next::<RA, _, _, _, _>(
 			&mut factory_state,
 			&client,